@@ -5,10 +5,13 @@
 use crate::service::FullClient;
 
 use node_template_runtime as runtime;
-use runtime::{AccountId, Balance, BalancesCall, SystemCall};
+use runtime::{
+	pallet_kitties::{self, KittyId},
+	pallet_poe, AccountId, Balance, BalancesCall, StorageMap, StorageValue, SystemCall,
+};
 use sc_cli::Result;
-use sc_client_api::BlockBackend;
-use sp_core::{Encode, Pair};
+use sc_client_api::{BlockBackend, StorageProvider};
+use sp_core::{storage::StorageKey, Decode, Encode, Pair};
 use sp_inherents::{InherentData, InherentDataProvider};
 use sp_keyring::Sr25519Keyring;
 use sp_runtime::{OpaqueExtrinsic, SaturatedConversion};
@@ -95,6 +98,150 @@ impl frame_benchmarking_cli::ExtrinsicBuilder for TransferKeepAliveBuilder {
 	}
 }
 
+/// Generates `KittiesModule::create` extrinsics for the benchmarks.
+///
+/// Note: Should only be used for benchmarking.
+pub struct CreateKittyBuilder {
+	client: Arc<FullClient>,
+}
+
+impl CreateKittyBuilder {
+	/// Creates a new [`Self`] from the given client.
+	pub fn new(client: Arc<FullClient>) -> Self {
+		Self { client }
+	}
+}
+
+impl frame_benchmarking_cli::ExtrinsicBuilder for CreateKittyBuilder {
+	fn pallet(&self) -> &str {
+		"kitties"
+	}
+
+	fn extrinsic(&self) -> &str {
+		"create"
+	}
+
+	fn build(&self, nonce: u32) -> std::result::Result<OpaqueExtrinsic, &'static str> {
+		let acc = Sr25519Keyring::Bob.pair();
+		let extrinsic: OpaqueExtrinsic = create_benchmark_extrinsic(
+			self.client.as_ref(),
+			acc,
+			pallet_kitties::Call::<runtime::Runtime>::create { name: *b"benchkit" }.into(),
+			nonce,
+		)
+		.into();
+
+		Ok(extrinsic)
+	}
+}
+
+/// Generates `KittiesModule::transfer` extrinsics for the benchmarks.
+///
+/// The kitty is the first one Bob owns at the best block. It is passed back and forth between Bob
+/// and Alice, so every transfer succeeds; even nonces are Bob's transfers and odd nonces Alice's.
+///
+/// Note: Should only be used for benchmarking.
+pub struct TransferKittyBuilder {
+	client: Arc<FullClient>,
+	kitty_id: Option<KittyId>,
+}
+
+impl TransferKittyBuilder {
+	/// Creates a new [`Self`] from the given client.
+	pub fn new(client: Arc<FullClient>) -> Self {
+		let kitty_id = first_kitty_of(&client, &Sr25519Keyring::Bob.to_account_id());
+		Self { client, kitty_id }
+	}
+}
+
+/// The lowest id of the kitties `owner` holds at the best block.
+fn first_kitty_of(client: &FullClient, owner: &AccountId) -> Option<KittyId> {
+	let best_hash = client.chain_info().best_hash;
+	let read = |key: Vec<u8>| client.storage(best_hash, &StorageKey(key)).ok().flatten();
+
+	let next_kitty_id = read(pallet_kitties::NextKittyId::<runtime::Runtime>::hashed_key().to_vec())
+		.and_then(|data| KittyId::decode(&mut &data.0[..]).ok())
+		.unwrap_or_default();
+	(0..next_kitty_id).find(|kitty_id| {
+		read(pallet_kitties::KittyOwner::<runtime::Runtime>::hashed_key_for(kitty_id))
+			.and_then(|data| AccountId::decode(&mut &data.0[..]).ok())
+			.map_or(false, |kitty_owner| &kitty_owner == owner)
+	})
+}
+
+impl frame_benchmarking_cli::ExtrinsicBuilder for TransferKittyBuilder {
+	fn pallet(&self) -> &str {
+		"kitties"
+	}
+
+	fn extrinsic(&self) -> &str {
+		"transfer"
+	}
+
+	fn build(&self, nonce: u32) -> std::result::Result<OpaqueExtrinsic, &'static str> {
+		let kitty_id = self.kitty_id.ok_or("Bob owns no kitty to transfer")?;
+		let (sender, recipient) = if nonce % 2 == 0 {
+			(Sr25519Keyring::Bob, Sr25519Keyring::Alice)
+		} else {
+			(Sr25519Keyring::Alice, Sr25519Keyring::Bob)
+		};
+		let extrinsic: OpaqueExtrinsic = create_benchmark_extrinsic(
+			self.client.as_ref(),
+			sender.pair(),
+			pallet_kitties::Call::<runtime::Runtime>::transfer {
+				recipient: recipient.to_account_id(),
+				kitty_id,
+			}
+			.into(),
+			nonce / 2,
+		)
+		.into();
+
+		Ok(extrinsic)
+	}
+}
+
+/// Generates `PoeModule::create_claim` extrinsics for the benchmarks, with a new claim per nonce.
+///
+/// Note: Should only be used for benchmarking.
+pub struct CreateClaimBuilder {
+	client: Arc<FullClient>,
+}
+
+impl CreateClaimBuilder {
+	/// Creates a new [`Self`] from the given client.
+	pub fn new(client: Arc<FullClient>) -> Self {
+		Self { client }
+	}
+}
+
+impl frame_benchmarking_cli::ExtrinsicBuilder for CreateClaimBuilder {
+	fn pallet(&self) -> &str {
+		"poe"
+	}
+
+	fn extrinsic(&self) -> &str {
+		"create_claim"
+	}
+
+	fn build(&self, nonce: u32) -> std::result::Result<OpaqueExtrinsic, &'static str> {
+		let acc = Sr25519Keyring::Bob.pair();
+		let claim = [&b"benchmark-claim-"[..], &nonce.to_le_bytes()].concat();
+		let extrinsic: OpaqueExtrinsic = create_benchmark_extrinsic(
+			self.client.as_ref(),
+			acc,
+			pallet_poe::Call::<runtime::Runtime>::create_claim {
+				claim: claim.try_into().map_err(|_| "claim exceeds MaxClaimLength")?,
+			}
+			.into(),
+			nonce,
+		)
+		.into();
+
+		Ok(extrinsic)
+	}
+}
+
 /// Create a transaction using the given `call`.
 ///
 /// Note: Should only be used for benchmarking.
@@ -108,6 +255,23 @@ pub fn create_benchmark_extrinsic(
 	let best_hash = client.chain_info().best_hash;
 	let best_block = client.chain_info().best_number;
 
+	sign_benchmark_extrinsic(sender, call, nonce, genesis_hash, best_hash, best_block)
+}
+
+/// Create a transaction using the given `call`, mortal from `best_block`.
+///
+/// Unlike [`create_benchmark_extrinsic`] this needs no local client, so it can sign
+/// transactions for a remote node.
+///
+/// Note: Should only be used for benchmarking.
+pub fn sign_benchmark_extrinsic(
+	sender: sp_core::sr25519::Pair,
+	call: runtime::RuntimeCall,
+	nonce: u32,
+	genesis_hash: runtime::Hash,
+	best_hash: runtime::Hash,
+	best_block: runtime::BlockNumber,
+) -> runtime::UncheckedExtrinsic {
 	let period = runtime::BlockHashCount::get()
 		.checked_next_power_of_two()
 		.map(|c| c / 2)
@@ -0,0 +1,267 @@
+//! Reference signer for `--keystore-uri`, serving the keys of a local keystore directory.
+//!
+//! Keys are added to the directory as usual, e.g. with
+//! `node-template key insert --keystore-path <dir> --key-type babe --scheme sr25519`, and the
+//! node is started with `--keystore-uri` pointing at `--listen`. Requests are served one at a
+//! time, which is plenty for a single authoring node.
+//!
+//! Anyone who can connect can sign with the keys, e.g. make the validator equivocate, so HTTP is
+//! only served on loopback addresses unless `--unsafe-external` is given. Only trusted nodes
+//! should be allowed to connect: VRF transcript labels and item names are kept for the lifetime
+//! of the signer.
+
+use clap::Parser;
+use node_template::remote_keystore::{decode_hex, Endpoint, Scheme};
+use sc_keystore::LocalKeystore;
+use serde_json::{json, Value};
+use sp_core::{
+	bytes::to_hex,
+	crypto::{CryptoTypePublicPair, KeyTypeId, SecretString},
+	sr25519,
+};
+use sp_keystore::{
+	vrf::{VRFTranscriptData, VRFTranscriptValue},
+	SyncCryptoStore,
+};
+use std::{
+	collections::BTreeSet,
+	io::{BufRead, BufReader, Read, Write},
+	net::{TcpListener, ToSocketAddrs},
+	os::unix::{fs::FileTypeExt, net::UnixListener},
+	path::{Path, PathBuf},
+	sync::Mutex,
+};
+
+#[derive(Debug, Parser)]
+struct Args {
+	/// The keystore directory holding the keys to sign with.
+	#[arg(long)]
+	keystore_path: PathBuf,
+
+	/// Password of the keystore, if it has one.
+	#[arg(long)]
+	password: Option<String>,
+
+	/// Where to listen, `http://host:port[/path]` or `unix:///path/to/socket`.
+	#[arg(long)]
+	listen: Endpoint,
+
+	/// Serve HTTP on non-loopback addresses. Requests are not authenticated, so only use this
+	/// behind a firewall or proxy that lets nothing but the node through.
+	#[arg(long)]
+	unsafe_external: bool,
+}
+
+/// Longest request accepted, far more than any signing request needs.
+const MAX_CONTENT_LENGTH: usize = 1024 * 1024;
+
+fn main() -> Result<(), String> {
+	let args = Args::parse();
+	let keystore = LocalKeystore::open(&args.keystore_path, args.password.map(SecretString::new))
+		.map_err(|e| format!("Opening keystore {} failed: {}", args.keystore_path.display(), e))?;
+
+	match args.listen {
+		Endpoint::Http { address, path } => {
+			if !args.unsafe_external {
+				ensure_loopback(&address)?;
+			}
+			serve_http(&keystore, &address, &path)
+		},
+		Endpoint::Unix(path) => serve_unix(&keystore, &path),
+	}
+	.map_err(|e| e.to_string())
+}
+
+/// Refuse to serve on addresses other nodes can reach.
+fn ensure_loopback(address: &str) -> Result<(), String> {
+	let mut addresses = address
+		.to_socket_addrs()
+		.map_err(|e| format!("Resolving {} failed: {}", address, e))?;
+	if addresses.all(|address| address.ip().is_loopback()) {
+		Ok(())
+	} else {
+		Err(format!(
+			"{} is not a loopback address; pass --unsafe-external to serve unauthenticated \
+			 signing requests on it",
+			address
+		))
+	}
+}
+
+fn serve_http(keystore: &LocalKeystore, address: &str, path: &str) -> std::io::Result<()> {
+	let listener = TcpListener::bind(address)?;
+	eprintln!("Signing on http://{}{}", address, path);
+	for stream in listener.incoming() {
+		let result = stream.and_then(|stream| {
+			let mut reader = BufReader::new(stream.try_clone()?);
+			let mut request_line = String::new();
+			reader.read_line(&mut request_line)?;
+
+			let mut content_length = 0;
+			loop {
+				let mut header = String::new();
+				if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+					break
+				}
+				if let Some((name, value)) = header.split_once(':') {
+					if name.eq_ignore_ascii_case("content-length") {
+						content_length = value.trim().parse().unwrap_or(0);
+					}
+				}
+			}
+			let (status, body) = if content_length > MAX_CONTENT_LENGTH {
+				("413 Payload Too Large", String::new())
+			} else if request_line.split(' ').take(2).eq(["POST", path]) {
+				let mut body = vec![0; content_length];
+				reader.read_exact(&mut body)?;
+				("200 OK", respond(keystore, &String::from_utf8_lossy(&body)))
+			} else {
+				("404 Not Found", String::new())
+			};
+			write!(
+				&stream,
+				"HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+				 Connection: close\r\n\r\n{}",
+				status,
+				body.len(),
+				body
+			)
+		});
+		if let Err(e) = result {
+			eprintln!("Serving request failed: {}", e);
+		}
+	}
+	Ok(())
+}
+
+fn serve_unix(keystore: &LocalKeystore, path: &Path) -> std::io::Result<()> {
+	// A socket left behind by a previous run would make binding fail.
+	if std::fs::metadata(path).map_or(false, |metadata| metadata.file_type().is_socket()) {
+		std::fs::remove_file(path)?;
+	}
+	let listener = UnixListener::bind(path)?;
+	eprintln!("Signing on unix://{}", path.display());
+	for stream in listener.incoming() {
+		let result = stream.and_then(|stream| {
+			let mut request = String::new();
+			BufReader::new((&stream).take(MAX_CONTENT_LENGTH as u64)).read_line(&mut request)?;
+			writeln!(&stream, "{}", respond(keystore, &request))
+		});
+		if let Err(e) = result {
+			eprintln!("Serving request failed: {}", e);
+		}
+	}
+	Ok(())
+}
+
+/// Answer a JSON-RPC request with the keys of `keystore`.
+fn respond(keystore: &LocalKeystore, request: &str) -> String {
+	let request: Value = match serde_json::from_str(request) {
+		Ok(request) => request,
+		Err(e) => return error_response(Value::Null, -32700, &e.to_string()),
+	};
+	let id = request.get("id").cloned().unwrap_or(Value::Null);
+	let method = request.get("method").and_then(Value::as_str).unwrap_or_default();
+	let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+	let result = match method {
+		"keystore_publicKeys" => key_params(&params).map(|(key_type, scheme)| {
+			let keys: Vec<Vec<u8>> = match scheme {
+				Scheme::Sr25519 => keystore
+					.sr25519_public_keys(key_type)
+					.into_iter()
+					.map(|public| public.0.to_vec())
+					.collect(),
+				Scheme::Ed25519 => keystore
+					.ed25519_public_keys(key_type)
+					.into_iter()
+					.map(|public| public.0.to_vec())
+					.collect(),
+			};
+			keys.iter().map(|public| to_hex(public, false)).collect()
+		}),
+		"keystore_sign" => key_params(&params).and_then(|(key_type, scheme)| {
+			let public = decode_hex(params.get("public").ok_or("missing `public`")?)?;
+			let payload = decode_hex(params.get("payload").ok_or("missing `payload`")?)?;
+			let key = CryptoTypePublicPair(scheme.crypto_id(), public);
+			match SyncCryptoStore::sign_with(keystore, key_type, &key, &payload) {
+				Ok(signature) => Ok(signature.map_or(Value::Null, |s| to_hex(&s, false).into())),
+				Err(e) => Err(e.to_string()),
+			}
+		}),
+		"keystore_vrfSign" => key_params(&params).and_then(|(key_type, scheme)| {
+			if scheme != Scheme::Sr25519 {
+				return Err("VRF signing needs an sr25519 key".into())
+			}
+			let public = decode_hex(params.get("public").ok_or("missing `public`")?)?;
+			let public = sr25519::Public::try_from(&public[..])
+				.map_err(|_| "invalid sr25519 public key".to_string())?;
+			let transcript = transcript_data(&params)?;
+			match SyncCryptoStore::sr25519_vrf_sign(keystore, key_type, &public, transcript) {
+				Ok(signature) => Ok(signature.map_or(Value::Null, |s| {
+					json!({
+						"output": to_hex(&s.output.to_bytes(), false),
+						"proof": to_hex(&s.proof.to_bytes(), false),
+					})
+				})),
+				Err(e) => Err(e.to_string()),
+			}
+		}),
+		_ => return error_response(id, -32601, &format!("unknown method `{}`", method)),
+	};
+
+	match result {
+		Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }).to_string(),
+		Err(e) => error_response(id, -32602, &e),
+	}
+}
+
+fn key_params(params: &Value) -> Result<(KeyTypeId, Scheme), String> {
+	let key_type = params.get("keyType").and_then(Value::as_str).ok_or("missing `keyType`")?;
+	let key_type =
+		KeyTypeId::try_from(key_type).map_err(|_| format!("invalid key type `{}`", key_type))?;
+	let scheme = match params.get("scheme").and_then(Value::as_str) {
+		Some("sr25519") => Scheme::Sr25519,
+		Some("ed25519") => Scheme::Ed25519,
+		scheme => return Err(format!("unsupported scheme {:?}", scheme)),
+	};
+	Ok((key_type, scheme))
+}
+
+fn transcript_data(params: &Value) -> Result<VRFTranscriptData, String> {
+	let label = decode_hex(params.get("label").ok_or("missing `label`")?)?;
+	let items = params.get("items").and_then(Value::as_array).ok_or("missing `items`")?;
+	let items = items
+		.iter()
+		.map(|item| {
+			let name = item.get("name").and_then(Value::as_str).ok_or("missing item `name`")?;
+			let value = match (item.get("bytes"), item.get("u64").and_then(Value::as_u64)) {
+				(Some(bytes), None) => VRFTranscriptValue::Bytes(decode_hex(bytes)?),
+				(None, Some(value)) => VRFTranscriptValue::U64(value),
+				_ => return Err(format!("item `{}` needs either `bytes` or `u64`", name)),
+			};
+			let name = std::str::from_utf8(intern(name.as_bytes()))
+				.expect("interned from a `str`; qed");
+			Ok((name, value))
+		})
+		.collect::<Result<_, String>>()?;
+	Ok(VRFTranscriptData { label: intern(&label), items })
+}
+
+/// Transcripts take `'static` labels and item names. BABE only uses a handful, so every
+/// distinct one is leaked once and reused afterwards.
+fn intern(bytes: &[u8]) -> &'static [u8] {
+	static INTERNED: Mutex<BTreeSet<&'static [u8]>> = Mutex::new(BTreeSet::new());
+	let mut interned = INTERNED.lock().unwrap_or_else(|e| e.into_inner());
+	if let Some(bytes) = interned.get(bytes) {
+		return bytes
+	}
+	let bytes: &'static [u8] = Box::leak(bytes.to_vec().into_boxed_slice());
+	interned.insert(bytes);
+	bytes
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> String {
+	json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+		.to_string()
+}
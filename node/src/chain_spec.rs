@@ -1,12 +1,18 @@
 use node_template_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-	SystemConfig, WASM_BINARY,
+	opaque::SessionKeys, AccountId, BabeConfig, Balance, BalancesConfig, CouncilConfig,
+	DemocracyConfig, GenesisConfig, GrandpaConfig, ImOnlineConfig, KittiesModuleConfig,
+	PoeModuleConfig, SessionConfig, Signature, StakerStatus, StakingConfig, SudoConfig,
+	SystemConfig, BABE_GENESIS_EPOCH_CONFIG, WASM_BINARY,
 };
+use pallet_im_online::sr25519::AuthorityId as ImOnlineId;
 use sc_service::ChainType;
-use sp_consensus_aura::sr25519::AuthorityId as AuraId;
+use sp_consensus_babe::AuthorityId as BabeId;
 use sp_consensus_grandpa::AuthorityId as GrandpaId;
-use sp_core::{sr25519, Pair, Public};
-use sp_runtime::traits::{IdentifyAccount, Verify};
+use sp_core::{blake2_128, sr25519, Pair, Public};
+use sp_runtime::{
+	traits::{IdentifyAccount, Verify},
+	Perbill,
+};
 
 // The URL for the telemetry server.
 // const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
@@ -31,9 +37,35 @@ where
 	AccountPublic::from(get_from_seed::<TPublic>(seed)).into_account()
 }
 
-/// Generate an Aura authority key.
-pub fn authority_keys_from_seed(s: &str) -> (AuraId, GrandpaId) {
-	(get_from_seed::<AuraId>(s), get_from_seed::<GrandpaId>(s))
+/// Balance every genesis validator bonds from its stash.
+const STASH: Balance = 1 << 50;
+
+/// Generate the stash and controller accounts and the session keys of a validator.
+pub fn authority_keys_from_seed(s: &str) -> (AccountId, AccountId, GrandpaId, BabeId, ImOnlineId) {
+	(
+		get_account_id_from_seed::<sr25519::Public>(&format!("{}//stash", s)),
+		get_account_id_from_seed::<sr25519::Public>(s),
+		get_from_seed::<GrandpaId>(s),
+		get_from_seed::<BabeId>(s),
+		get_from_seed::<ImOnlineId>(s),
+	)
+}
+
+fn session_keys(grandpa: GrandpaId, babe: BabeId, im_online: ImOnlineId) -> SessionKeys {
+	SessionKeys { babe, grandpa, im_online }
+}
+
+/// Generate a genesis kitty owned by the account derived from `seed`.
+///
+/// The DNA is derived from the owner seed and kitty name so it is stable across chain specs.
+pub fn kitty_from_seed(seed: &str, name: &[u8; 8]) -> (AccountId, [u8; 8], [u8; 16]) {
+	let dna = blake2_128(&[seed.as_bytes(), &name[..]].concat());
+	(get_account_id_from_seed::<sr25519::Public>(seed), *name, dna)
+}
+
+/// Generate a genesis proof-of-existence claim owned by the account derived from `seed`.
+pub fn claim_from_seed(seed: &str, claim: &[u8]) -> (Vec<u8>, AccountId) {
+	(claim.to_vec(), get_account_id_from_seed::<sr25519::Public>(seed))
 }
 
 pub fn development_config() -> Result<ChainSpec, String> {
@@ -48,10 +80,15 @@ pub fn development_config() -> Result<ChainSpec, String> {
 		move || {
 			testnet_genesis(
 				wasm_binary,
-				// Initial PoA authorities
+				// Initial validators
 				vec![authority_keys_from_seed("Alice")],
 				// Sudo account
 				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				// Council members
+				vec![
+					get_account_id_from_seed::<sr25519::Public>("Alice"),
+					get_account_id_from_seed::<sr25519::Public>("Bob"),
+				],
 				// Pre-funded accounts
 				vec![
 					get_account_id_from_seed::<sr25519::Public>("Alice"),
@@ -59,6 +96,17 @@ pub fn development_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
 				],
+				// Initial kitties
+				vec![
+					kitty_from_seed("Alice", b"kitty000"),
+					kitty_from_seed("Alice", b"kitty001"),
+					kitty_from_seed("Bob", b"kitty002"),
+				],
+				// Initial proof-of-existence claims
+				vec![
+					claim_from_seed("Alice", b"dev-claim-alice"),
+					claim_from_seed("Bob", b"dev-claim-bob"),
+				],
 				true,
 			)
 		},
@@ -88,10 +136,16 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 		move || {
 			testnet_genesis(
 				wasm_binary,
-				// Initial PoA authorities
+				// Initial validators
 				vec![authority_keys_from_seed("Alice"), authority_keys_from_seed("Bob")],
 				// Sudo account
 				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				// Council members
+				vec![
+					get_account_id_from_seed::<sr25519::Public>("Alice"),
+					get_account_id_from_seed::<sr25519::Public>("Bob"),
+					get_account_id_from_seed::<sr25519::Public>("Charlie"),
+				],
 				// Pre-funded accounts
 				vec![
 					get_account_id_from_seed::<sr25519::Public>("Alice"),
@@ -107,6 +161,18 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
 				],
+				// Initial kitties
+				vec![
+					kitty_from_seed("Alice", b"kitty000"),
+					kitty_from_seed("Bob", b"kitty001"),
+					kitty_from_seed("Charlie", b"kitty002"),
+					kitty_from_seed("Dave", b"kitty003"),
+				],
+				// Initial proof-of-existence claims
+				vec![
+					claim_from_seed("Alice", b"local-claim-alice"),
+					claim_from_seed("Bob", b"local-claim-bob"),
+				],
 				true,
 			)
 		},
@@ -127,9 +193,12 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 /// Configure initial storage state for FRAME modules.
 fn testnet_genesis(
 	wasm_binary: &[u8],
-	initial_authorities: Vec<(AuraId, GrandpaId)>,
+	initial_authorities: Vec<(AccountId, AccountId, GrandpaId, BabeId, ImOnlineId)>,
 	root_key: AccountId,
+	council_members: Vec<AccountId>,
 	endowed_accounts: Vec<AccountId>,
+	initial_kitties: Vec<(AccountId, [u8; 8], [u8; 16])>,
+	initial_claims: Vec<(Vec<u8>, AccountId)>,
 	_enable_println: bool,
 ) -> GenesisConfig {
 	GenesisConfig {
@@ -141,16 +210,43 @@ fn testnet_genesis(
 			// Configure endowed accounts with initial balance of 1 << 60.
 			balances: endowed_accounts.iter().cloned().map(|k| (k, 1 << 60)).collect(),
 		},
-		aura: AuraConfig {
-			authorities: initial_authorities.iter().map(|x| (x.0.clone())).collect(),
+		// The session pallet sets the BABE, GRANDPA and im-online authorities from the keys.
+		babe: BabeConfig { authorities: vec![], epoch_config: Some(BABE_GENESIS_EPOCH_CONFIG) },
+		grandpa: GrandpaConfig { authorities: vec![] },
+		im_online: ImOnlineConfig { keys: vec![] },
+		session: SessionConfig {
+			keys: initial_authorities
+				.iter()
+				.map(|x| {
+					let keys = session_keys(x.2.clone(), x.3.clone(), x.4.clone());
+					(x.0.clone(), x.0.clone(), keys)
+				})
+				.collect(),
 		},
-		grandpa: GrandpaConfig {
-			authorities: initial_authorities.iter().map(|x| (x.1.clone(), 1)).collect(),
+		staking: StakingConfig {
+			validator_count: initial_authorities.len() as u32,
+			minimum_validator_count: initial_authorities.len() as u32,
+			invulnerables: initial_authorities.iter().map(|x| x.0.clone()).collect(),
+			slash_reward_fraction: Perbill::from_percent(10),
+			stakers: initial_authorities
+				.iter()
+				.map(|x| (x.0.clone(), x.1.clone(), STASH, StakerStatus::Validator))
+				.collect(),
+			..Default::default()
 		},
+		// Validators can be added by governance later on, next to the staked ones.
+		validator_set: Default::default(),
 		sudo: SudoConfig {
-			// Assign network admin rights.
-			key: Some(root_key),
+			// Assign network admin rights until governance takes over.
+			key: Some(root_key.clone()),
 		},
+		council: CouncilConfig { members: council_members, phantom: Default::default() },
+		democracy: DemocracyConfig::default(),
+		treasury: Default::default(),
+		assets: Default::default(),
 		transaction_payment: Default::default(),
+		poe_module: PoeModuleConfig { claims: initial_claims },
+		// 超级用户同时可以签发 kitty 领取券
+		kitties_module: KittiesModuleConfig { kitties: initial_kitties, minters: vec![root_key] },
 	}
 }
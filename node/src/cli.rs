@@ -1,3 +1,4 @@
+use crate::service::Sealing;
 use sc_cli::RunCmd;
 
 #[derive(Debug, clap::Parser)]
@@ -7,6 +8,13 @@ pub struct Cli {
 
 	#[clap(flatten)]
 	pub run: RunCmd,
+
+	/// Author blocks with manual seal instead of BABE and GRANDPA: `instant` seals a block for
+	/// every new transaction, `manual` only when asked through `engine_createBlock`, and a number
+	/// seals a block every that many milliseconds. `engine_createBlock` and
+	/// `engine_finalizeBlock` are available in every mode.
+	#[arg(long)]
+	pub sealing: Option<Sealing>,
 }
 
 #[derive(Debug, clap::Subcommand)]
@@ -50,4 +58,13 @@ pub enum Subcommand {
 
 	/// Db meta columns information.
 	ChainInfo(sc_cli::ChainInfoCmd),
+
+	/// Export the family tree of a kitty as Graphviz DOT or JSON.
+	FamilyTree(crate::family_tree::FamilyTreeCmd),
+
+	/// Issue a signed voucher for minting a kitty without balance.
+	IssueVoucher(crate::voucher::IssueVoucherCmd),
+
+	/// Flood a running node with transactions and report latency and throughput.
+	Loadgen(crate::loadgen::LoadgenCmd),
 }
@@ -0,0 +1,179 @@
+//! The `family-tree` subcommand, exporting a kitty's lineage from the local database.
+
+use crate::service::FullClient;
+
+use pallet_kitties_runtime_api::{KittiesApi, KittyId, KittyRelative};
+use sc_cli::{CliConfiguration, DatabaseParams, PruningParams, Result, SharedParams};
+use sp_api::ProvideRuntimeApi;
+
+use std::{collections::BTreeMap, fmt::Write, path::PathBuf, sync::Arc};
+
+/// Output format of the `family-tree` subcommand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum FamilyTreeFormat {
+	/// Graphviz DOT, render with e.g. `dot -Tsvg`.
+	Dot,
+	/// JSON list of kitties with their parents and depth relative to the kitty.
+	Json,
+}
+
+/// Which relatives of the kitty to export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum FamilyTreeDirection {
+	/// Parents, grandparents, ...
+	Ancestors,
+	/// Children, grandchildren, ...
+	Descendants,
+	/// Both ancestors and descendants.
+	Both,
+}
+
+/// Export the family tree of a kitty at the best block of the local database.
+#[derive(Debug, clap::Parser)]
+pub struct FamilyTreeCmd {
+	/// Id of the kitty whose family tree is exported.
+	pub kitty_id: KittyId,
+
+	/// Which relatives to include.
+	#[arg(long, value_enum, default_value_t = FamilyTreeDirection::Both)]
+	pub direction: FamilyTreeDirection,
+
+	/// Number of generations to walk in each direction, capped by the runtime.
+	#[arg(long, default_value_t = 8)]
+	pub depth: u32,
+
+	/// Output format.
+	#[arg(long, value_enum, default_value_t = FamilyTreeFormat::Dot)]
+	pub format: FamilyTreeFormat,
+
+	/// Write the family tree to this file instead of stdout.
+	#[arg(long, short)]
+	pub output: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+impl FamilyTreeCmd {
+	/// Run the command against the given client.
+	pub fn run(&self, client: Arc<FullClient>) -> Result<()> {
+		let best_hash = client.chain_info().best_hash;
+		let api = client.runtime_api();
+
+		// Depth relative to the requested kitty: negative for ancestors.
+		let mut family = BTreeMap::<KittyId, (i64, KittyRelative)>::new();
+		if self.direction != FamilyTreeDirection::Descendants {
+			let ancestors = api
+				.ancestors(best_hash, self.kitty_id, self.depth)
+				.map_err(|e| format!("Failed to query ancestors: {:?}", e))?;
+			for relative in ancestors {
+				family.insert(relative.kitty_id, (-(relative.depth as i64), relative));
+			}
+		}
+		if self.direction != FamilyTreeDirection::Ancestors {
+			let descendants = api
+				.descendants(best_hash, self.kitty_id, self.depth)
+				.map_err(|e| format!("Failed to query descendants: {:?}", e))?;
+			for relative in descendants {
+				family.insert(relative.kitty_id, (relative.depth as i64, relative));
+			}
+		}
+
+		if family.is_empty() {
+			return Err(
+				format!("Kitty {} does not exist at block {:?}", self.kitty_id, best_hash).into()
+			)
+		}
+
+		let rendered = match self.format {
+			FamilyTreeFormat::Dot => render_dot(self.kitty_id, &family),
+			FamilyTreeFormat::Json => render_json(self.kitty_id, &family)?,
+		};
+
+		match &self.output {
+			Some(path) => std::fs::write(path, rendered)?,
+			None => println!("{}", rendered),
+		}
+
+		Ok(())
+	}
+}
+
+impl CliConfiguration for FamilyTreeCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
+
+fn kitty_name(relative: &KittyRelative) -> String {
+	String::from_utf8_lossy(&relative.kitty.name).trim_end_matches('\0').to_string()
+}
+
+fn kitty_dna(relative: &KittyRelative) -> String {
+	format!("0x{}", sp_core::hexdisplay::HexDisplay::from(&relative.kitty.dna))
+}
+
+fn render_dot(root: KittyId, family: &BTreeMap<KittyId, (i64, KittyRelative)>) -> String {
+	let mut dot = String::from("digraph family_tree {\n\tnode [shape=box];\n");
+
+	for (kitty_id, (_, relative)) in family {
+		let style = if *kitty_id == root { ", style=bold" } else { "" };
+		let _ = writeln!(
+			dot,
+			"\t\"{}\" [label=\"#{} {}\\n{}\"{}];",
+			kitty_id,
+			kitty_id,
+			kitty_name(relative).escape_default(),
+			kitty_dna(relative),
+			style,
+		);
+	}
+
+	for (kitty_id, (_, relative)) in family {
+		if let Some((parent_1, parent_2)) = relative.parents {
+			for parent in [parent_1, parent_2] {
+				if family.contains_key(&parent) {
+					let _ = writeln!(dot, "\t\"{}\" -> \"{}\";", parent, kitty_id);
+				}
+			}
+		}
+	}
+
+	dot.push('}');
+	dot
+}
+
+fn render_json(root: KittyId, family: &BTreeMap<KittyId, (i64, KittyRelative)>) -> Result<String> {
+	let kitties = family
+		.iter()
+		.map(|(kitty_id, (depth, relative))| {
+			serde_json::json!({
+				"id": kitty_id,
+				"name": kitty_name(relative),
+				"dna": kitty_dna(relative),
+				"parents": relative.parents.map(|(parent_1, parent_2)| [parent_1, parent_2]),
+				"depth": depth,
+			})
+		})
+		.collect::<Vec<_>>();
+
+	serde_json::to_string_pretty(&serde_json::json!({ "root": root, "kitties": kitties }))
+		.map_err(|e| format!("Failed to serialize family tree: {}", e).into())
+}
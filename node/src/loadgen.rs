@@ -0,0 +1,362 @@
+//! The `loadgen` subcommand, flooding a running node with signed pallet transactions.
+
+use crate::benchmarking::sign_benchmark_extrinsic;
+
+use codec::Encode;
+use futures::{
+	channel::mpsc,
+	future::{self, Either},
+	StreamExt,
+};
+use jsonrpsee::{
+	core::client::{ClientT, Subscription, SubscriptionClientT},
+	rpc_params,
+	ws_client::{WsClient, WsClientBuilder},
+};
+use node_template_runtime::{
+	opaque::Header, pallet_kitties, pallet_poe, AccountId, Balance, BalancesCall, Hash, Index,
+	Runtime, RuntimeCall, UncheckedExtrinsic, EXISTENTIAL_DEPOSIT,
+};
+use sc_cli::{CliConfiguration, Result, SharedParams};
+use serde_json::Value;
+use sp_core::{blake2_256, bytes::from_hex, crypto::Ss58Codec, sr25519, Bytes, Pair};
+use sp_keyring::Sr25519Keyring;
+use sp_runtime::traits::Header as HeaderT;
+use std::{
+	collections::{HashMap, HashSet},
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+/// Submissions waiting for the node to accept them at once.
+const MAX_IN_FLIGHT: usize = 1000;
+
+/// Give up on the transactions not in a block yet once none was included for this long.
+const INCLUSION_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Flood a running node with balance transfers, kitty mints and proof-of-existence claims.
+///
+/// The transactions are signed by accounts derived from Alice as `//Alice//loadgen//<n>`, which
+/// Alice funds first, and are submitted at a fixed rate. Blocks are watched separately, so slow
+/// inclusion does not hold back submission. Once all of them are in a block or failed, the
+/// achieved submission rate, the inclusion latency and the throughput are reported.
+#[derive(Debug, clap::Parser)]
+pub struct LoadgenCmd {
+	/// WebSocket RPC endpoint of the node.
+	#[arg(long, default_value = "ws://127.0.0.1:9944")]
+	pub url: String,
+
+	/// Number of derived accounts signing transactions.
+	#[arg(long, default_value_t = 100)]
+	pub accounts: u32,
+
+	/// Total number of transactions to submit.
+	#[arg(long, default_value_t = 1000)]
+	pub count: u32,
+
+	/// Target number of transactions submitted per second.
+	#[arg(long, default_value_t = 100)]
+	pub tps: u32,
+
+	/// Balance Alice transfers to every derived account before the run.
+	#[arg(long, default_value_t = 1_000_000_000_000_000)]
+	pub endowment: Balance,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+}
+
+/// Chain data every transaction is signed against.
+struct Chain {
+	genesis_hash: Hash,
+	best_hash: Hash,
+	best_number: u32,
+}
+
+impl LoadgenCmd {
+	/// Fund the accounts, submit the transactions and print the report.
+	pub async fn run(&self) -> Result<()> {
+		if self.accounts == 0 || self.tps == 0 {
+			return Err("`--accounts` and `--tps` must be positive".into())
+		}
+		let client = WsClientBuilder::default()
+			.max_concurrent_requests(MAX_IN_FLIGHT)
+			.build(&self.url)
+			.await
+			.map(Arc::new)
+			.map_err(|e| format!("Connecting to {} failed: {}", self.url, e))?;
+		let chain = fetch_chain(&client).await?;
+
+		let accounts: Vec<sr25519::Pair> = (0..self.accounts)
+			.map(|n| {
+				sr25519::Pair::from_string(&format!("//Alice//loadgen//{}", n), None)
+					.expect("static derivation paths are valid; qed")
+			})
+			.collect();
+
+		// 先由 Alice 给所有账户转账, 全部上链后再开始
+		let alice = Sr25519Keyring::Alice.pair();
+		let nonce = next_index(&client, &alice).await?;
+		let funding: Vec<_> = accounts
+			.iter()
+			.zip(nonce..)
+			.map(|(account, nonce)| {
+				let call = BalancesCall::transfer_keep_alive {
+					dest: AccountId::from(account.public()).into(),
+					value: self.endowment,
+				};
+				sign(&chain, alice.clone(), call.into(), nonce)
+			})
+			.collect();
+		println!("Funding {} accounts with {}", accounts.len(), self.endowment);
+		let funded = submit_all(&client, funding, u32::MAX).await?;
+		if let Some(Err(e)) = funded.results.into_iter().find(Result::is_err) {
+			return Err(format!("Funding the accounts failed: {}", e).into())
+		}
+
+		let mut nonces = Vec::with_capacity(accounts.len());
+		for account in &accounts {
+			nonces.push(next_index(&client, account).await?);
+		}
+
+		// Sign everything up front so signing does not slow down submission.
+		let transactions: Vec<_> = (0..self.count as usize)
+			.map(|n| {
+				let index = n % accounts.len();
+				let account = &accounts[index];
+				let nonce = nonces[index];
+				nonces[index] += 1;
+				let call: RuntimeCall = match (n / accounts.len()) % 3 {
+					0 => BalancesCall::transfer_keep_alive {
+						dest: Sr25519Keyring::Alice.to_account_id().into(),
+						value: EXISTENTIAL_DEPOSIT,
+					}
+					.into(),
+					1 => pallet_kitties::Call::<Runtime>::create { name: *b"loadgen0" }.into(),
+					_ => pallet_poe::Call::<Runtime>::create_claim {
+						claim: blake2_256(&(account.public(), nonce).encode())
+							.to_vec()
+							.try_into()
+							.expect("MaxClaimLength is at least 32 bytes; qed"),
+					}
+					.into(),
+				};
+				sign(&chain, account.clone(), call, nonce)
+			})
+			.collect();
+
+		println!("Submitting {} transactions at {} TPS", transactions.len(), self.tps);
+		let Report { results, submission, elapsed } =
+			submit_all(&client, transactions, self.tps).await?;
+		println!(
+			"Submitted in {:.1}s: {:.1} TPS",
+			submission.as_secs_f64(),
+			results.len() as f64 / submission.as_secs_f64(),
+		);
+
+		let mut latencies: Vec<Duration> =
+			results.iter().filter_map(|r| r.as_ref().ok().copied()).collect();
+		latencies.sort();
+		let failed = results.len() - latencies.len();
+		println!("Included: {}, failed: {}", latencies.len(), failed);
+		for e in results.iter().filter_map(|r| r.as_ref().err()).take(5) {
+			println!("  e.g. {}", e);
+		}
+		if latencies.is_empty() {
+			return Ok(())
+		}
+
+		let percentile = |p: usize| latencies[(latencies.len() - 1) * p / 100].as_millis();
+		let total: Duration = latencies.iter().sum();
+		println!(
+			"Inclusion latency (ms): min {}, avg {}, p50 {}, p95 {}, max {}",
+			percentile(0),
+			(total / latencies.len() as u32).as_millis(),
+			percentile(50),
+			percentile(95),
+			percentile(100),
+		);
+		println!(
+			"Throughput: {:.1} TPS over {:.1}s",
+			latencies.len() as f64 / elapsed.as_secs_f64(),
+			elapsed.as_secs_f64(),
+		);
+
+		Ok(())
+	}
+}
+
+fn sign(chain: &Chain, sender: sr25519::Pair, call: RuntimeCall, nonce: Index) -> Bytes {
+	let extrinsic: UncheckedExtrinsic = sign_benchmark_extrinsic(
+		sender,
+		call,
+		nonce,
+		chain.genesis_hash,
+		chain.best_hash,
+		chain.best_number,
+	);
+	extrinsic.encode().into()
+}
+
+async fn fetch_chain(client: &WsClient) -> Result<Chain> {
+	let genesis_hash: Option<Hash> = client
+		.request("chain_getBlockHash", rpc_params![0u32])
+		.await
+		.map_err(|e| format!("Fetching the genesis hash failed: {}", e))?;
+	let best: Header = client
+		.request("chain_getHeader", rpc_params![])
+		.await
+		.map_err(|e| format!("Fetching the best header failed: {}", e))?;
+	Ok(Chain {
+		genesis_hash: genesis_hash.ok_or("The node has no genesis block")?,
+		best_hash: best.hash(),
+		best_number: best.number,
+	})
+}
+
+async fn next_index(client: &WsClient, account: &sr25519::Pair) -> Result<Index> {
+	let address = AccountId::from(account.public()).to_ss58check();
+	client
+		.request("system_accountNextIndex", rpc_params![address.clone()])
+		.await
+		.map_err(|e| format!("Fetching the nonce of {} failed: {}", address, e).into())
+}
+
+/// The outcome of [`submit_all`].
+struct Report {
+	/// How long each transaction took to get into a block, or why it did not.
+	results: Vec<std::result::Result<Duration, String>>,
+	/// How long submitting took.
+	submission: Duration,
+	/// How long it took until the last transaction was in a block.
+	elapsed: Duration,
+}
+
+/// Submit `transactions` at `tps` and track how long each takes to get into a block.
+async fn submit_all(
+	client: &Arc<WsClient>,
+	transactions: Vec<Bytes>,
+	tps: u32,
+) -> Result<Report> {
+	// Subscribe before submitting so that no block with our transactions is missed.
+	let heads = client
+		.subscribe::<Header, _>(
+			"chain_subscribeNewHeads",
+			rpc_params![],
+			"chain_unsubscribeNewHeads",
+		)
+		.await
+		.map_err(|e| format!("Subscribing to new blocks failed: {}", e))?;
+	let hashes = transactions
+		.iter()
+		.enumerate()
+		.map(|(n, transaction)| (Hash::from(blake2_256(transaction)), n))
+		.collect();
+	let (included_tx, mut included) = mpsc::unbounded();
+	let tracker = tokio::spawn(track_inclusion(client.clone(), heads, hashes, included_tx));
+
+	// Only submission is paced: it returns once the pool accepted the transaction.
+	let count = transactions.len();
+	let interval = Duration::from_secs(1) / tps;
+	let start = Instant::now();
+	let mut submissions = Vec::with_capacity(count);
+	for (n, transaction) in transactions.into_iter().enumerate() {
+		let due = start + interval * n as u32;
+		futures_timer::Delay::new(due.saturating_duration_since(Instant::now())).await;
+		let client = client.clone();
+		submissions.push(tokio::spawn(async move {
+			let submitted = Instant::now();
+			client
+				.request::<Hash, _>("author_submitExtrinsic", rpc_params![transaction])
+				.await
+				.map(|_| submitted)
+				.map_err(|e| e.to_string())
+		}));
+	}
+	let submitted: Vec<_> = future::join_all(submissions)
+		.await
+		.into_iter()
+		.map(|submission| submission.map_err(|e| e.to_string()).and_then(|result| result))
+		.collect();
+	let submission = start.elapsed();
+
+	let mut included_at = vec![None; count];
+	let mut pending = submitted.iter().filter(|result| result.is_ok()).count();
+	while pending > 0 {
+		let timeout = futures_timer::Delay::new(INCLUSION_TIMEOUT);
+		match future::select(included.next(), timeout).await {
+			Either::Left((Some((n, at)), _)) =>
+				if included_at[n].is_none() {
+					included_at[n] = Some(at);
+					pending -= submitted[n].is_ok() as usize;
+				},
+			_ => break,
+		}
+	}
+	tracker.abort();
+
+	let last_included = included_at.iter().flatten().max().copied();
+	let results = submitted
+		.into_iter()
+		.zip(included_at)
+		.map(|(submitted, included_at)| -> std::result::Result<Duration, String> {
+			let submitted = submitted?;
+			let included_at = included_at.ok_or("not included in time")?;
+			Ok(included_at.saturating_duration_since(submitted))
+		})
+		.collect();
+	Ok(Report {
+		results,
+		submission,
+		elapsed: last_included.map_or(submission, |at| at.saturating_duration_since(start)),
+	})
+}
+
+/// Report the index in `hashes` of every transaction in a new best block, with when the block was
+/// announced.
+async fn track_inclusion(
+	client: Arc<WsClient>,
+	mut heads: Subscription<Header>,
+	hashes: HashMap<Hash, usize>,
+	included: mpsc::UnboundedSender<(usize, Instant)>,
+) {
+	let mut seen = HashSet::new();
+	let mut first_number = None;
+	while let Some(Ok(head)) = heads.next().await {
+		let at = Instant::now();
+		let first_number = *first_number.get_or_insert(head.number);
+
+		// Blocks imported in quick succession are announced once; walk back to the unseen ones.
+		let mut next = Some(head);
+		while let Some(header) = next.take() {
+			let hash = header.hash();
+			if !seen.insert(hash) {
+				break
+			}
+			let block: Value = match client.request("chain_getBlock", rpc_params![hash]).await {
+				Ok(block) => block,
+				Err(_) => break,
+			};
+			let extrinsics = block["block"]["extrinsics"].as_array().cloned().unwrap_or_default();
+			for extrinsic in extrinsics {
+				let hash = extrinsic.as_str().and_then(|hex| from_hex(hex).ok()).map(|bytes| {
+					Hash::from(blake2_256(&bytes))
+				});
+				if let Some(n) = hash.and_then(|hash| hashes.get(&hash)) {
+					let _ = included.unbounded_send((*n, at));
+				}
+			}
+			if header.number > first_number {
+				next =
+					client.request("chain_getHeader", rpc_params![header.parent_hash]).await.ok();
+			}
+		}
+	}
+}
+
+impl CliConfiguration for LoadgenCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+}
@@ -7,7 +7,11 @@ mod service;
 mod benchmarking;
 mod cli;
 mod command;
+mod family_tree;
+mod loadgen;
+mod remote_keystore;
 mod rpc;
+mod voucher;
 
 fn main() -> sc_cli::Result<()> {
 	command::run()
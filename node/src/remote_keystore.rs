@@ -0,0 +1,658 @@
+//! A keystore that keeps no secrets in the node and asks a remote signer to sign instead.
+//!
+//! The signer speaks JSON-RPC 2.0, either over HTTP (`http://host:port[/path]`) or as one
+//! newline-terminated request and response per connection over a Unix socket
+//! (`unix:///path/to/socket`). Keys are identified by their four character key type, their
+//! scheme (`sr25519` or `ed25519`) and their hex encoded public key:
+//!
+//! - `keystore_publicKeys {keyType, scheme}` returns the hex public keys the signer holds.
+//! - `keystore_sign {keyType, scheme, public, payload}` returns the hex signature of `payload`,
+//!   or `null` if the signer does not hold `public`.
+//! - `keystore_vrfSign {keyType, scheme, public, label, items}` signs a VRF transcript, as BABE
+//!   does to claim slots, and returns the hex `{output, proof}`, or `null` like `keystore_sign`.
+//!   The scheme is always `sr25519`, the label is hex and every item is either
+//!   `{name, bytes}` with hex bytes or `{name, u64}`.
+//!
+//! `keystore-signer` is a reference signer serving a local keystore directory.
+
+use async_trait::async_trait;
+use schnorrkel::vrf::{VRFOutput, VRFProof};
+use serde_json::{json, Value};
+use sp_core::{
+	bytes::{from_hex, to_hex},
+	crypto::{CryptoTypeId, CryptoTypePublicPair, KeyTypeId},
+	ecdsa, ed25519, sr25519,
+};
+use sp_keystore::{
+	vrf::{VRFSignature, VRFTranscriptData, VRFTranscriptValue},
+	CryptoStore, Error, SyncCryptoStore,
+};
+use std::{
+	io::{BufRead, BufReader, Read, Write},
+	net::TcpStream,
+	os::unix::net::UnixStream,
+	path::{Path, PathBuf},
+	time::Duration,
+};
+use tokio::runtime::RuntimeFlavor;
+
+/// How long a single request to the signer may take before it is given up.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Signature schemes supported by the remote signer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheme {
+	Sr25519,
+	Ed25519,
+}
+
+impl Scheme {
+	/// The name of the scheme on the wire.
+	pub fn name(self) -> &'static str {
+		match self {
+			Scheme::Sr25519 => "sr25519",
+			Scheme::Ed25519 => "ed25519",
+		}
+	}
+
+	/// The crypto type of the scheme's keys in a keystore.
+	pub fn crypto_id(self) -> CryptoTypeId {
+		match self {
+			Scheme::Sr25519 => sr25519::CRYPTO_ID,
+			Scheme::Ed25519 => ed25519::CRYPTO_ID,
+		}
+	}
+
+	fn from_crypto_id(id: CryptoTypeId) -> Option<Self> {
+		[Scheme::Sr25519, Scheme::Ed25519].into_iter().find(|scheme| scheme.crypto_id() == id)
+	}
+}
+
+/// Where the signer listens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+	/// `http://host:port[/path]`.
+	Http { address: String, path: String },
+	/// `unix:///path/to/socket`.
+	Unix(PathBuf),
+}
+
+impl std::str::FromStr for Endpoint {
+	type Err = String;
+
+	fn from_str(uri: &str) -> Result<Self, Self::Err> {
+		if let Some(rest) = uri.strip_prefix("http://") {
+			let (address, path) = match rest.find('/') {
+				Some(index) => (&rest[..index], &rest[index..]),
+				None => (rest, "/"),
+			};
+			if address.is_empty() {
+				return Err(format!("missing host in `{}`", uri))
+			}
+			Ok(Endpoint::Http { address: address.into(), path: path.into() })
+		} else if let Some(path) = uri.strip_prefix("unix://") {
+			Ok(Endpoint::Unix(path.into()))
+		} else {
+			Err(format!("expected an `http://` or `unix://` URI, got `{}`", uri))
+		}
+	}
+}
+
+/// A keystore backed by a remote signer.
+#[derive(Clone)]
+pub struct RemoteKeystore {
+	endpoint: Endpoint,
+}
+
+impl RemoteKeystore {
+	/// Connect to the signer at `uri`, failing if it does not answer.
+	pub fn open(uri: &str) -> Result<Self, String> {
+		let keystore = Self { endpoint: uri.parse()? };
+		keystore.public_keys(sp_core::crypto::key_types::BABE, Scheme::Sr25519)?;
+		Ok(keystore)
+	}
+
+	/// The public keys of `scheme` the signer holds under `key_type`.
+	pub fn public_keys(&self, key_type: KeyTypeId, scheme: Scheme) -> Result<Vec<Vec<u8>>, String> {
+		let keys = self.call(
+			"keystore_publicKeys",
+			json!({ "keyType": key_type_name(key_type)?, "scheme": scheme.name() }),
+		)?;
+		keys.as_array()
+			.ok_or_else(|| "expected an array of public keys".to_string())?
+			.iter()
+			.map(decode_hex)
+			.collect()
+	}
+
+	/// Sign `payload` with `public`, `None` if the signer does not hold the key.
+	pub fn sign(
+		&self,
+		key_type: KeyTypeId,
+		scheme: Scheme,
+		public: &[u8],
+		payload: &[u8],
+	) -> Result<Option<Vec<u8>>, String> {
+		let signature = self.call(
+			"keystore_sign",
+			json!({
+				"keyType": key_type_name(key_type)?,
+				"scheme": scheme.name(),
+				"public": to_hex(public, false),
+				"payload": to_hex(payload, false),
+			}),
+		)?;
+		match signature {
+			Value::Null => Ok(None),
+			signature => decode_hex(&signature).map(Some),
+		}
+	}
+
+	/// Sign a VRF transcript with the sr25519 key `public`, `None` if the signer does not hold
+	/// the key.
+	pub fn vrf_sign(
+		&self,
+		key_type: KeyTypeId,
+		public: &[u8],
+		transcript: &VRFTranscriptData,
+	) -> Result<Option<VRFSignature>, String> {
+		let signature = self.call(
+			"keystore_vrfSign",
+			json!({
+				"keyType": key_type_name(key_type)?,
+				"scheme": Scheme::Sr25519.name(),
+				"public": to_hex(public, false),
+				"label": to_hex(transcript.label, false),
+				"items": transcript_items(transcript),
+			}),
+		)?;
+		if signature.is_null() {
+			return Ok(None)
+		}
+
+		let field = |name: &str| decode_hex(signature.get(name).unwrap_or(&Value::Null));
+		let output = VRFOutput::from_bytes(&field("output")?)
+			.map_err(|e| format!("invalid VRF output: {}", e))?;
+		let proof = VRFProof::from_bytes(&field("proof")?)
+			.map_err(|e| format!("invalid VRF proof: {}", e))?;
+		Ok(Some(VRFSignature { output, proof }))
+	}
+
+	fn call(&self, method: &str, params: Value) -> Result<Value, String> {
+		let request = encode_request(method, params);
+		let response = block_in_place(|| match &self.endpoint {
+			Endpoint::Http { address, path } => http_post(address, path, &request),
+			Endpoint::Unix(path) => unix_request(path, &request),
+		})
+		.map_err(|e| format!("remote signer unreachable: {}", e))?;
+		decode_response(&response)
+	}
+
+	/// Run `f` with a copy of the keystore on a thread that may block.
+	async fn blocking<R: Send + 'static>(&self, f: impl FnOnce(Self) -> R + Send + 'static) -> R {
+		let keystore = self.clone();
+		match tokio::task::spawn_blocking(move || f(keystore)).await {
+			Ok(result) => result,
+			Err(e) => std::panic::resume_unwind(e.into_panic()),
+		}
+	}
+
+	fn public_pairs(&self, key_type: KeyTypeId, scheme: Scheme) -> Vec<CryptoTypePublicPair> {
+		self.public_keys(key_type, scheme)
+			.unwrap_or_else(|e| {
+				let scheme = scheme.name();
+				log::warn!(target: "remote-keystore", "Listing {} keys failed: {}", scheme, e);
+				Vec::new()
+			})
+			.into_iter()
+			.map(|public| CryptoTypePublicPair(scheme.crypto_id(), public))
+			.collect()
+	}
+}
+
+/// Run `f`, which blocks for up to `TIMEOUT`, without stalling the other tasks of the runtime.
+///
+/// BABE and GRANDPA call the `SyncCryptoStore` methods straight from their async tasks. On a
+/// worker of the multi-threaded runtime the worker hands its other tasks over while `f` runs;
+/// anywhere else, e.g. on a blocking thread, `f` just runs.
+fn block_in_place<R>(f: impl FnOnce() -> R) -> R {
+	match tokio::runtime::Handle::try_current() {
+		Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread =>
+			tokio::task::block_in_place(f),
+		_ => f(),
+	}
+}
+
+fn encode_request(method: &str, params: Value) -> String {
+	json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }).to_string()
+}
+
+/// The result of a JSON-RPC response, or its error.
+fn decode_response(response: &str) -> Result<Value, String> {
+	let mut response: Value = serde_json::from_str(response)
+		.map_err(|e| format!("invalid response from remote signer: {}", e))?;
+	if let Some(error) = response.get("error") {
+		return Err(format!("remote signer failed: {}", error))
+	}
+	response
+		.get_mut("result")
+		.map(Value::take)
+		.ok_or_else(|| "response from remote signer has no result".into())
+}
+
+fn transcript_items(transcript: &VRFTranscriptData) -> Vec<Value> {
+	transcript
+		.items
+		.iter()
+		.map(|(name, value)| match value {
+			VRFTranscriptValue::Bytes(bytes) =>
+				json!({ "name": name, "bytes": to_hex(bytes, false) }),
+			VRFTranscriptValue::U64(value) => json!({ "name": name, "u64": value }),
+		})
+		.collect()
+}
+
+fn key_type_name(key_type: KeyTypeId) -> Result<String, String> {
+	String::from_utf8(key_type.0.to_vec())
+		.map_err(|_| format!("key type {:?} is not UTF-8", key_type))
+}
+
+/// Decode a `0x` prefixed hex string.
+pub fn decode_hex(value: &Value) -> Result<Vec<u8>, String> {
+	let hex = value.as_str().ok_or_else(|| format!("expected a hex string, got {}", value))?;
+	from_hex(hex).map_err(|e| format!("invalid hex `{}`: {}", hex, e))
+}
+
+fn http_post(address: &str, path: &str, body: &str) -> std::io::Result<String> {
+	let mut stream = TcpStream::connect(address)?;
+	stream.set_read_timeout(Some(TIMEOUT))?;
+	stream.set_write_timeout(Some(TIMEOUT))?;
+	write!(
+		stream,
+		"POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+		 Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+		path,
+		address,
+		body.len(),
+		body
+	)?;
+
+	read_http_response(BufReader::new(stream))
+}
+
+/// Read an HTTP/1.1 response and return its body, delimited by `Content-Length`, by a chunked
+/// `Transfer-Encoding` or by the end of the connection.
+fn read_http_response(mut reader: impl BufRead) -> std::io::Result<String> {
+	let invalid = |msg: String| std::io::Error::new(std::io::ErrorKind::InvalidData, msg);
+	let truncated = || invalid("truncated HTTP response".into());
+
+	let mut status = String::new();
+	if reader.read_line(&mut status)? == 0 {
+		return Err(truncated())
+	}
+	if status.split(' ').nth(1) != Some("200") {
+		return Err(invalid(status.trim_end().into()))
+	}
+
+	let mut content_length = None;
+	let mut chunked = false;
+	loop {
+		let mut header = String::new();
+		if reader.read_line(&mut header)? == 0 {
+			return Err(truncated())
+		}
+		let header = header.trim_end();
+		if header.is_empty() {
+			break
+		}
+		if let Some((name, value)) = header.split_once(':') {
+			let value = value.trim();
+			if name.eq_ignore_ascii_case("content-length") {
+				let length = value.parse::<usize>();
+				content_length =
+					Some(length.map_err(|_| invalid(format!("invalid length `{}`", value)))?);
+			} else if name.eq_ignore_ascii_case("transfer-encoding") {
+				chunked = value
+					.split(',')
+					.any(|coding| coding.trim().eq_ignore_ascii_case("chunked"));
+			}
+		}
+	}
+
+	let mut body = Vec::new();
+	if chunked {
+		loop {
+			let mut size = String::new();
+			if reader.read_line(&mut size)? == 0 {
+				return Err(truncated())
+			}
+			// Chunk extensions follow the size after a `;`.
+			let size = size.split(';').next().unwrap_or_default().trim();
+			let size = usize::from_str_radix(size, 16)
+				.map_err(|_| invalid(format!("invalid chunk size `{}`", size)))?;
+			if size == 0 {
+				break
+			}
+			let start = body.len();
+			body.resize(start + size, 0);
+			reader.read_exact(&mut body[start..])?;
+			reader.read_exact(&mut [0; 2])?;
+		}
+	} else if let Some(length) = content_length {
+		body.resize(length, 0);
+		reader.read_exact(&mut body)?;
+	} else {
+		reader.read_to_end(&mut body)?;
+	}
+	String::from_utf8(body).map_err(|e| invalid(e.to_string()))
+}
+
+fn unix_request(path: &Path, body: &str) -> std::io::Result<String> {
+	let mut stream = UnixStream::connect(path)?;
+	stream.set_read_timeout(Some(TIMEOUT))?;
+	stream.set_write_timeout(Some(TIMEOUT))?;
+	writeln!(stream, "{}", body)?;
+
+	let mut response = String::new();
+	BufReader::new(stream).read_line(&mut response)?;
+	Ok(response)
+}
+
+impl SyncCryptoStore for RemoteKeystore {
+	fn sr25519_public_keys(&self, id: KeyTypeId) -> Vec<sr25519::Public> {
+		self.public_pairs(id, Scheme::Sr25519)
+			.into_iter()
+			.filter_map(|pair| sr25519::Public::try_from(&pair.1[..]).ok())
+			.collect()
+	}
+
+	fn sr25519_generate_new(
+		&self,
+		_id: KeyTypeId,
+		_seed: Option<&str>,
+	) -> Result<sr25519::Public, Error> {
+		Err(Error::Other("keys must be generated on the remote signer".into()))
+	}
+
+	fn ed25519_public_keys(&self, id: KeyTypeId) -> Vec<ed25519::Public> {
+		self.public_pairs(id, Scheme::Ed25519)
+			.into_iter()
+			.filter_map(|pair| ed25519::Public::try_from(&pair.1[..]).ok())
+			.collect()
+	}
+
+	fn ed25519_generate_new(
+		&self,
+		_id: KeyTypeId,
+		_seed: Option<&str>,
+	) -> Result<ed25519::Public, Error> {
+		Err(Error::Other("keys must be generated on the remote signer".into()))
+	}
+
+	fn ecdsa_public_keys(&self, _id: KeyTypeId) -> Vec<ecdsa::Public> {
+		Vec::new()
+	}
+
+	fn ecdsa_generate_new(
+		&self,
+		_id: KeyTypeId,
+		_seed: Option<&str>,
+	) -> Result<ecdsa::Public, Error> {
+		Err(Error::Unavailable)
+	}
+
+	fn insert_unknown(&self, _id: KeyTypeId, _suri: &str, _public: &[u8]) -> Result<(), ()> {
+		Err(())
+	}
+
+	fn supported_keys(
+		&self,
+		id: KeyTypeId,
+		keys: Vec<CryptoTypePublicPair>,
+	) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		let held = SyncCryptoStore::keys(self, id)?;
+		Ok(keys.into_iter().filter(|key| held.contains(key)).collect())
+	}
+
+	fn keys(&self, id: KeyTypeId) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		let mut keys = self.public_pairs(id, Scheme::Sr25519);
+		keys.extend(self.public_pairs(id, Scheme::Ed25519));
+		Ok(keys)
+	}
+
+	fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> bool {
+		public_keys.iter().all(|(public, id)| {
+			SyncCryptoStore::keys(self, *id)
+				.map(|keys| keys.iter().any(|key| &key.1 == public))
+				.unwrap_or(false)
+		})
+	}
+
+	fn sign_with(
+		&self,
+		id: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		msg: &[u8],
+	) -> Result<Option<Vec<u8>>, Error> {
+		let scheme = Scheme::from_crypto_id(key.0).ok_or(Error::KeyNotSupported(id))?;
+		self.sign(id, scheme, &key.1, msg).map_err(Error::Other)
+	}
+
+	fn sr25519_vrf_sign(
+		&self,
+		key_type: KeyTypeId,
+		public: &sr25519::Public,
+		transcript_data: VRFTranscriptData,
+	) -> Result<Option<VRFSignature>, Error> {
+		self.vrf_sign(key_type, public.as_ref(), &transcript_data).map_err(Error::Other)
+	}
+
+	fn ecdsa_sign_prehashed(
+		&self,
+		_id: KeyTypeId,
+		_public: &ecdsa::Public,
+		_msg: &[u8; 32],
+	) -> Result<Option<ecdsa::Signature>, Error> {
+		Err(Error::Unavailable)
+	}
+}
+
+// Requests to the signer block for up to `TIMEOUT`, so they run on the blocking threads of the
+// runtime rather than stalling BABE and GRANDPA. Key generation and ECDSA never reach the signer.
+#[async_trait]
+impl CryptoStore for RemoteKeystore {
+	async fn sr25519_public_keys(&self, id: KeyTypeId) -> Vec<sr25519::Public> {
+		self.blocking(move |keystore| SyncCryptoStore::sr25519_public_keys(&keystore, id)).await
+	}
+
+	async fn sr25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<sr25519::Public, Error> {
+		SyncCryptoStore::sr25519_generate_new(self, id, seed)
+	}
+
+	async fn ed25519_public_keys(&self, id: KeyTypeId) -> Vec<ed25519::Public> {
+		self.blocking(move |keystore| SyncCryptoStore::ed25519_public_keys(&keystore, id)).await
+	}
+
+	async fn ed25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ed25519::Public, Error> {
+		SyncCryptoStore::ed25519_generate_new(self, id, seed)
+	}
+
+	async fn ecdsa_public_keys(&self, id: KeyTypeId) -> Vec<ecdsa::Public> {
+		SyncCryptoStore::ecdsa_public_keys(self, id)
+	}
+
+	async fn ecdsa_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ecdsa::Public, Error> {
+		SyncCryptoStore::ecdsa_generate_new(self, id, seed)
+	}
+
+	async fn insert_unknown(&self, id: KeyTypeId, suri: &str, public: &[u8]) -> Result<(), ()> {
+		SyncCryptoStore::insert_unknown(self, id, suri, public)
+	}
+
+	async fn supported_keys(
+		&self,
+		id: KeyTypeId,
+		keys: Vec<CryptoTypePublicPair>,
+	) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		self.blocking(move |keystore| SyncCryptoStore::supported_keys(&keystore, id, keys)).await
+	}
+
+	async fn keys(&self, id: KeyTypeId) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		self.blocking(move |keystore| SyncCryptoStore::keys(&keystore, id)).await
+	}
+
+	async fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> bool {
+		let public_keys = public_keys.to_vec();
+		self.blocking(move |keystore| SyncCryptoStore::has_keys(&keystore, &public_keys)).await
+	}
+
+	async fn sign_with(
+		&self,
+		id: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		msg: &[u8],
+	) -> Result<Option<Vec<u8>>, Error> {
+		let (key, msg) = (key.clone(), msg.to_vec());
+		self.blocking(move |keystore| SyncCryptoStore::sign_with(&keystore, id, &key, &msg)).await
+	}
+
+	async fn sr25519_vrf_sign(
+		&self,
+		key_type: KeyTypeId,
+		public: &sr25519::Public,
+		transcript_data: VRFTranscriptData,
+	) -> Result<Option<VRFSignature>, Error> {
+		let public = *public;
+		self.blocking(move |keystore| {
+			SyncCryptoStore::sr25519_vrf_sign(&keystore, key_type, &public, transcript_data)
+		})
+		.await
+	}
+
+	async fn ecdsa_sign_prehashed(
+		&self,
+		id: KeyTypeId,
+		public: &ecdsa::Public,
+		msg: &[u8; 32],
+	) -> Result<Option<ecdsa::Signature>, Error> {
+		SyncCryptoStore::ecdsa_sign_prehashed(self, id, public, msg)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn endpoints_are_parsed() {
+		let http = |address: &str, path: &str| Endpoint::Http {
+			address: address.into(),
+			path: path.into(),
+		};
+		assert_eq!("http://127.0.0.1:9000".parse(), Ok(http("127.0.0.1:9000", "/")));
+		assert_eq!("http://signer:80/rpc/v1".parse(), Ok(http("signer:80", "/rpc/v1")));
+		assert_eq!(
+			"unix:///run/signer.sock".parse(),
+			Ok(Endpoint::Unix("/run/signer.sock".into()))
+		);
+		assert!("http:///rpc".parse::<Endpoint>().is_err());
+		assert!("https://signer".parse::<Endpoint>().is_err());
+		assert!("/run/signer.sock".parse::<Endpoint>().is_err());
+	}
+
+	fn read(response: &str) -> std::io::Result<String> {
+		read_http_response(response.as_bytes())
+	}
+
+	#[test]
+	fn http_bodies_are_framed() {
+		let body = read("HTTP/1.1 200 OK\r\ncontent-length: 4\r\n\r\n{}{}trailing");
+		assert_eq!(body.unwrap(), "{}{}");
+
+		let chunked = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+		               4;ext=1\r\n{\"a\"\r\n3\r\n:1}\r\n0\r\n\r\n";
+		assert_eq!(read(chunked).unwrap(), "{\"a\":1}");
+
+		assert_eq!(read("HTTP/1.0 200 OK\r\n\r\n{\"a\":1}").unwrap(), "{\"a\":1}");
+	}
+
+	#[test]
+	fn bad_http_responses_fail() {
+		let error = read("HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n").unwrap_err();
+		assert_eq!(error.to_string(), "HTTP/1.1 404 Not Found");
+		assert!(read("").is_err());
+		assert!(read("HTTP/1.1 200 OK\r\nContent-Length: 4").is_err());
+		assert!(read("HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\n{}").is_err());
+		assert!(read("HTTP/1.1 200 OK\r\nContent-Length: x\r\n\r\n").is_err());
+		assert!(read("HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nz\r\n").is_err());
+	}
+
+	#[test]
+	fn json_rpc_requests_and_responses() {
+		let request: Value =
+			serde_json::from_str(&encode_request("keystore_sign", json!({ "a": 1 }))).unwrap();
+		assert_eq!(
+			request,
+			json!({ "jsonrpc": "2.0", "id": 1, "method": "keystore_sign", "params": { "a": 1 } })
+		);
+
+		let response = |body: Value| decode_response(&body.to_string());
+		let result = response(json!({ "jsonrpc": "2.0", "id": 1, "result": ["0x01"] }));
+		assert_eq!(result, Ok(json!(["0x01"])));
+		let result = response(json!({ "jsonrpc": "2.0", "id": 1, "result": null }));
+		assert_eq!(result, Ok(Value::Null));
+		assert!(response(json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32601 } }))
+			.unwrap_err()
+			.contains("-32601"));
+		assert!(response(json!({ "jsonrpc": "2.0", "id": 1 })).is_err());
+		assert!(decode_response("not json").is_err());
+	}
+
+	#[test]
+	fn transcripts_are_hex_encoded() {
+		let transcript = VRFTranscriptData {
+			label: b"BABE",
+			items: vec![
+				("slot number", VRFTranscriptValue::U64(7)),
+				("chain randomness", VRFTranscriptValue::Bytes(vec![0xab, 0xcd])),
+			],
+		};
+		assert_eq!(
+			transcript_items(&transcript),
+			vec![
+				json!({ "name": "slot number", "u64": 7 }),
+				json!({ "name": "chain randomness", "bytes": "0xabcd" }),
+			]
+		);
+		assert_eq!(decode_hex(&json!("0xabcd")), Ok(vec![0xab, 0xcd]));
+		assert!(decode_hex(&json!("0xabc")).is_err());
+		assert!(decode_hex(&json!(12)).is_err());
+	}
+
+	fn unreachable_keys() -> Vec<sr25519::Public> {
+		let keystore = RemoteKeystore { endpoint: Endpoint::Unix("/nonexistent/signer".into()) };
+		SyncCryptoStore::sr25519_public_keys(&keystore, sp_core::crypto::key_types::BABE)
+	}
+
+	#[tokio::test(flavor = "multi_thread")]
+	async fn sync_calls_work_on_runtime_workers() {
+		assert!(unreachable_keys().is_empty());
+	}
+
+	#[tokio::test(flavor = "current_thread")]
+	async fn sync_calls_work_on_current_thread_runtimes() {
+		assert!(unreachable_keys().is_empty());
+	}
+}
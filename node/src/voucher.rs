@@ -0,0 +1,124 @@
+//! The `issue-voucher` subcommand, signing kitty vouchers with a minter key from the keystore.
+
+use codec::Encode;
+use node_template_runtime::{
+	pallet_kitties::{self, KittyVoucher},
+	AccountId, BlockNumber, RuntimeCall, Signature, UncheckedExtrinsic,
+};
+use sc_cli::{CliConfiguration, KeystoreParams, Result, SharedParams};
+use sc_keystore::LocalKeystore;
+use sc_service::config::KeystoreConfig;
+use sp_core::{
+	crypto::{CryptoTypePublicPair, KeyTypeId, Ss58Codec},
+	hexdisplay::HexDisplay,
+	sr25519,
+};
+use sp_keystore::SyncCryptoStore;
+
+/// Key type of voucher minter keys in the keystore.
+pub const VOUCHER_KEY_TYPE: KeyTypeId = KeyTypeId(*b"kvch");
+
+/// Issue a signed kitty voucher, claimable without any balance through an unsigned transaction.
+///
+/// The minter key must be an sr25519 key in the keystore under key type `kvch`, e.g. added with
+/// `key insert --key-type kvch --scheme sr25519`, and its account must be an authorized minter.
+#[derive(Debug, clap::Parser)]
+pub struct IssueVoucherCmd {
+	/// SS58 address of the minter key.
+	#[arg(long)]
+	pub minter: String,
+
+	/// SS58 address of the account receiving the kitty.
+	#[arg(long)]
+	pub recipient: String,
+
+	/// Name of the kitty, at most 8 bytes.
+	#[arg(long)]
+	pub name: String,
+
+	/// Distinguishes otherwise identical vouchers of the same minter.
+	#[arg(long, default_value_t = 0)]
+	pub nonce: u32,
+
+	/// The last block in which the voucher can be claimed.
+	#[arg(long)]
+	pub expires_at: BlockNumber,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub keystore_params: KeystoreParams,
+}
+
+impl IssueVoucherCmd {
+	/// Sign the voucher with the keystore of the node configuration.
+	pub fn run(&self, keystore: &KeystoreConfig) -> Result<()> {
+		let keystore = match keystore {
+			KeystoreConfig::Path { path, password } =>
+				LocalKeystore::open(path.clone(), password.clone())?,
+			_ => return Err("Issuing vouchers requires a keystore on disk".into()),
+		};
+
+		let minter = sr25519::Public::from_ss58check(&self.minter)
+			.map_err(|e| format!("Invalid minter address: {:?}", e))?;
+		let recipient = AccountId::from_ss58check(&self.recipient)
+			.map_err(|e| format!("Invalid recipient address: {:?}", e))?;
+		if self.name.len() > 8 {
+			return Err("Kitty names are at most 8 bytes long".into())
+		}
+		let mut name = [0u8; 8];
+		name[..self.name.len()].copy_from_slice(self.name.as_bytes());
+
+		let voucher = KittyVoucher {
+			minter: AccountId::from(minter),
+			recipient,
+			name,
+			nonce: self.nonce,
+			expires_at: self.expires_at,
+		};
+
+		let key = CryptoTypePublicPair(sr25519::CRYPTO_ID, minter.0.to_vec());
+		let payload = voucher.signing_payload();
+		let signature = SyncCryptoStore::sign_with(&keystore, VOUCHER_KEY_TYPE, &key, &payload)
+			.map_err(|e| format!("Failed to sign the voucher: {}", e))?
+			.ok_or_else(|| format!("Minter key {} is not in the keystore", self.minter))?;
+		let signature = sr25519::Signature::from_slice(&signature)
+			.ok_or("The keystore returned a malformed signature")?;
+		let signature = Signature::from(signature);
+
+		let call = RuntimeCall::KittiesModule(pallet_kitties::Call::claim_voucher {
+			voucher: voucher.clone(),
+			signature: signature.clone(),
+		});
+		let extrinsic = UncheckedExtrinsic::new_unsigned(call);
+
+		let output = serde_json::json!({
+			"voucher": {
+				"minter": voucher.minter.to_ss58check(),
+				"recipient": voucher.recipient.to_ss58check(),
+				"name": self.name,
+				"nonce": voucher.nonce,
+				"expiresAt": voucher.expires_at,
+			},
+			"signature": format!("0x{}", HexDisplay::from(&signature.encode())),
+			// 可以直接通过 author_submitExtrinsic 提交
+			"extrinsic": format!("0x{}", HexDisplay::from(&extrinsic.encode())),
+		});
+		println!("{}", serde_json::to_string_pretty(&output).map_err(|e| e.to_string())?);
+
+		Ok(())
+	}
+}
+
+impl CliConfiguration for IssueVoucherCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn keystore_params(&self) -> Option<&KeystoreParams> {
+		Some(&self.keystore_params)
+	}
+}
@@ -0,0 +1,144 @@
+//! Signs through a running `keystore-signer` with the remote keystore, as an authoring node does.
+
+use node_template::remote_keystore::RemoteKeystore;
+use sc_keystore::LocalKeystore;
+use sp_core::{
+	crypto::{CryptoTypePublicPair, KeyTypeId},
+	ed25519, sr25519, Pair,
+};
+use sp_keystore::{
+	vrf::{make_transcript, VRFTranscriptData, VRFTranscriptValue},
+	CryptoStore, SyncCryptoStore,
+};
+use std::{
+	net::TcpListener,
+	path::Path,
+	process::{Child, Command},
+	thread,
+	time::Duration,
+};
+
+const KEY_TYPE: KeyTypeId = KeyTypeId(*b"test");
+
+/// A running signer, killed when the test ends.
+struct Signer(Child);
+
+impl Drop for Signer {
+	fn drop(&mut self) {
+		let _ = self.0.kill();
+		let _ = self.0.wait();
+	}
+}
+
+fn start_signer(keystore: &Path, listen: &str) -> (Signer, RemoteKeystore) {
+	let signer = Command::new(env!("CARGO_BIN_EXE_keystore-signer"))
+		.arg("--keystore-path")
+		.arg(keystore)
+		.args(["--listen", listen])
+		.spawn()
+		.map(Signer)
+		.expect("keystore-signer is built with the tests");
+
+	// Opening fails until the signer listens.
+	for _ in 0..100 {
+		if let Ok(remote) = RemoteKeystore::open(listen) {
+			return (signer, remote)
+		}
+		thread::sleep(Duration::from_millis(50));
+	}
+	panic!("keystore-signer did not start listening on {}", listen);
+}
+
+/// A local keystore with one sr25519 and one ed25519 key.
+fn local_keystore(path: &Path) -> (LocalKeystore, sr25519::Public, ed25519::Public) {
+	let local = LocalKeystore::open(path, None).unwrap();
+	let sr25519 = SyncCryptoStore::sr25519_generate_new(&local, KEY_TYPE, None).unwrap();
+	let ed25519 = SyncCryptoStore::ed25519_generate_new(&local, KEY_TYPE, None).unwrap();
+	(local, sr25519, ed25519)
+}
+
+fn transcript() -> VRFTranscriptData {
+	VRFTranscriptData {
+		label: b"BABE",
+		items: vec![
+			("slot number", VRFTranscriptValue::U64(7)),
+			("current epoch", VRFTranscriptValue::U64(1)),
+			("chain randomness", VRFTranscriptValue::Bytes(vec![3; 32])),
+		],
+	}
+}
+
+fn check_signatures(
+	local: &LocalKeystore,
+	remote: &RemoteKeystore,
+	sr: sr25519::Public,
+	ed: ed25519::Public,
+) {
+	assert_eq!(SyncCryptoStore::sr25519_public_keys(remote, KEY_TYPE), vec![sr]);
+	assert_eq!(SyncCryptoStore::ed25519_public_keys(remote, KEY_TYPE), vec![ed]);
+
+	let message = b"kitties";
+	let sr_key = CryptoTypePublicPair(sr25519::CRYPTO_ID, sr.0.to_vec());
+	let signature = SyncCryptoStore::sign_with(remote, KEY_TYPE, &sr_key, message).unwrap();
+	let signature = sr25519::Signature::try_from(&signature.unwrap()[..]).unwrap();
+	assert!(sr25519::Pair::verify(&signature, message, &sr));
+
+	let ed_key = CryptoTypePublicPair(ed25519::CRYPTO_ID, ed.0.to_vec());
+	let signature = SyncCryptoStore::sign_with(remote, KEY_TYPE, &ed_key, message).unwrap();
+	let signature = ed25519::Signature::try_from(&signature.unwrap()[..]).unwrap();
+	assert!(ed25519::Pair::verify(&signature, message, &ed));
+
+	// VRF outputs are deterministic, the proofs are not.
+	let signature =
+		SyncCryptoStore::sr25519_vrf_sign(remote, KEY_TYPE, &sr, transcript()).unwrap().unwrap();
+	let public = schnorrkel::PublicKey::from_bytes(sr.as_ref()).unwrap();
+	let transcript_to_verify = make_transcript(transcript());
+	assert!(public.vrf_verify(transcript_to_verify, &signature.output, &signature.proof).is_ok());
+	let expected =
+		SyncCryptoStore::sr25519_vrf_sign(local, KEY_TYPE, &sr, transcript()).unwrap().unwrap();
+	assert_eq!(signature.output, expected.output);
+
+	// Keys the signer does not hold sign nothing.
+	let unknown = sr25519::Pair::from_seed(&[9; 32]).public();
+	let unknown_key = CryptoTypePublicPair(sr25519::CRYPTO_ID, unknown.0.to_vec());
+	assert_eq!(SyncCryptoStore::sign_with(remote, KEY_TYPE, &unknown_key, message).unwrap(), None);
+	let vrf = SyncCryptoStore::sr25519_vrf_sign(remote, KEY_TYPE, &unknown, transcript());
+	assert!(vrf.unwrap().is_none());
+}
+
+#[test]
+fn signs_over_a_unix_socket() {
+	let dir = tempfile::tempdir().unwrap();
+	let (local, sr, ed) = local_keystore(&dir.path().join("keystore"));
+	let socket = format!("unix://{}", dir.path().join("signer.sock").display());
+	let (_signer, remote) = start_signer(&dir.path().join("keystore"), &socket);
+
+	check_signatures(&local, &remote, sr, ed);
+}
+
+#[test]
+fn signs_over_http() {
+	let dir = tempfile::tempdir().unwrap();
+	let (local, sr, ed) = local_keystore(&dir.path().join("keystore"));
+	let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+	let (_signer, remote) =
+		start_signer(&dir.path().join("keystore"), &format!("http://127.0.0.1:{}/sign", port));
+
+	check_signatures(&local, &remote, sr, ed);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn signs_asynchronously() {
+	let dir = tempfile::tempdir().unwrap();
+	let (_local, sr, _ed) = local_keystore(&dir.path().join("keystore"));
+	let socket = format!("unix://{}", dir.path().join("signer.sock").display());
+	let (_signer, remote) = start_signer(&dir.path().join("keystore"), &socket);
+
+	assert_eq!(CryptoStore::sr25519_public_keys(&remote, KEY_TYPE).await, vec![sr]);
+	let key = CryptoTypePublicPair(sr25519::CRYPTO_ID, sr.0.to_vec());
+	let signature = CryptoStore::sign_with(&remote, KEY_TYPE, &key, b"kitties").await.unwrap();
+	let signature = sr25519::Signature::try_from(&signature.unwrap()[..]).unwrap();
+	assert!(sr25519::Pair::verify(&signature, b"kitties", &sr));
+	let vrf = CryptoStore::sr25519_vrf_sign(&remote, KEY_TYPE, &sr, transcript()).await;
+	assert!(vrf.unwrap().is_some());
+}
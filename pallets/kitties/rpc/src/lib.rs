@@ -0,0 +1,99 @@
+//! RPC methods serving the kitty artwork the offchain worker of `pallet_kitties` renders into
+//! persistent offchain storage. Nodes must run with offchain workers enabled for it to be filled.
+
+use jsonrpsee::{
+	core::RpcResult,
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use pallet_kitties::{render, KittyId};
+use sp_core::offchain::{OffchainStorage, STORAGE_PREFIX};
+
+#[rpc(client, server)]
+pub trait KittiesApi {
+	/// The SVG image of a kitty, `None` if it has not been rendered yet.
+	#[method(name = "kitties_svg")]
+	fn svg(&self, kitty_id: KittyId) -> RpcResult<Option<String>>;
+
+	/// The JSON metadata of a kitty, `None` if it has not been rendered yet.
+	#[method(name = "kitties_metadata")]
+	fn metadata(&self, kitty_id: KittyId) -> RpcResult<Option<serde_json::Value>>;
+}
+
+/// Serves kitty artwork from the node's offchain storage.
+pub struct Kitties<S> {
+	storage: S,
+}
+
+impl<S> Kitties<S> {
+	pub fn new(storage: S) -> Self {
+		Self { storage }
+	}
+}
+
+const RENDER_ERROR: i32 = 1;
+
+fn render_error(message: String) -> jsonrpsee::core::Error {
+	CallError::Custom(ErrorObject::owned(RENDER_ERROR, message, None::<()>)).into()
+}
+
+impl<S: OffchainStorage> Kitties<S> {
+	fn read(&self, prefix: &[u8], kitty_id: KittyId) -> RpcResult<Option<String>> {
+		self.storage
+			.get(STORAGE_PREFIX, &render::storage_key(prefix, kitty_id))
+			.map(|bytes| {
+				String::from_utf8(bytes).map_err(|e| {
+					render_error(format!("Corrupted artwork of kitty {}: {}", kitty_id, e))
+				})
+			})
+			.transpose()
+	}
+}
+
+impl<S: OffchainStorage + 'static> KittiesApiServer for Kitties<S> {
+	fn svg(&self, kitty_id: KittyId) -> RpcResult<Option<String>> {
+		self.read(render::SVG_KEY_PREFIX, kitty_id)
+	}
+
+	fn metadata(&self, kitty_id: KittyId) -> RpcResult<Option<serde_json::Value>> {
+		self.read(render::METADATA_KEY_PREFIX, kitty_id)?
+			.map(|json| {
+				serde_json::from_str(&json).map_err(|e| {
+					render_error(format!("Corrupted metadata of kitty {}: {}", kitty_id, e))
+				})
+			})
+			.transpose()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pallet_kitties::Kitty;
+	use sp_core::offchain::storage::InMemOffchainStorage;
+
+	#[test]
+	fn serves_rendered_artwork() {
+		let kitty = Kitty { dna: [3; 16], name: *b"kitty\0\0\0" };
+		let mut storage = InMemOffchainStorage::default();
+		storage.set(
+			STORAGE_PREFIX,
+			&render::storage_key(render::SVG_KEY_PREFIX, 0),
+			render::svg(&kitty.dna).as_bytes(),
+		);
+		storage.set(
+			STORAGE_PREFIX,
+			&render::storage_key(render::METADATA_KEY_PREFIX, 0),
+			render::metadata(0, &kitty).as_bytes(),
+		);
+		let api = Kitties::new(storage);
+
+		assert_eq!(api.svg(0).unwrap(), Some(render::svg(&kitty.dna)));
+		let metadata = api.metadata(0).unwrap().unwrap();
+		assert_eq!(metadata["id"], 0);
+		assert_eq!(metadata["name"], "kitty");
+		assert_eq!(metadata["dna"], "0x03030303030303030303030303030303");
+		assert_eq!(api.svg(1).unwrap(), None);
+		assert_eq!(api.metadata(1).unwrap(), None);
+	}
+}
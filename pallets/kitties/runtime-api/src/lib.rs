@@ -0,0 +1,25 @@
+//! Runtime API definition for the kitties pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+pub use pallet_kitties::{CollectionId, KittyId, KittyRelative, ProvenanceRecord};
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	#[api_version(4)]
+	pub trait KittiesApi<AccountId: Codec, AssetId: Codec, Balance: Codec, BlockNumber: Codec> {
+		/// The kitty followed by its ancestors, at most `max_depth` generations back.
+		fn ancestors(kitty_id: KittyId, max_depth: u32) -> Vec<KittyRelative>;
+		/// The kitty followed by its descendants, at most `max_depth` generations forward.
+		fn descendants(kitty_id: KittyId, max_depth: u32) -> Vec<KittyRelative>;
+		/// The current price of minting into a collection, `None` if it does not exist.
+		fn quote_mint_price(collection_id: CollectionId) -> Option<Balance>;
+		/// The current price of breeding two kitties, `None` if either does not exist.
+		fn quote_breed_price(kitty_id_1: KittyId, kitty_id_2: KittyId) -> Option<Balance>;
+		/// The previous owners of a kitty, oldest first.
+		fn provenance(
+			kitty_id: KittyId,
+		) -> Vec<ProvenanceRecord<AccountId, BlockNumber, AssetId, Balance>>;
+	}
+}
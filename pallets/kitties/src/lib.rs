@@ -14,25 +14,44 @@ mod tests;
 //将package加进来
 mod migrations;
 
+pub mod pricing;
+pub mod render;
+
 
 #[frame_support::pallet]
 //pallet 划分traits来实现它的功能 需要引入trait，定义在support里面
 pub mod pallet {
 	use super::*;
+	use crate::pricing::{PriceCurve, PriceInput};
 	use frame_support::{pallet_prelude::*, traits::StorageVersion};
 	use frame_system::pallet_prelude::*;
 
 	use frame_support::{
-		traits::{Currency, ExistenceRequirement, Randomness},
+		traits::{
+			fungibles, Currency, ExistenceRequirement, OnUnbalanced, Randomness, WithdrawReasons,
+		},
 		PalletId,
 	};
-	use sp_runtime::traits::AccountIdConversion;
+	use sp_runtime::{
+		traits::{AccountIdConversion, IdentifyAccount, One, Saturating, Verify, Zero},
+		Permill, SaturatedConversion,
+	};
+	use sp_std::{collections::btree_set::BTreeSet, prelude::*};
 
-	use sp_io::hashing::blake2_128;
+	use sp_io::hashing::{blake2_128, blake2_256};
 
 	pub type KittyId = u32;
+	pub type CollectionId = u32;
 	pub type BalanceOf<T> =
 		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+	pub type AssetIdOf<T> = <<T as Config>::Assets as fungibles::Inspect<
+		<T as frame_system::Config>::AccountId,
+	>>::AssetId;
+	pub type PriceParametersOf<T> =
+		<<T as Config>::PriceCurve as PriceCurve<BalanceOf<T>>>::Parameters;
+	pub type NegativeImbalanceOf<T> = <<T as Config>::Currency as Currency<
+		<T as frame_system::Config>::AccountId,
+	>>::NegativeImbalance;
 	
 	//增加currency操作，currency作为一个traits特征,在Currency中会定义Balance的类型，,使用这个Balance或者代币单位,需要这个类型的定义,有了Price可以创建一个Kitty执行reserve操作  
 
@@ -44,7 +63,119 @@ pub mod pallet {
 		pub name: [u8; 8],
 		//第一个 dna，2.名字
 	}
-	const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
+	/// A kitty visited while walking a family tree, see [`Pallet::ancestors`].
+	#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq, TypeInfo)]
+	pub struct KittyRelative {
+		pub kitty_id: KittyId,
+		pub kitty: Kitty,
+		pub parents: Option<(KittyId, KittyId)>,
+		/// Number of generations between this kitty and the one the walk started from.
+		pub depth: u32,
+	}
+
+	/// Read access to kitties for other pallets.
+	pub trait KittyInspect<AccountId> {
+		/// The DNA of a kitty, if it exists.
+		fn dna(kitty_id: KittyId) -> Option<[u8; 16]>;
+		/// The current owner of a kitty, if it exists.
+		fn owner(kitty_id: KittyId) -> Option<AccountId>;
+	}
+
+	/// A previous owner of a kitty, see [`Pallet::provenance`].
+	#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq, TypeInfo, MaxEncodedLen)]
+	pub struct ProvenanceRecord<AccountId, BlockNumber, AssetId, Balance> {
+		/// The owner who gave the kitty away.
+		pub owner: AccountId,
+		/// The block in which the kitty changed hands.
+		pub block: BlockNumber,
+		/// The price paid by the new owner, `None` for transfers.
+		pub price: Option<Balance>,
+		/// The asset the price was paid in, `None` for the native currency.
+		pub asset_id: Option<AssetId>,
+	}
+
+	pub type ProvenanceRecordOf<T> = ProvenanceRecord<
+		<T as frame_system::Config>::AccountId,
+		<T as frame_system::Config>::BlockNumber,
+		AssetIdOf<T>,
+		BalanceOf<T>,
+	>;
+
+	/// Lock-up of a staked kitty.
+	#[derive(Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq, TypeInfo, MaxEncodedLen)]
+	pub struct StakeInfo<BlockNumber, Balance> {
+		/// The block from which the kitty can be unstaked.
+		pub unlock_at: BlockNumber,
+		/// Value of `RewardPerKitty` when rewards were last paid out for this kitty.
+		pub reward_debt: Balance,
+	}
+
+	/// Domain separator of the payload signed by voucher minters.
+	pub const VOUCHER_CONTEXT: &[u8] = b"kitty-voucher";
+
+	/// Execution weight of verifying a voucher signature. The `sr25519_verification` baseline
+	/// benchmark of `frame_benchmarking` measures about 48µs per signature on reference
+	/// hardware; twice that covers the ecdsa variant of `MultiSignature` with a margin, as
+	/// nobody pays a fee for `claim_voucher`.
+	pub const VOUCHER_SIGNATURE_WEIGHT: Weight = Weight::from_parts(96_000_000, 0);
+
+	/// Execution weight of `claim_voucher` besides the signature and storage: hashing the
+	/// voucher, drawing the DNA and emitting the events.
+	pub const VOUCHER_EXECUTION_WEIGHT: Weight = Weight::from_parts(10_000_000, 0);
+
+	/// Permission to mint one kitty for free, signed off-chain by an authorized
+	/// minter and claimed with an unsigned [`Pallet::claim_voucher`] transaction.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct KittyVoucher<AccountId, BlockNumber> {
+		pub minter: AccountId,
+		pub recipient: AccountId,
+		pub name: [u8; 8],
+		/// Lets a minter issue several otherwise identical vouchers.
+		pub nonce: u32,
+		/// The last block in which the voucher can be claimed.
+		pub expires_at: BlockNumber,
+	}
+
+	impl<AccountId: Encode, BlockNumber: Encode> KittyVoucher<AccountId, BlockNumber> {
+		/// The bytes the minter signs.
+		pub fn signing_payload(&self) -> Vec<u8> {
+			(VOUCHER_CONTEXT, self).encode()
+		}
+
+		/// Identifies the voucher in `UsedVouchers`.
+		pub fn hash(&self) -> [u8; 32] {
+			self.using_encoded(blake2_256)
+		}
+	}
+
+	/// The collection kitties are minted into by `create`, `breed` and vouchers. It exists without
+	/// being created, owned by the pallet account, until root or a migration stores it.
+	pub const DEFAULT_COLLECTION: CollectionId = 0;
+
+	/// A group of kitties minted by its creator, with its own serial numbers.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(MaxMetadata))]
+	#[codec(mel_bound(AccountId: MaxEncodedLen, Balance: MaxEncodedLen))]
+	pub struct CollectionDetails<AccountId, Balance, MaxMetadata: Get<u32>> {
+		pub creator: AccountId,
+		pub metadata: BoundedVec<u8, MaxMetadata>,
+		/// The maximum number of kitties in the collection, unlimited if `None`.
+		pub max_supply: Option<u32>,
+		/// Number of kitties minted so far, which is also the serial number of the next one.
+		pub minted: u32,
+		/// Price of minting into the collection, the price curve if `None`.
+		pub mint_price: Option<Balance>,
+		/// Frozen collections cannot be minted into, and their kitties cannot change hands.
+		pub frozen: bool,
+	}
+
+	pub type CollectionDetailsOf<T> = CollectionDetails<
+		<T as frame_system::Config>::AccountId,
+		BalanceOf<T>,
+		<T as Config>::MaxCollectionMetadata,
+	>;
+
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(6);
 	//更新，创建STORAGE_VERSION常量，改一下版本号
 
 	#[pallet::pallet]
@@ -61,11 +192,53 @@ pub mod pallet {
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 		type Randomness: Randomness<Self::Hash, Self::BlockNumber>;
 		type Currency: Currency<Self::AccountId>;
-		#[pallet::constant]
-		type KittyPrice: Get<BalanceOf<Self>>;
-		//价格 定义kitty常量
+		/// Assets sellers can price their kitties in, besides the native currency.
+		type Assets: fungibles::Inspect<Self::AccountId, Balance = BalanceOf<Self>>
+			+ fungibles::Mutate<Self::AccountId>
+			+ fungibles::Transfer<Self::AccountId>;
+		/// How the price of minting and breeding kitties evolves.
+		type PriceCurve: PriceCurve<BalanceOf<Self>>;
+		/// Price curve parameters used until governance sets others.
+		type DefaultPriceParameters: Get<PriceParametersOf<Self>>;
+		/// The origin allowed to authorize minters, set the price curve parameters and manage
+		/// any collection.
+		type GovernanceOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+		//价格 定义kitty价格曲线
 		type PalletId: Get<PalletId>;
 		// 定义palletid，可以转换成装户
+		/// The maximum number of generations walked by the lineage queries.
+		#[pallet::constant]
+		type MaxLineageDepth: Get<u32>;
+		/// The maximum number of kitties returned by a single lineage query.
+		#[pallet::constant]
+		type MaxLineageSize: Get<u32>;
+		/// Number of blocks between two distributions of staking rewards.
+		#[pallet::constant]
+		type EraLength: Get<Self::BlockNumber>;
+		/// Number of blocks a kitty stays locked after being staked.
+		#[pallet::constant]
+		type StakingLockPeriod: Get<Self::BlockNumber>;
+		/// Receives the fees left in the pallet account once an era's rewards are distributed,
+		/// e.g. the treasury.
+		type RevenueDestination: OnUnbalanced<NegativeImbalanceOf<Self>>;
+		/// The part of each era's fees shared among stakers. The rest goes to
+		/// `RevenueDestination`.
+		#[pallet::constant]
+		type StakerShare: Get<Permill>;
+		/// The key type of voucher minters, identifying their account.
+		type VoucherPublic: IdentifyAccount<AccountId = Self::AccountId>;
+		/// The signature scheme of kitty vouchers.
+		type VoucherSignature: Parameter + Verify<Signer = Self::VoucherPublic>;
+		/// Priority of unsigned `claim_voucher` transactions.
+		#[pallet::constant]
+		type VoucherPriority: Get<TransactionPriority>;
+		/// The maximum length of collection metadata.
+		#[pallet::constant]
+		type MaxCollectionMetadata: Get<u32>;
+		/// The number of provenance records kept per kitty. Older records are pruned in
+		/// `on_idle`, so a kitty may hold a few more until the next block with spare weight.
+		#[pallet::constant]
+		type MaxProvenanceEntries: Get<u32>;
 	}
 
 	// pallet的运行时存储项。 
@@ -89,9 +262,123 @@ pub mod pallet {
 	pub type KittyParents<T: Config> =
 		StorageMap<_, Blake2_128Concat, KittyId, (KittyId, KittyId), OptionQuery>;
 
+	/// Reverse index of `KittyParents`: parent id => child id.
+	#[pallet::storage]
+	pub type KittyChildren<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, KittyId, Blake2_128Concat, KittyId, (), OptionQuery>;
+
+	/// Listed kitties with their asking price.
 	#[pallet::storage]
 	#[pallet::getter(fn kitty_on_sale)]
-	pub type KittyOnSale<T: Config> = StorageMap<_, Blake2_128Concat, KittyId, (), OptionQuery>;
+	pub type KittyOnSale<T: Config> =
+		StorageMap<_, Blake2_128Concat, KittyId, BalanceOf<T>, OptionQuery>;
+
+	/// The asset of listings priced in an asset instead of the native currency.
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_sale_asset)]
+	pub type KittySaleAsset<T: Config> =
+		StorageMap<_, Blake2_128Concat, KittyId, AssetIdOf<T>, OptionQuery>;
+
+	/// Generation of bred kitties, one more than their older parent. Minted kitties are 0.
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_generation)]
+	pub type KittyGeneration<T: Config> = StorageMap<_, Blake2_128Concat, KittyId, u32, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn price_parameters)]
+	pub type PriceParameters<T: Config> =
+		StorageValue<_, PriceParametersOf<T>, ValueQuery, T::DefaultPriceParameters>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn staked_kitties)]
+	pub type StakedKitties<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		KittyId,
+		StakeInfo<T::BlockNumber, BalanceOf<T>>,
+		OptionQuery,
+	>;
+
+	/// Number of entries in `StakedKitties`.
+	#[pallet::storage]
+	#[pallet::getter(fn total_staked)]
+	pub type TotalStaked<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	/// Rewards accumulated by a single kitty staked since the pool was created.
+	#[pallet::storage]
+	#[pallet::getter(fn reward_per_kitty)]
+	pub type RewardPerKitty<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
+
+	/// Rewards distributed to stakers but not yet paid out of the pallet account.
+	#[pallet::storage]
+	#[pallet::getter(fn unclaimed_rewards)]
+	pub type UnclaimedRewards<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
+
+	#[pallet::type_value]
+	pub fn FirstCollectionId() -> CollectionId {
+		DEFAULT_COLLECTION + 1
+	}
+
+	#[pallet::storage]
+	#[pallet::getter(fn next_collection_id)]
+	pub type NextCollectionId<T> =
+		StorageValue<_, CollectionId, ValueQuery, FirstCollectionId>;
+
+	#[pallet::storage]
+	pub type Collections<T: Config> =
+		StorageMap<_, Blake2_128Concat, CollectionId, CollectionDetailsOf<T>, OptionQuery>;
+
+	/// The collection of a kitty and its serial number within that collection.
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_collection)]
+	pub type KittyCollection<T> =
+		StorageMap<_, Blake2_128Concat, KittyId, (CollectionId, u32), OptionQuery>;
+
+	/// Reverse index of `KittyCollection`: collection id => serial number => kitty id.
+	#[pallet::storage]
+	pub type CollectionKitties<T> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		CollectionId,
+		Twox64Concat,
+		u32,
+		KittyId,
+		OptionQuery,
+	>;
+
+	/// Previous owners of a kitty, by kitty id and sequence number.
+	#[pallet::storage]
+	pub type Provenance<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		KittyId,
+		Twox64Concat,
+		u32,
+		ProvenanceRecordOf<T>,
+		OptionQuery,
+	>;
+
+	/// Sequence numbers of the oldest and the next provenance record of a kitty.
+	#[pallet::storage]
+	pub type ProvenanceRange<T> = StorageMap<_, Blake2_128Concat, KittyId, (u32, u32), ValueQuery>;
+
+	/// Kitties holding more than `MaxProvenanceEntries` records, pruned in `on_idle`.
+	#[pallet::storage]
+	pub type ProvenanceToPrune<T> = StorageMap<_, Blake2_128Concat, KittyId, (), OptionQuery>;
+
+	/// Accounts whose vouchers can be claimed.
+	#[pallet::storage]
+	pub type AuthorizedMinters<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, (), OptionQuery>;
+
+	/// Hashes of vouchers that have already been claimed, by the block they expire after.
+	#[pallet::storage]
+	pub type UsedVouchers<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, T::BlockNumber, Identity, [u8; 32], (), OptionQuery>;
+
+	/// The first block whose expired vouchers have not been pruned from `UsedVouchers`.
+	#[pallet::storage]
+	pub type VoucherPruneCursor<T: Config> = StorageValue<_, T::BlockNumber, ValueQuery>;
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
@@ -100,8 +387,36 @@ pub mod pallet {
 		KittyCreated { who: T::AccountId, kitty_id: KittyId, kitty: Kitty },
 		KittyBred { who: T::AccountId, kitty_id: KittyId, kitty: Kitty },
 		KittyTransferred { who: T::AccountId, recipient: T::AccountId, kitty_id: KittyId },
-		KittyOnSale { who: T::AccountId, kitty_id: KittyId },
+		KittyOnSale { who: T::AccountId, kitty_id: KittyId, price: BalanceOf<T> },
+		KittyOnSaleForAsset {
+			who: T::AccountId,
+			kitty_id: KittyId,
+			asset_id: AssetIdOf<T>,
+			price: BalanceOf<T>,
+		},
 		KittyBought { who: T::AccountId, kitty_id: KittyId },
+		KittyStaked { who: T::AccountId, kitty_id: KittyId, unlock_at: T::BlockNumber },
+		KittyUnstaked { who: T::AccountId, kitty_id: KittyId },
+		RewardsClaimed { who: T::AccountId, kitty_id: KittyId, amount: BalanceOf<T> },
+		EraRewardsDistributed { reward_per_kitty: BalanceOf<T>, total: BalanceOf<T> },
+		MinterUpdated { minter: T::AccountId, authorized: bool },
+		VoucherClaimed { minter: T::AccountId, recipient: T::AccountId, kitty_id: KittyId },
+		CollectionCreated {
+			creator: T::AccountId,
+			collection_id: CollectionId,
+			max_supply: Option<u32>,
+		},
+		CollectionPriceSet { collection_id: CollectionId, mint_price: Option<BalanceOf<T>> },
+		CollectionFrozen { collection_id: CollectionId },
+		CollectionThawed { collection_id: CollectionId },
+		PriceParametersSet { parameters: PriceParametersOf<T> },
+		KittyMinted {
+			who: T::AccountId,
+			collection_id: CollectionId,
+			serial: u32,
+			kitty_id: KittyId,
+		},
+		RevenueSwept { amount: BalanceOf<T> },
 	}
 	//错误处理 substrate 使用 #[pallet::error] 定义错误，我们在 Error 中添加如下代码：
 	#[pallet::error]
@@ -114,16 +429,109 @@ pub mod pallet {
 		NoOwner,
 		AlreadyOwned,
 		NotOnSale,
+		/// No more kitty ids are available.
+		KittyIdOverflow,
+		/// A kitty cannot be transferred to its current owner.
+		TransferToSelf,
+		/// Only the owner of both parents can breed them.
+		NotOwnerOfParent,
+		/// The kitty is staked and cannot change hands.
+		KittyStaked,
+		/// The kitty is not staked.
+		NotStaked,
+		/// The kitty's lock-up period has not ended yet.
+		StakeLocked,
+		/// The voucher was not issued by an authorized minter.
+		UnauthorizedMinter,
+		/// The voucher signature does not match its minter.
+		InvalidVoucherSignature,
+		/// The voucher can no longer be claimed.
+		VoucherExpired,
+		/// The voucher has already been claimed.
+		VoucherAlreadyUsed,
+		/// The collection does not exist.
+		UnknownCollection,
+		/// No more collection ids are available.
+		CollectionIdOverflow,
+		/// Only the collection creator can do this.
+		NotCollectionCreator,
+		/// The collection is frozen.
+		CollectionFrozen,
+		/// The collection has reached its maximum supply.
+		CollectionSoldOut,
+		/// The maximum supply of a collection must not be zero.
+		InvalidMaxSupply,
+		UnknownAsset,
+	}
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// Kitties minted at genesis as `(owner, name, dna)`, assigned sequential ids from 0.
+		pub kitties: Vec<(T::AccountId, [u8; 8], [u8; 16])>,
+		/// Accounts allowed to issue kitty vouchers.
+		pub minters: Vec<T::AccountId>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { kitties: Default::default(), minters: Default::default() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			for (owner, name, dna) in &self.kitties {
+				assert!(
+					!Kitties::<T>::iter_values().any(|kitty| kitty.dna == *dna),
+					"duplicate kitty dna in genesis config"
+				);
+				let (collection, kitty_id) = Pallet::<T>::ensure_can_mint(DEFAULT_COLLECTION)
+					.expect("genesis kitties must not overflow the kitty id space");
+				let kitty = Kitty { dna: *dna, name: *name };
+				Pallet::<T>::insert_kitty(DEFAULT_COLLECTION, collection, kitty_id, owner, &kitty);
+			}
+			for minter in &self.minters {
+				AuthorizedMinters::<T>::insert(minter, ());
+			}
+		}
 	}
 
 	//调用hooks方法，内部调用，方法名字是on_runtime_upgrade，最后返回weight，添加好把它加进来
 	#[pallet::hooks]
 	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(n: BlockNumberFor<T>) -> Weight {
+			let era_length = T::EraLength::get();
+			if era_length.is_zero() || !(n % era_length).is_zero() {
+				return Weight::zero()
+			}
+			Self::distribute_era_rewards().saturating_add(Self::sweep_revenue())
+		}
+
 		fn on_runtime_upgrade() -> Weight {
 			// migrations::v1::migrate::<T>()
 			migrations::v2::migrate::<T>()
+				.saturating_add(migrations::v3::migrate::<T>())
+				.saturating_add(migrations::v4::migrate::<T>())
+				.saturating_add(migrations::v5::migrate::<T>())
+				.saturating_add(migrations::v6::migrate::<T>())
 			////函数调用放在hook，直接引用版本
 		}
+
+		fn on_idle(_n: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			let used = Self::prune_provenance(remaining_weight);
+			used.saturating_add(Self::prune_used_vouchers(remaining_weight.saturating_sub(used)))
+		}
+
+		fn offchain_worker(_n: BlockNumberFor<T>) {
+			Self::render_pending_kitties();
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn try_state(_n: BlockNumberFor<T>) -> Result<(), &'static str> {
+			Self::do_try_state()
+		}
 	}
 
 	// Dispatchable functions allows users to interact with the pallet and invoke state changes.
@@ -141,17 +549,17 @@ pub mod pallet {
 			// https://docs.substrate.io/main-docs/build/origins/
 			let who = ensure_signed(origin)?;
 
-			let kitty_id = Self::get_kitty_id()?;
+			// 先检查和付款, 最后再写存储
+			let (collection, kitty_id) = Self::ensure_can_mint(DEFAULT_COLLECTION)?;
 			let dna = Self::random_value(&who);
 			let kitty = Kitty { dna, name };
 
-			let price = T::KittyPrice::get();
+			let price = Self::mint_price(&collection);
 			//根据type get的方法取得price
 			// T::Currency::reserve(&who, price)?;
 			T::Currency::transfer(&who, &Self::get_account_id(), price, ExistenceRequirement::KeepAlive)?;
 
-			Kitties::<T>::insert(kitty_id, &kitty);
-			KittyOwner::<T>::insert(kitty_id, &who);
+			Self::insert_kitty(DEFAULT_COLLECTION, collection, kitty_id, &who, &kitty);
 
 			// Emit an event.
 			Self::deposit_event(Event::KittyCreated { who, kitty_id, kitty });
@@ -173,13 +581,17 @@ pub mod pallet {
 
 			ensure!(kitty_id_1 != kitty_id_2, Error::<T>::SamedKittyId);
 
-			ensure!(Kitties::<T>::contains_key(kitty_id_1), Error::<T>::InvalidKittyId);
-			ensure!(Kitties::<T>::contains_key(kitty_id_2), Error::<T>::InvalidKittyId);
+			let kitty_1 = Kitties::<T>::get(kitty_id_1).ok_or(Error::<T>::InvalidKittyId)?;
+			let kitty_2 = Kitties::<T>::get(kitty_id_2).ok_or(Error::<T>::InvalidKittyId)?;
 
-			let kitty_1 = Kitties::<T>::get(kitty_id_1).expect("We checked it exists");
-			let kitty_2 = Kitties::<T>::get(kitty_id_2).expect("We checked it exists");
+			let owns = |kitty_id| Self::kitty_owner(kitty_id).as_ref() == Some(&who);
+			ensure!(owns(kitty_id_1) && owns(kitty_id_2), Error::<T>::NotOwnerOfParent);
 
-			let kitty_id = Self::get_kitty_id()?;
+			// 先检查和付款, 最后再写存储
+			let (collection, kitty_id) = Self::ensure_can_mint(DEFAULT_COLLECTION)?;
+			let generation = Self::kitty_generation(kitty_id_1)
+				.max(Self::kitty_generation(kitty_id_2))
+				.saturating_add(1);
 
 			let selector = Self::random_value(&who);
 			let mut dna = [0u8; 16];
@@ -189,7 +601,7 @@ pub mod pallet {
 
 			let kitty = Kitty{dna, name};
 
-			let price = T::KittyPrice::get();
+			let price = Self::breed_price(generation);
 			// T::Currency::reserve(&who, price)?; 
 			
 			//和create一样，需要调用这个方法去transfer你的token到pallet account里面，调用方法一样
@@ -200,9 +612,11 @@ pub mod pallet {
 				ExistenceRequirement::KeepAlive,
 			)?;
 
-			Kitties::<T>::insert(kitty_id, &kitty);
-			KittyOwner::<T>::insert(kitty_id, &who);
+			Self::insert_kitty(DEFAULT_COLLECTION, collection, kitty_id, &who, &kitty);
 			KittyParents::<T>::insert(kitty_id, (kitty_id_1, kitty_id_2));
+			KittyGeneration::<T>::insert(kitty_id, generation);
+			KittyChildren::<T>::insert(kitty_id_1, kitty_id, ());
+			KittyChildren::<T>::insert(kitty_id_2, kitty_id, ());
 
 			Self::deposit_event(Event::KittyBred { who, kitty_id, kitty });
 
@@ -219,11 +633,18 @@ pub mod pallet {
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
 			ensure!(Kitties::<T>::contains_key(kitty_id), Error::<T>::InvalidKittyId);
-			ensure!(KittyOwner::<T>::contains_key(kitty_id), Error::<T>::InvalidKittyId);
 
-			let owner = KittyOwner::<T>::get(kitty_id).unwrap();
+			let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
 			ensure!(owner == who, Error::<T>::NotOwner);
+			ensure!(recipient != who, Error::<T>::TransferToSelf);
+			ensure!(!StakedKitties::<T>::contains_key(kitty_id), Error::<T>::KittyStaked);
+			Self::ensure_not_frozen(kitty_id)?;
+
+			// 转移后旧主人的上架不再有效
+			KittyOnSale::<T>::remove(kitty_id);
+			KittySaleAsset::<T>::remove(kitty_id);
 			KittyOwner::<T>::insert(kitty_id, &recipient);
+			Self::record_provenance(kitty_id, owner, None, None);
 
 			Self::deposit_event(Event::KittyTransferred { who, recipient, kitty_id });
 
@@ -233,17 +654,17 @@ pub mod pallet {
 		#[pallet::call_index(3)]
 		#[pallet::weight({0})] 
 		//对kitty可以实现买卖，新增定义方法 sale，有这个方法做标示
-		pub fn sale(origin: OriginFor<T>, kitty_id: KittyId) -> DispatchResult {
+		pub fn sale(
+			origin: OriginFor<T>,
+			kitty_id: KittyId,
+			price: BalanceOf<T>,
+		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
-			ensure!(Kitties::<T>::contains_key(kitty_id), Error::<T>::InvalidKittyId);
-			//是否在？
-			let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::NoOwner)?;
-			ensure!(owner == who, Error::<T>::NotOwner);
-			ensure!(!KittyOnSale::<T>::contains_key(kitty_id), Error::<T>::AlreadyOnSale);
+			Self::ensure_can_list(&who, kitty_id)?;
 			//错误类型 AlreadyOnSale
-			KittyOnSale::<T>::insert(kitty_id, ());
+			KittyOnSale::<T>::insert(kitty_id, price);
 			//链上的状态表示，增加存储
-			Self::deposit_event(Event::KittyOnSale { who, kitty_id });
+			Self::deposit_event(Event::KittyOnSale { who, kitty_id, price });
 			//  抛出KittyOnSale
 			Ok(())
 		}
@@ -261,33 +682,903 @@ pub mod pallet {
 			ensure!(Kitties::<T>::contains_key(kitty_id), Error::<T>::InvalidKittyId);
 			let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::NoOwner)?;
 			ensure!(owner != who, Error::<T>::AlreadyOwned);
-			ensure!(KittyOnSale::<T>::contains_key(kitty_id), Error::<T>::NotOnSale);
+			let price = Self::kitty_on_sale(kitty_id).ok_or(Error::<T>::NotOnSale)?;
+			ensure!(!StakedKitties::<T>::contains_key(kitty_id), Error::<T>::KittyStaked);
+			Self::ensure_not_frozen(kitty_id)?;
 			//基本判断
-			//得到价格
-			let price = T::KittyPrice::get();
+			//得到价格: 卖家上架时定的价格
 			//	调用方法一样，从旧的买家转到新的买家
-			T::Currency::transfer(&who, &owner, price, ExistenceRequirement::KeepAlive)?;
+			// 先付款, 成功后才修改所有权, 付款失败时存储不会被改动
+			let asset_id = Self::kitty_sale_asset(kitty_id);
+			match asset_id {
+				Some(asset_id) => {
+					<T::Assets as fungibles::Transfer<_>>::transfer(
+						asset_id, &who, &owner, price, true,
+					)?;
+				},
+				None => {
+					T::Currency::transfer(&who, &owner, price, ExistenceRequirement::KeepAlive)?;
+				},
+			}
 			// T::Currency::reserve(&who, &Self::get_account_id, price, 如果质押需要调用currency的 reservce 方法
 			// ExistenceRequirement::KeepAlive)?; T::Currency::unreserve(&owner, price);
 
 			KittyOwner::<T>::insert(kitty_id, &who);
 			KittyOnSale::<T>::remove(kitty_id);
+			KittySaleAsset::<T>::remove(kitty_id);
+			Self::record_provenance(kitty_id, owner, Some(price), asset_id);
 			// 状态转换
 			Self::deposit_event(Event::KittyBought { who, kitty_id });
 
 			Ok(())
 		}
+
+		/// Lock a kitty for `StakingLockPeriod` blocks. While staked it earns an equal share of
+		/// the fees collected in the pallet account every era, and cannot be transferred or sold.
+		#[pallet::call_index(5)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(5, 2))]
+		pub fn stake(origin: OriginFor<T>, kitty_id: KittyId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+			ensure!(owner == who, Error::<T>::NotOwner);
+			ensure!(!StakedKitties::<T>::contains_key(kitty_id), Error::<T>::KittyStaked);
+			ensure!(!KittyOnSale::<T>::contains_key(kitty_id), Error::<T>::AlreadyOnSale);
+
+			let now = frame_system::Pallet::<T>::block_number();
+			let unlock_at = now.saturating_add(T::StakingLockPeriod::get());
+			// 只分享质押之后的奖励
+			let stake = StakeInfo { unlock_at, reward_debt: RewardPerKitty::<T>::get() };
+
+			StakedKitties::<T>::insert(kitty_id, stake);
+			TotalStaked::<T>::mutate(|total| *total = total.saturating_add(1));
+
+			Self::deposit_event(Event::KittyStaked { who, kitty_id, unlock_at });
+
+			Ok(())
+		}
+
+		/// Unlock a staked kitty once its lock-up period is over, paying out pending rewards.
+		#[pallet::call_index(6)]
+		// 发放奖励时读写双方账户和 UnclaimedRewards
+		#[pallet::weight(T::DbWeight::get().reads_writes(7, 5))]
+		pub fn unstake(origin: OriginFor<T>, kitty_id: KittyId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+			ensure!(owner == who, Error::<T>::NotOwner);
+			let mut stake = StakedKitties::<T>::get(kitty_id).ok_or(Error::<T>::NotStaked)?;
+			ensure!(
+				frame_system::Pallet::<T>::block_number() >= stake.unlock_at,
+				Error::<T>::StakeLocked
+			);
+
+			Self::pay_rewards(&who, kitty_id, &mut stake)?;
+
+			StakedKitties::<T>::remove(kitty_id);
+			TotalStaked::<T>::mutate(|total| *total = total.saturating_sub(1));
+
+			Self::deposit_event(Event::KittyUnstaked { who, kitty_id });
+
+			Ok(())
+		}
+
+		/// Pay out the rewards a staked kitty has earned so far.
+		#[pallet::call_index(7)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(6, 4))]
+		pub fn claim_rewards(origin: OriginFor<T>, kitty_id: KittyId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+			ensure!(owner == who, Error::<T>::NotOwner);
+			let mut stake = StakedKitties::<T>::get(kitty_id).ok_or(Error::<T>::NotStaked)?;
+
+			Self::pay_rewards(&who, kitty_id, &mut stake)?;
+			StakedKitties::<T>::insert(kitty_id, stake);
+
+			Ok(())
+		}
+
+		/// Mint the kitty described by a voucher of an authorized minter, free of charge.
+		/// Submitted as an unsigned transaction, so the recipient needs no balance.
+		#[pallet::call_index(8)]
+		#[pallet::weight(Pallet::<T>::claim_voucher_weight())]
+		pub fn claim_voucher(
+			origin: OriginFor<T>,
+			voucher: KittyVoucher<T::AccountId, T::BlockNumber>,
+			signature: T::VoucherSignature,
+		) -> DispatchResult {
+			ensure_none(origin)?;
+			Self::check_voucher(&voucher, &signature)?;
+
+			let (collection, kitty_id) = Self::ensure_can_mint(DEFAULT_COLLECTION)?;
+			let dna = Self::random_value(&voucher.recipient);
+			let kitty = Kitty { dna, name: voucher.name };
+
+			UsedVouchers::<T>::insert(voucher.expires_at, voucher.hash(), ());
+			let recipient = &voucher.recipient;
+			Self::insert_kitty(DEFAULT_COLLECTION, collection, kitty_id, recipient, &kitty);
+
+			let KittyVoucher { minter, recipient, .. } = voucher;
+			Self::deposit_event(Event::KittyCreated { who: recipient.clone(), kitty_id, kitty });
+			Self::deposit_event(Event::VoucherClaimed { minter, recipient, kitty_id });
+
+			Ok(())
+		}
+
+		/// Allow or disallow an account to issue kitty vouchers.
+		#[pallet::call_index(9)]
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_minter(
+			origin: OriginFor<T>,
+			minter: T::AccountId,
+			authorized: bool,
+		) -> DispatchResult {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+
+			if authorized {
+				AuthorizedMinters::<T>::insert(&minter, ());
+			} else {
+				AuthorizedMinters::<T>::remove(&minter);
+			}
+
+			Self::deposit_event(Event::MinterUpdated { minter, authorized });
+
+			Ok(())
+		}
+
+		/// Create a collection owned by the caller, holding at most `max_supply` kitties.
+		#[pallet::call_index(10)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 2))]
+		pub fn create_collection(
+			origin: OriginFor<T>,
+			metadata: BoundedVec<u8, T::MaxCollectionMetadata>,
+			max_supply: Option<u32>,
+		) -> DispatchResult {
+			let creator = ensure_signed(origin)?;
+			ensure!(max_supply != Some(0), Error::<T>::InvalidMaxSupply);
+
+			let collection_id = Self::next_collection_id();
+			let next_collection_id =
+				collection_id.checked_add(1).ok_or(Error::<T>::CollectionIdOverflow)?;
+
+			NextCollectionId::<T>::put(next_collection_id);
+			Collections::<T>::insert(
+				collection_id,
+				CollectionDetails {
+					creator: creator.clone(),
+					metadata,
+					max_supply,
+					minted: 0,
+					mint_price: None,
+					frozen: false,
+				},
+			);
+
+			Self::deposit_event(Event::CollectionCreated { creator, collection_id, max_supply });
+
+			Ok(())
+		}
+
+		/// Set the price of minting into a collection, falling back to the price curve if `None`.
+		#[pallet::call_index(11)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_collection_price(
+			origin: OriginFor<T>,
+			collection_id: CollectionId,
+			mint_price: Option<BalanceOf<T>>,
+		) -> DispatchResult {
+			let mut collection = Self::ensure_collection_admin(origin, collection_id)?;
+
+			collection.mint_price = mint_price;
+			Collections::<T>::insert(collection_id, collection);
+
+			Self::deposit_event(Event::CollectionPriceSet { collection_id, mint_price });
+
+			Ok(())
+		}
+
+		/// Stop minting into a collection and moving its kitties.
+		#[pallet::call_index(12)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn freeze_collection(
+			origin: OriginFor<T>,
+			collection_id: CollectionId,
+		) -> DispatchResult {
+			let mut collection = Self::ensure_collection_admin(origin, collection_id)?;
+
+			collection.frozen = true;
+			Collections::<T>::insert(collection_id, collection);
+
+			Self::deposit_event(Event::CollectionFrozen { collection_id });
+
+			Ok(())
+		}
+
+		/// Undo [`Pallet::freeze_collection`].
+		#[pallet::call_index(13)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn thaw_collection(
+			origin: OriginFor<T>,
+			collection_id: CollectionId,
+		) -> DispatchResult {
+			let mut collection = Self::ensure_collection_admin(origin, collection_id)?;
+
+			collection.frozen = false;
+			Collections::<T>::insert(collection_id, collection);
+
+			Self::deposit_event(Event::CollectionThawed { collection_id });
+
+			Ok(())
+		}
+
+		/// Mint a kitty into a collection, paying the collection's mint price.
+		#[pallet::call_index(14)]
+		// 付款读写双方账户, 新 kitty 写入六项存储
+		#[pallet::weight(T::DbWeight::get().reads_writes(6, 8))]
+		pub fn mint(
+			origin: OriginFor<T>,
+			collection_id: CollectionId,
+			name: [u8; 8],
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let (collection, kitty_id) = Self::ensure_can_mint(collection_id)?;
+			let dna = Self::random_value(&who);
+			let kitty = Kitty { dna, name };
+
+			let price = Self::mint_price(&collection);
+			T::Currency::transfer(
+				&who,
+				&Self::get_account_id(),
+				price,
+				ExistenceRequirement::KeepAlive,
+			)?;
+
+			let serial = Self::insert_kitty(collection_id, collection, kitty_id, &who, &kitty);
+
+			Self::deposit_event(Event::KittyMinted { who, collection_id, serial, kitty_id });
+
+			Ok(())
+		}
+
+		/// Change the parameters of the mint and breed price curve.
+		#[pallet::call_index(15)]
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_price_parameters(
+			origin: OriginFor<T>,
+			parameters: PriceParametersOf<T>,
+		) -> DispatchResult {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+
+			PriceParameters::<T>::put(&parameters);
+
+			Self::deposit_event(Event::PriceParametersSet { parameters });
+
+			Ok(())
+		}
+
+		/// List a kitty priced in `asset_id` instead of the native currency.
+		#[pallet::call_index(16)]
+		// 检查上架条件读六项, 再读资产
+		#[pallet::weight(T::DbWeight::get().reads_writes(7, 2))]
+		pub fn sale_for_asset(
+			origin: OriginFor<T>,
+			kitty_id: KittyId,
+			asset_id: AssetIdOf<T>,
+			price: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_can_list(&who, kitty_id)?;
+			ensure!(
+				<T::Assets as fungibles::Inspect<_>>::asset_exists(asset_id),
+				Error::<T>::UnknownAsset
+			);
+
+			KittyOnSale::<T>::insert(kitty_id, price);
+			KittySaleAsset::<T>::insert(kitty_id, asset_id);
+
+			Self::deposit_event(Event::KittyOnSaleForAsset { who, kitty_id, asset_id, price });
+
+			Ok(())
+		}
+	}
+
+	#[pallet::validate_unsigned]
+	impl<T: Config> ValidateUnsigned for Pallet<T> {
+		type Call = Call<T>;
+
+		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			let (voucher, signature) = match call {
+				Call::claim_voucher { voucher, signature } => (voucher, signature),
+				_ => return InvalidTransaction::Call.into(),
+			};
+
+			Self::check_voucher(voucher, signature).map_err(|error| match error {
+				Error::<T>::InvalidVoucherSignature => InvalidTransaction::BadProof,
+				Error::<T>::VoucherExpired | Error::<T>::VoucherAlreadyUsed =>
+					InvalidTransaction::Stale,
+				_ => InvalidTransaction::BadSigner,
+			})?;
+
+			let now = frame_system::Pallet::<T>::block_number();
+			ValidTransaction::with_tag_prefix("KittyVoucher")
+				.priority(T::VoucherPriority::get())
+				.and_provides(voucher.hash())
+				.longevity(voucher.expires_at.saturating_sub(now).saturated_into::<u64>().max(1))
+				.propagate(true)
+				.build()
+		}
+	}
+
+	impl<T: Config> KittyInspect<T::AccountId> for Pallet<T> {
+		fn dna(kitty_id: KittyId) -> Option<[u8; 16]> {
+			Kitties::<T>::get(kitty_id).map(|kitty| kitty.dna)
+		}
+
+		fn owner(kitty_id: KittyId) -> Option<T::AccountId> {
+			KittyOwner::<T>::get(kitty_id)
+		}
 	}
 
 	impl<T: Config> Pallet<T> {
-		fn get_kitty_id() -> Result<KittyId, DispatchError> {
-			NextKittyId::<T>::try_mutate(|next_id| -> Result<KittyId, DispatchError> {
-				let current_id = *next_id;
-				*next_id = next_id.checked_add(1).ok_or(Error::<T>::InvalidKittyId)?;
-				Ok(current_id)
+		/// The kitty itself followed by its ancestors, breadth first, up to `max_depth`
+		/// generations back (capped by `MaxLineageDepth` and `MaxLineageSize`).
+		pub fn ancestors(kitty_id: KittyId, max_depth: u32) -> Vec<KittyRelative> {
+			Self::walk_lineage(kitty_id, max_depth, |id| {
+				KittyParents::<T>::get(id).map(|(p1, p2)| vec![p1, p2]).unwrap_or_default()
+			})
+		}
+
+		/// The kitty itself followed by its descendants, breadth first, up to `max_depth`
+		/// generations forward (capped by `MaxLineageDepth` and `MaxLineageSize`).
+		pub fn descendants(kitty_id: KittyId, max_depth: u32) -> Vec<KittyRelative> {
+			Self::walk_lineage(kitty_id, max_depth, |id| {
+				KittyChildren::<T>::iter_key_prefix(id).collect()
+			})
+		}
+
+		/// The previous owners of a kitty, oldest first, at most the last `MaxProvenanceEntries`.
+		pub fn provenance(kitty_id: KittyId) -> Vec<ProvenanceRecordOf<T>> {
+			let (oldest, next) = ProvenanceRange::<T>::get(kitty_id);
+			let first = oldest.max(next.saturating_sub(T::MaxProvenanceEntries::get()));
+			(first..next).filter_map(|index| Provenance::<T>::get(kitty_id, index)).collect()
+		}
+
+		/// Remember that `owner` gave `kitty_id` away in the current block.
+		fn record_provenance(
+			kitty_id: KittyId,
+			owner: T::AccountId,
+			price: Option<BalanceOf<T>>,
+			asset_id: Option<AssetIdOf<T>>,
+		) {
+			let block = frame_system::Pallet::<T>::block_number();
+			let record = ProvenanceRecord { owner, block, price, asset_id };
+			ProvenanceRange::<T>::mutate(kitty_id, |(oldest, next)| {
+				Provenance::<T>::insert(kitty_id, *next, record);
+				*next = next.saturating_add(1);
+				// 超出上限的记录留给 on_idle 删除
+				if *next - *oldest > T::MaxProvenanceEntries::get() {
+					ProvenanceToPrune::<T>::insert(kitty_id, ());
+				}
+			});
+		}
+
+		/// Delete the oldest provenance records of kitties holding more than
+		/// `MaxProvenanceEntries`, as far as `remaining_weight` allows.
+		fn prune_provenance(remaining_weight: Weight) -> Weight {
+			let db_weight = T::DbWeight::get();
+			let per_kitty = db_weight.reads_writes(2, 2);
+			let per_record = db_weight.writes(1);
+			let max = T::MaxProvenanceEntries::get();
+
+			let mut used = db_weight.reads(1);
+			let fits =
+				|used: Weight, extra: Weight| remaining_weight.all_gte(used.saturating_add(extra));
+			if !fits(used, per_kitty.saturating_add(per_record)) {
+				return Weight::zero()
+			}
+
+			// 先取出一批，避免在遍历时修改同一个 map
+			let batch = remaining_weight
+				.ref_time()
+				.checked_div(per_kitty.saturating_add(per_record).ref_time())
+				.unwrap_or(u64::MAX);
+			let kitties: Vec<KittyId> =
+				ProvenanceToPrune::<T>::iter_keys().take(batch.saturated_into()).collect();
+
+			for kitty_id in kitties {
+				if !fits(used, per_kitty.saturating_add(per_record)) {
+					break
+				}
+				used.saturating_accrue(per_kitty);
+
+				let (mut oldest, next) = ProvenanceRange::<T>::get(kitty_id);
+				while next - oldest > max && fits(used, per_record) {
+					Provenance::<T>::remove(kitty_id, oldest);
+					oldest += 1;
+					used.saturating_accrue(per_record);
+				}
+				ProvenanceRange::<T>::insert(kitty_id, (oldest, next));
+				if next - oldest <= max {
+					ProvenanceToPrune::<T>::remove(kitty_id);
+				}
+			}
+
+			used
+		}
+
+		/// Delete the used vouchers that expired, block by block, as far as `remaining_weight`
+		/// allows. Expired vouchers are rejected anyway, so they cannot be claimed again.
+		fn prune_used_vouchers(remaining_weight: Weight) -> Weight {
+			let db_weight = T::DbWeight::get();
+			let per_block = db_weight.reads(1);
+			let per_voucher = db_weight.reads_writes(1, 1);
+
+			let mut used = db_weight.reads_writes(1, 1);
+			let fits =
+				|used: Weight, extra: Weight| remaining_weight.all_gte(used.saturating_add(extra));
+			if !fits(used, per_block.saturating_add(per_voucher)) {
+				return Weight::zero()
+			}
+
+			let now = frame_system::Pallet::<T>::block_number();
+			let mut block = VoucherPruneCursor::<T>::get();
+			while block < now && fits(used, per_block.saturating_add(per_voucher)) {
+				used.saturating_accrue(per_block);
+				let limit = remaining_weight
+					.saturating_sub(used)
+					.ref_time()
+					.checked_div(per_voucher.ref_time())
+					.unwrap_or(u64::MAX);
+				let result = UsedVouchers::<T>::clear_prefix(block, limit.saturated_into(), None);
+				used.saturating_accrue(per_voucher.saturating_mul(result.loops.into()));
+				// 这个区块还没删完, 下次继续
+				if result.maybe_cursor.is_some() {
+					break
+				}
+				block += One::one();
+			}
+			VoucherPruneCursor::<T>::put(block);
+
+			used
+		}
+
+		/// Weight of `claim_voucher`: checking the minter's signature and minting the kitty.
+		pub fn claim_voucher_weight() -> Weight {
+			// 读 AuthorizedMinters、UsedVouchers、Collections、NextKittyId 和随机数,
+			// 写 UsedVouchers 和新 kitty 的六项存储
+			VOUCHER_SIGNATURE_WEIGHT
+				.saturating_add(VOUCHER_EXECUTION_WEIGHT)
+				.saturating_add(T::DbWeight::get().reads_writes(5, 7))
+		}
+
+		/// Rewards a staked kitty can currently claim.
+		pub fn pending_rewards(kitty_id: KittyId) -> BalanceOf<T> {
+			StakedKitties::<T>::get(kitty_id)
+				.map(|stake| RewardPerKitty::<T>::get().saturating_sub(stake.reward_debt))
+				.unwrap_or_else(Zero::zero)
+		}
+
+		/// Split `StakerShare` of the fees collected in the pallet account since the last era
+		/// equally among all staked kitties. Only the per-kitty accumulator is updated, so the
+		/// cost does not depend on the number of stakers; the other fees and the division
+		/// remainder are swept by `sweep_revenue`.
+		fn distribute_era_rewards() -> Weight {
+			let total_staked = TotalStaked::<T>::get();
+			if total_staked == 0 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let unclaimed = UnclaimedRewards::<T>::get();
+			let revenue = T::Currency::free_balance(&Self::get_account_id())
+				.saturating_sub(T::Currency::minimum_balance())
+				.saturating_sub(unclaimed);
+			let shared = T::StakerShare::get() * revenue;
+			let reward_per_kitty = shared / BalanceOf::<T>::from(total_staked);
+			if reward_per_kitty.is_zero() {
+				return T::DbWeight::get().reads(3)
+			}
+
+			let total = reward_per_kitty.saturating_mul(BalanceOf::<T>::from(total_staked));
+			RewardPerKitty::<T>::mutate(|acc| acc.saturating_accrue(reward_per_kitty));
+			UnclaimedRewards::<T>::put(unclaimed.saturating_add(total));
+
+			Self::deposit_event(Event::EraRewardsDistributed { reward_per_kitty, total });
+
+			T::DbWeight::get().reads_writes(4, 3)
+		}
+
+		/// Hand everything in the pallet account that is not owed to stakers over to
+		/// `RevenueDestination`, keeping the account alive.
+		fn sweep_revenue() -> Weight {
+			let account = Self::get_account_id();
+			let surplus = T::Currency::free_balance(&account)
+				.saturating_sub(T::Currency::minimum_balance())
+				.saturating_sub(UnclaimedRewards::<T>::get());
+			if surplus.is_zero() {
+				return T::DbWeight::get().reads(2)
+			}
+
+			// 只取出质押者尚未领取之外的部分, 账户被锁定时留到下一个 era
+			if let Ok(revenue) = T::Currency::withdraw(
+				&account,
+				surplus,
+				WithdrawReasons::TRANSFER,
+				ExistenceRequirement::KeepAlive,
+			) {
+				T::RevenueDestination::on_unbalanced(revenue);
+				Self::deposit_event(Event::RevenueSwept { amount: surplus });
+			}
+
+			T::DbWeight::get().reads_writes(3, 2)
+		}
+
+		fn pay_rewards(
+			who: &T::AccountId,
+			kitty_id: KittyId,
+			stake: &mut StakeInfo<T::BlockNumber, BalanceOf<T>>,
+		) -> DispatchResult {
+			let reward_per_kitty = RewardPerKitty::<T>::get();
+			let amount = reward_per_kitty.saturating_sub(stake.reward_debt);
+			stake.reward_debt = reward_per_kitty;
+			if amount.is_zero() {
+				return Ok(())
+			}
+
+			T::Currency::transfer(
+				&Self::get_account_id(),
+				who,
+				amount,
+				ExistenceRequirement::KeepAlive,
+			)?;
+			UnclaimedRewards::<T>::mutate(|unclaimed| unclaimed.saturating_reduce(amount));
+
+			Self::deposit_event(Event::RewardsClaimed { who: who.clone(), kitty_id, amount });
+
+			Ok(())
+		}
+
+		/// Check the storage invariants of the pallet.
+		#[cfg(any(feature = "try-runtime", test))]
+		pub fn do_try_state() -> Result<(), &'static str> {
+			let next_kitty_id = Self::next_kitty_id();
+
+			for (kitty_id, _) in Kitties::<T>::iter() {
+				ensure!(kitty_id < next_kitty_id, "kitty id is not below NextKittyId");
+				ensure!(KittyOwner::<T>::contains_key(kitty_id), "kitty has no owner");
+				ensure!(KittyCollection::<T>::contains_key(kitty_id), "kitty has no collection");
+			}
+			for kitty_id in KittyOwner::<T>::iter_keys() {
+				ensure!(Kitties::<T>::contains_key(kitty_id), "owner entry for a missing kitty");
+			}
+			for kitty_id in KittyOnSale::<T>::iter_keys() {
+				ensure!(Kitties::<T>::contains_key(kitty_id), "listed kitty does not exist");
+			}
+			for kitty_id in KittySaleAsset::<T>::iter_keys() {
+				ensure!(
+					KittyOnSale::<T>::contains_key(kitty_id),
+					"sale asset of an unlisted kitty"
+				);
+			}
+			for (kitty_id, (parent_1, parent_2)) in KittyParents::<T>::iter() {
+				ensure!(Kitties::<T>::contains_key(kitty_id), "parents of a missing kitty");
+				for parent in [parent_1, parent_2] {
+					ensure!(Kitties::<T>::contains_key(parent), "kitty parent does not exist");
+					ensure!(parent < kitty_id, "kitty parent is younger than its child");
+					ensure!(
+						KittyChildren::<T>::contains_key(parent, kitty_id),
+						"kitty missing from its parent's children"
+					);
+				}
+			}
+			for (parent, kitty_id, ()) in KittyChildren::<T>::iter() {
+				let is_parent = KittyParents::<T>::get(kitty_id)
+					.map_or(false, |(parent_1, parent_2)| parent == parent_1 || parent == parent_2);
+				ensure!(is_parent, "children entry without matching parents entry");
+			}
+			let mut staked = 0u32;
+			for kitty_id in StakedKitties::<T>::iter_keys() {
+				ensure!(Kitties::<T>::contains_key(kitty_id), "staked kitty does not exist");
+				ensure!(!KittyOnSale::<T>::contains_key(kitty_id), "staked kitty is listed");
+				staked += 1;
+			}
+			ensure!(staked == TotalStaked::<T>::get(), "TotalStaked does not match staked kitties");
+			for (kitty_id, (collection_id, serial)) in KittyCollection::<T>::iter() {
+				ensure!(
+					Kitties::<T>::contains_key(kitty_id),
+					"collection entry for a missing kitty"
+				);
+				ensure!(
+					CollectionKitties::<T>::get(collection_id, serial) == Some(kitty_id),
+					"kitty missing from its collection"
+				);
+				let collection =
+					Collections::<T>::get(collection_id).ok_or("kitty collection does not exist")?;
+				ensure!(serial < collection.minted, "kitty serial is not below the minted count");
+			}
+			for collection in Collections::<T>::iter_values() {
+				let max_supply = collection.max_supply.unwrap_or(u32::MAX);
+				ensure!(collection.minted <= max_supply, "collection exceeds its max supply");
+			}
+			for (kitty_id, (oldest, next)) in ProvenanceRange::<T>::iter() {
+				ensure!(oldest <= next, "provenance range is reversed");
+				let pending = ProvenanceToPrune::<T>::contains_key(kitty_id);
+				ensure!(
+					pending || next - oldest <= T::MaxProvenanceEntries::get(),
+					"too many provenance records and none pending pruning"
+				);
+			}
+			let prune_cursor = VoucherPruneCursor::<T>::get();
+			for (expires_at, _) in UsedVouchers::<T>::iter_keys() {
+				ensure!(expires_at >= prune_cursor, "expired voucher not pruned");
+			}
+
+			Ok(())
+		}
+
+		fn walk_lineage(
+			root: KittyId,
+			max_depth: u32,
+			next: impl Fn(KittyId) -> Vec<KittyId>,
+		) -> Vec<KittyRelative> {
+			let max_depth = max_depth.min(T::MaxLineageDepth::get());
+			let max_size = T::MaxLineageSize::get() as usize;
+
+			let mut relatives = Vec::new();
+			let mut visited = BTreeSet::from([root]);
+			let mut frontier = vec![root];
+
+			for depth in 0..=max_depth {
+				let mut next_frontier = Vec::new();
+				for kitty_id in frontier {
+					if relatives.len() >= max_size {
+						return relatives
+					}
+					let kitty = match Kitties::<T>::get(kitty_id) {
+						Some(kitty) => kitty,
+						None => continue,
+					};
+					relatives.push(KittyRelative {
+						kitty_id,
+						kitty,
+						parents: KittyParents::<T>::get(kitty_id),
+						depth,
+					});
+					if depth < max_depth {
+						next_frontier
+							.extend(next(kitty_id).into_iter().filter(|id| visited.insert(*id)));
+					}
+				}
+				if next_frontier.is_empty() {
+					break
+				}
+				frontier = next_frontier;
+			}
+
+			relatives
+		}
+
+		/// Render the artwork of kitties created since the last run into persistent offchain
+		/// storage, then check the already rendered kitties in turn and render them again if a
+		/// reorg changed them. At most `render::MAX_RENDERS_PER_BLOCK` kitties per call.
+		pub(crate) fn render_pending_kitties() {
+			use sp_runtime::offchain::{
+				storage::StorageValueRef,
+				storage_lock::{StorageLock, Time},
+			};
+
+			// 不同分叉上的 worker 可能同时运行, 拿不到锁的留给下一个区块
+			let mut lock = StorageLock::<Time>::new(render::RENDER_LOCK_KEY);
+			let _guard = match lock.try_lock() {
+				Ok(guard) => guard,
+				Err(_) => return,
+			};
+
+			let cursor = StorageValueRef::persistent(render::RENDER_CURSOR_KEY);
+			let (rendered, mut checked) =
+				cursor.get::<(KittyId, KittyId)>().ok().flatten().unwrap_or_default();
+			// 重组后 kitty 可能变少, 之后重新创建的 id 要重新渲染
+			let start = rendered.min(Self::next_kitty_id());
+			let end =
+				Self::next_kitty_id().min(start.saturating_add(render::MAX_RENDERS_PER_BLOCK));
+			for kitty_id in start..end {
+				Self::render_kitty(kitty_id);
+			}
+
+			// 剩下的额度轮流检查已经渲染过的 kitty
+			let budget = render::MAX_RENDERS_PER_BLOCK - (end - start);
+			for _ in 0..budget.min(start) {
+				if checked >= start {
+					checked = 0;
+				}
+				Self::render_kitty(checked);
+				checked += 1;
+			}
+
+			cursor.set(&(end, checked));
+		}
+
+		/// Render the artwork of a kitty unless it was rendered from the same kitty already, and
+		/// clear it if the kitty does not exist.
+		fn render_kitty(kitty_id: KittyId) {
+			use sp_runtime::offchain::{storage::StorageValueRef, StorageKind};
+
+			let rendered_key = render::storage_key(render::RENDERED_KEY_PREFIX, kitty_id);
+			let mut rendered = StorageValueRef::persistent(&rendered_key);
+			let kitty = Self::kitties(kitty_id);
+			if rendered.get::<Kitty>().ok().flatten() == kitty {
+				return
+			}
+
+			let svg_key = render::storage_key(render::SVG_KEY_PREFIX, kitty_id);
+			let metadata_key = render::storage_key(render::METADATA_KEY_PREFIX, kitty_id);
+			match kitty {
+				Some(kitty) => {
+					sp_io::offchain::local_storage_set(
+						StorageKind::PERSISTENT,
+						&svg_key,
+						render::svg(&kitty.dna).as_bytes(),
+					);
+					sp_io::offchain::local_storage_set(
+						StorageKind::PERSISTENT,
+						&metadata_key,
+						render::metadata(kitty_id, &kitty).as_bytes(),
+					);
+					rendered.set(&kitty);
+				},
+				None => {
+					sp_io::offchain::local_storage_clear(StorageKind::PERSISTENT, &svg_key);
+					sp_io::offchain::local_storage_clear(StorageKind::PERSISTENT, &metadata_key);
+					rendered.clear();
+				},
+			}
+		}
+
+		/// Check that a voucher can be claimed: issued by an authorized minter, correctly signed,
+		/// not expired and not claimed before.
+		pub fn check_voucher(
+			voucher: &KittyVoucher<T::AccountId, T::BlockNumber>,
+			signature: &T::VoucherSignature,
+		) -> Result<(), Error<T>> {
+			ensure!(
+				AuthorizedMinters::<T>::contains_key(&voucher.minter),
+				Error::<T>::UnauthorizedMinter
+			);
+			ensure!(
+				frame_system::Pallet::<T>::block_number() <= voucher.expires_at,
+				Error::<T>::VoucherExpired
+			);
+			ensure!(
+				!UsedVouchers::<T>::contains_key(voucher.expires_at, voucher.hash()),
+				Error::<T>::VoucherAlreadyUsed
+			);
+			ensure!(
+				signature.verify(&voucher.signing_payload()[..], &voucher.minter),
+				Error::<T>::InvalidVoucherSignature
+			);
+			Ok(())
+		}
+
+		/// A collection, including the default collection before it is stored.
+		pub fn collections(collection_id: CollectionId) -> Option<CollectionDetailsOf<T>> {
+			Collections::<T>::get(collection_id).or_else(|| {
+				(collection_id == DEFAULT_COLLECTION).then(|| CollectionDetails {
+					creator: Self::get_account_id(),
+					metadata: Default::default(),
+					max_supply: None,
+					minted: 0,
+					mint_price: None,
+					frozen: false,
+				})
 			})
 		}
 
+		/// The price of minting a kitty into `collection`: its own price if set, otherwise the
+		/// price curve at the current supply.
+		pub fn mint_price(collection: &CollectionDetailsOf<T>) -> BalanceOf<T> {
+			collection.mint_price.unwrap_or_else(|| {
+				let input = PriceInput { supply: Self::next_kitty_id(), generation: 0 };
+				T::PriceCurve::price(&Self::price_parameters(), input)
+			})
+		}
+
+		/// The price of breeding a kitty of `generation`.
+		pub fn breed_price(generation: u32) -> BalanceOf<T> {
+			let input = PriceInput { supply: Self::next_kitty_id(), generation };
+			T::PriceCurve::price(&Self::price_parameters(), input)
+		}
+
+		/// The current price of minting into a collection, `None` if it does not exist.
+		pub fn quote_mint_price(collection_id: CollectionId) -> Option<BalanceOf<T>> {
+			Self::collections(collection_id).map(|collection| Self::mint_price(&collection))
+		}
+
+		/// The current price of breeding two kitties, `None` if either does not exist.
+		pub fn quote_breed_price(
+			kitty_id_1: KittyId,
+			kitty_id_2: KittyId,
+		) -> Option<BalanceOf<T>> {
+			if !Kitties::<T>::contains_key(kitty_id_1) || !Kitties::<T>::contains_key(kitty_id_2) {
+				return None
+			}
+			let generation = Self::kitty_generation(kitty_id_1)
+				.max(Self::kitty_generation(kitty_id_2))
+				.saturating_add(1);
+			Some(Self::breed_price(generation))
+		}
+
+		/// Check that a kitty can be minted into `collection_id`, returning the collection and the
+		/// id the kitty will get. Nothing is written, see [`Self::insert_kitty`].
+		fn ensure_can_mint(
+			collection_id: CollectionId,
+		) -> Result<(CollectionDetailsOf<T>, KittyId), DispatchError> {
+			let collection = Self::collections(collection_id).ok_or(Error::<T>::UnknownCollection)?;
+			ensure!(!collection.frozen, Error::<T>::CollectionFrozen);
+			ensure!(
+				collection.max_supply.map_or(true, |max_supply| collection.minted < max_supply),
+				Error::<T>::CollectionSoldOut
+			);
+
+			let kitty_id = Self::next_kitty_id();
+			kitty_id.checked_add(1).ok_or(Error::<T>::KittyIdOverflow)?;
+
+			Ok((collection, kitty_id))
+		}
+
+		/// Store a kitty checked by [`Self::ensure_can_mint`] as the next one of its collection.
+		fn insert_kitty(
+			collection_id: CollectionId,
+			mut collection: CollectionDetailsOf<T>,
+			kitty_id: KittyId,
+			owner: &T::AccountId,
+			kitty: &Kitty,
+		) -> u32 {
+			let serial = collection.minted;
+			collection.minted = serial.saturating_add(1);
+
+			NextKittyId::<T>::put(kitty_id.saturating_add(1));
+			Kitties::<T>::insert(kitty_id, kitty);
+			KittyOwner::<T>::insert(kitty_id, owner);
+			KittyCollection::<T>::insert(kitty_id, (collection_id, serial));
+			CollectionKitties::<T>::insert(collection_id, serial, kitty_id);
+			Collections::<T>::insert(collection_id, collection);
+
+			serial
+		}
+
+		/// `who` owns `kitty_id` and may put it on sale.
+		fn ensure_can_list(who: &T::AccountId, kitty_id: KittyId) -> DispatchResult {
+			ensure!(Kitties::<T>::contains_key(kitty_id), Error::<T>::InvalidKittyId);
+			//是否在？
+			let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::NoOwner)?;
+			ensure!(&owner == who, Error::<T>::NotOwner);
+			ensure!(!KittyOnSale::<T>::contains_key(kitty_id), Error::<T>::AlreadyOnSale);
+			ensure!(!StakedKitties::<T>::contains_key(kitty_id), Error::<T>::KittyStaked);
+			Self::ensure_not_frozen(kitty_id)
+		}
+
+		fn ensure_not_frozen(kitty_id: KittyId) -> DispatchResult {
+			let frozen = Self::kitty_collection(kitty_id)
+				.and_then(|(collection_id, _)| Self::collections(collection_id))
+				.map_or(false, |collection| collection.frozen);
+			ensure!(!frozen, Error::<T>::CollectionFrozen);
+			Ok(())
+		}
+
+		/// The collection `origin` may manage: signed by its creator, or the governance origin.
+		fn ensure_collection_admin(
+			origin: OriginFor<T>,
+			collection_id: CollectionId,
+		) -> Result<CollectionDetailsOf<T>, DispatchError> {
+			let collection = Self::collections(collection_id).ok_or(Error::<T>::UnknownCollection)?;
+			if let Err(origin) = T::GovernanceOrigin::try_origin(origin) {
+				let who = ensure_signed(origin)?;
+				ensure!(who == collection.creator, Error::<T>::NotCollectionCreator);
+			}
+			Ok(collection)
+		}
+
 		fn random_value(sender: &T::AccountId) -> [u8; 16] {
 			let payload = (
 				T::Randomness::random_seed(),
@@ -1,7 +1,7 @@
 use codec::{Decode, Encode, MaxEncodedLen};
 use frame_support::{
 	migration::storage_key_iter, storage::StoragePrefixedMap,
-	traits::GetStorageVersion, weights::Weight, Blake2_128Concat,
+	traits::{GetStorageVersion, StorageVersion}, weights::Weight, Blake2_128Concat,
 };
 use scale_info::TypeInfo;
 
@@ -21,7 +21,7 @@ pub fn migrate<T: Config>() -> Weight {
 		return Weight::zero()
 	}
 
-	if current_version != 2 {
+	if current_version < 2 {
 		return Weight::zero()
 	}
 
@@ -37,5 +37,7 @@ pub fn migrate<T: Config>() -> Weight {
 		Kitties::<T>::insert(index, &new_kitty);
 	}
 
+	StorageVersion::new(2).put::<Pallet<T>>();
+
 	Weight::zero()
 }
@@ -0,0 +1,31 @@
+use frame_support::{
+	traits::{Get, GetStorageVersion, StorageVersion},
+	weights::Weight,
+};
+
+use crate::{Config, KittyChildren, KittyParents, Pallet};
+
+// v3 adds the `KittyChildren` reverse index, rebuilt from the existing `KittyParents` entries.
+pub fn migrate<T: Config>() -> Weight {
+	let on_chain_version = Pallet::<T>::on_chain_storage_version();
+	let current_version = Pallet::<T>::current_storage_version();
+
+	if on_chain_version != 2 {
+		return Weight::zero()
+	}
+
+	if current_version < 3 {
+		return Weight::zero()
+	}
+
+	let mut count = 0u64;
+	for (kitty_id, (parent_1, parent_2)) in KittyParents::<T>::iter() {
+		KittyChildren::<T>::insert(parent_1, kitty_id, ());
+		KittyChildren::<T>::insert(parent_2, kitty_id, ());
+		count += 1;
+	}
+
+	StorageVersion::new(3).put::<Pallet<T>>();
+
+	T::DbWeight::get().reads_writes(count, count * 2 + 1)
+}
@@ -0,0 +1,41 @@
+use frame_support::{
+	traits::{Get, GetStorageVersion, StorageVersion},
+	weights::Weight,
+};
+
+use crate::{
+	CollectionKitties, Collections, Config, KittyCollection, NextKittyId, Pallet,
+	DEFAULT_COLLECTION,
+};
+
+// v4 adds collections: existing kitties join the default collection, numbered in id order.
+pub fn migrate<T: Config>() -> Weight {
+	let on_chain_version = Pallet::<T>::on_chain_storage_version();
+	let current_version = Pallet::<T>::current_storage_version();
+
+	if on_chain_version != 3 {
+		return Weight::zero()
+	}
+
+	if current_version < 4 {
+		return Weight::zero()
+	}
+
+	let mut collection =
+		Pallet::<T>::collections(DEFAULT_COLLECTION).expect("the default collection always exists");
+	let next_kitty_id = NextKittyId::<T>::get();
+	for kitty_id in 0..next_kitty_id {
+		if Pallet::<T>::kitties(kitty_id).is_none() {
+			continue
+		}
+		KittyCollection::<T>::insert(kitty_id, (DEFAULT_COLLECTION, collection.minted));
+		CollectionKitties::<T>::insert(DEFAULT_COLLECTION, collection.minted, kitty_id);
+		collection.minted += 1;
+	}
+	let count = collection.minted as u64;
+	Collections::<T>::insert(DEFAULT_COLLECTION, collection);
+
+	StorageVersion::new(4).put::<Pallet<T>>();
+
+	T::DbWeight::get().reads_writes(next_kitty_id as u64 + 2, count * 2 + 2)
+}
@@ -0,0 +1,50 @@
+use frame_support::{
+	traits::{Get, GetStorageVersion, StorageVersion},
+	weights::Weight,
+};
+
+use crate::{
+	Config, KittyGeneration, KittyOnSale, KittyParents, NextKittyId, Pallet, DEFAULT_COLLECTION,
+};
+
+// v5 replaces the constant price: listings get an asking price, and bred kitties a generation.
+pub fn migrate<T: Config>() -> Weight {
+	let on_chain_version = Pallet::<T>::on_chain_storage_version();
+	let current_version = Pallet::<T>::current_storage_version();
+
+	if on_chain_version != 4 {
+		return Weight::zero()
+	}
+
+	if current_version < 5 {
+		return Weight::zero()
+	}
+
+	// 旧的上架没有价格，按当前的铸造价格上架
+	let collection =
+		Pallet::<T>::collections(DEFAULT_COLLECTION).expect("the default collection always exists");
+	let price = Pallet::<T>::mint_price(&collection);
+	let mut listed = 0u64;
+	KittyOnSale::<T>::translate::<(), _>(|_, _| {
+		listed += 1;
+		Some(price)
+	});
+
+	// 父母的 id 总是比孩子小，按 id 顺序就能算出每一代
+	let next_kitty_id = NextKittyId::<T>::get();
+	let mut bred = 0u64;
+	for kitty_id in 0..next_kitty_id {
+		if let Some((parent_1, parent_2)) = KittyParents::<T>::get(kitty_id) {
+			let generation = KittyGeneration::<T>::get(parent_1)
+				.max(KittyGeneration::<T>::get(parent_2))
+				.saturating_add(1);
+			KittyGeneration::<T>::insert(kitty_id, generation);
+			bred += 1;
+		}
+	}
+
+	StorageVersion::new(5).put::<Pallet<T>>();
+
+	T::DbWeight::get()
+		.reads_writes(listed + next_kitty_id as u64 + bred * 2 + 3, listed + bred + 1)
+}
@@ -0,0 +1,46 @@
+use codec::{Decode, Encode};
+use frame_support::{
+	traits::{Get, GetStorageVersion, StorageVersion},
+	weights::Weight,
+};
+
+use crate::{BalanceOf, Config, Pallet, Provenance, ProvenanceRecord};
+
+#[derive(Encode, Decode)]
+pub struct OldProvenanceRecord<AccountId, BlockNumber, Balance> {
+	owner: AccountId,
+	block: BlockNumber,
+	price: Option<Balance>,
+}
+
+// v6 records the asset of each sale: earlier sales were all paid in the native currency.
+pub fn migrate<T: Config>() -> Weight {
+	let on_chain_version = Pallet::<T>::on_chain_storage_version();
+	let current_version = Pallet::<T>::current_storage_version();
+
+	if on_chain_version != 5 {
+		return Weight::zero()
+	}
+
+	if current_version < 6 {
+		return Weight::zero()
+	}
+
+	let mut count = 0u64;
+	Provenance::<T>::translate::<
+		OldProvenanceRecord<T::AccountId, T::BlockNumber, BalanceOf<T>>,
+		_,
+	>(|_, _, old| {
+		count += 1;
+		Some(ProvenanceRecord {
+			owner: old.owner,
+			block: old.block,
+			price: old.price,
+			asset_id: None,
+		})
+	});
+
+	StorageVersion::new(6).put::<Pallet<T>>();
+
+	T::DbWeight::get().reads_writes(count + 1, count + 1)
+}
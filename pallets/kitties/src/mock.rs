@@ -1,16 +1,20 @@
 use crate as pallet_kitties;
+use crate::pricing::{LinearParameters, LinearPrice, PriceBasis};
 use frame_support::{
 	parameter_types,
-	traits::{ConstU16, ConstU64},
+	traits::{AsEnsureOriginWithArg, ConstU16, ConstU64, Currency, Get, OnUnbalanced},
 	PalletId,
 };
-use pallet_balances::{self, AccountData};
+use pallet_balances::{self, AccountData, NegativeImbalance};
 use pallet_insecure_randomness_collective_flip;
 use sp_core::{ConstU128, ConstU32, H256};
 use sp_runtime::{
-	testing::Header,
+	testing::{Header, TestSignature, UintAuthorityId},
+	transaction_validity::TransactionPriority,
 	traits::{BlakeTwo256, IdentityLookup},
+	Permill,
 };
+use std::cell::RefCell;
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
@@ -26,6 +30,7 @@ frame_support::construct_runtime!(
 		KittiesModule: pallet_kitties,
 		Randomness: pallet_insecure_randomness_collective_flip,
 		Balances: pallet_balances,
+		Assets: pallet_assets,
 	}
 );
 
@@ -74,17 +79,84 @@ impl pallet_balances::Config for Test {
 	type WeightInfo = pallet_balances::weights::SubstrateWeight<Test>;
 }
 
+impl pallet_assets::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type Balance = Balance;
+	type AssetId = u32;
+	type AssetIdParameter = u32;
+	type Currency = Balances;
+	type CreateOrigin = AsEnsureOriginWithArg<frame_system::EnsureSigned<u64>>;
+	type ForceOrigin = frame_system::EnsureRoot<u64>;
+	type AssetDeposit = ConstU128<1>;
+	type AssetAccountDeposit = ConstU128<1>;
+	type MetadataDepositBase = ConstU128<1>;
+	type MetadataDepositPerByte = ConstU128<1>;
+	type ApprovalDeposit = ConstU128<1>;
+	type StringLimit = ConstU32<50>;
+	type Freezer = ();
+	type Extra = ();
+	type CallbackHandle = ();
+	type WeightInfo = ();
+	type RemoveItemsLimit = ConstU32<1000>;
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = ();
+}
+
 parameter_types! {
 	pub KittyPalletId: PalletId = PalletId(*b"py/kitty");
 	pub KittyPrice: Balance = EXISTENTIAL_DEPOSIT * 10;
+	// 默认价格不随供应量变化，方便测试
+	pub DefaultPriceParameters: LinearParameters<Balance> =
+		LinearParameters { base: KittyPrice::get(), slope: 0, basis: PriceBasis::Supply };
+	pub const VoucherPriority: TransactionPriority = TransactionPriority::max_value() / 2;
+}
+
+thread_local! {
+	static STAKER_SHARE: RefCell<Permill> = RefCell::new(Permill::one());
+}
+
+/// Share the given part of the era fees with stakers from now on, instead of all of them.
+pub fn set_staker_share(share: Permill) {
+	STAKER_SHARE.with(|staker_share| *staker_share.borrow_mut() = share);
+}
+
+pub struct StakerShare;
+impl Get<Permill> for StakerShare {
+	fn get() -> Permill {
+		STAKER_SHARE.with(|staker_share| *staker_share.borrow())
+	}
+}
+
+/// Stands in for the treasury, receiving the swept kitty revenue.
+pub const TREASURY: u64 = 99;
+
+pub struct RevenueToTreasury;
+impl OnUnbalanced<NegativeImbalance<Test>> for RevenueToTreasury {
+	fn on_nonzero_unbalanced(amount: NegativeImbalance<Test>) {
+		Balances::resolve_creating(&TREASURY, amount);
+	}
 }
 
 impl pallet_kitties::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
 	type Randomness = Randomness;
 	type Currency = Balances;
-	type KittyPrice = KittyPrice;
+	type Assets = Assets;
+	type PriceCurve = LinearPrice;
+	type DefaultPriceParameters = DefaultPriceParameters;
+	type GovernanceOrigin = frame_system::EnsureRoot<u64>;
 	type PalletId = KittyPalletId;
+	type MaxLineageDepth = ConstU32<4>;
+	type MaxLineageSize = ConstU32<16>;
+	type EraLength = ConstU64<10>;
+	type StakingLockPeriod = ConstU64<20>;
+	type RevenueDestination = RevenueToTreasury;
+	type StakerShare = StakerShare;
+	type VoucherPublic = UintAuthorityId;
+	type VoucherSignature = TestSignature;
+	type VoucherPriority = VoucherPriority;
+	type MaxCollectionMetadata = ConstU32<32>;
+	type MaxProvenanceEntries = ConstU32<3>;
 }
 
 impl pallet_insecure_randomness_collective_flip::Config for Test {}
@@ -95,3 +167,16 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
 	ext.execute_with(|| System::set_block_number(1));
 	ext
 }
+
+/// Run `test` in a fresh externality and check the pallet's storage invariants afterwards.
+pub fn build_and_execute(test: impl FnOnce()) {
+	execute_with_try_state(new_test_ext(), test)
+}
+
+/// Run `test` in `ext` and check the pallet's storage invariants afterwards.
+pub fn execute_with_try_state(mut ext: sp_io::TestExternalities, test: impl FnOnce()) {
+	ext.execute_with(|| {
+		test();
+		KittiesModule::do_try_state().expect("storage invariants violated");
+	});
+}
@@ -0,0 +1,92 @@
+//! Mint and breed price curves, see [`crate::Config::PriceCurve`].
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{pallet_prelude::*, Parameter};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{AtLeast32BitUnsigned, Saturating},
+	FixedPointNumber, FixedPointOperand, FixedU128, Permill,
+};
+
+/// What a price is quoted for.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct PriceInput {
+	/// Number of kitties minted so far.
+	pub supply: u32,
+	/// Generation of the new kitty: 0 when minted, one more than its older parent when bred.
+	pub generation: u32,
+}
+
+/// Which part of the [`PriceInput`] a curve grows with.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum PriceBasis {
+	Supply,
+	Generation,
+}
+
+impl PriceInput {
+	fn get(&self, basis: PriceBasis) -> u32 {
+		match basis {
+			PriceBasis::Supply => self.supply,
+			PriceBasis::Generation => self.generation,
+		}
+	}
+}
+
+/// A price curve, with parameters root can change at runtime.
+pub trait PriceCurve<Balance> {
+	type Parameters: Parameter + MaxEncodedLen;
+
+	fn price(parameters: &Self::Parameters, input: PriceInput) -> Balance;
+}
+
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct LinearParameters<Balance> {
+	pub base: Balance,
+	/// Added to the price for every unit of `basis`.
+	pub slope: Balance,
+	pub basis: PriceBasis,
+}
+
+/// `base + slope * x`.
+pub struct LinearPrice;
+
+impl<Balance> PriceCurve<Balance> for LinearPrice
+where
+	Balance: AtLeast32BitUnsigned + Parameter + MaxEncodedLen + Copy,
+{
+	type Parameters = LinearParameters<Balance>;
+
+	fn price(parameters: &Self::Parameters, input: PriceInput) -> Balance {
+		let x: Balance = input.get(parameters.basis).into();
+		parameters.base.saturating_add(parameters.slope.saturating_mul(x))
+	}
+}
+
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct ExponentialParameters<Balance> {
+	pub base: Balance,
+	/// Growth of the price for every unit of `basis`.
+	pub growth: Permill,
+	pub basis: PriceBasis,
+	/// The price never exceeds this.
+	pub max: Balance,
+}
+
+/// `min(base * (1 + growth)^x, max)`.
+pub struct ExponentialPrice;
+
+impl<Balance> PriceCurve<Balance> for ExponentialPrice
+where
+	Balance: AtLeast32BitUnsigned + FixedPointOperand + Parameter + MaxEncodedLen + Copy,
+{
+	type Parameters = ExponentialParameters<Balance>;
+
+	fn price(parameters: &Self::Parameters, input: PriceInput) -> Balance {
+		let x = input.get(parameters.basis);
+		let factor = FixedU128::one()
+			.saturating_add(FixedU128::from(parameters.growth))
+			.saturating_pow(x as usize);
+		factor.saturating_mul_int(parameters.base).min(parameters.max)
+	}
+}
@@ -0,0 +1,123 @@
+//! Deterministic kitty artwork: an SVG image and JSON metadata derived from DNA alone, so every
+//! frontend shows the same kitty. Rendered by the offchain worker into the node's persistent
+//! offchain storage, where the `kitties_svg` / `kitties_metadata` RPCs read it back.
+
+use crate::{Kitty, KittyId};
+use scale_info::prelude::{format, string::String};
+use sp_std::prelude::*;
+
+/// Offchain storage key prefix of a kitty's SVG image.
+pub const SVG_KEY_PREFIX: &[u8] = b"kitties::svg::";
+/// Offchain storage key prefix of a kitty's JSON metadata.
+pub const METADATA_KEY_PREFIX: &[u8] = b"kitties::metadata::";
+/// Offchain storage key prefix of the kitty a kitty's artwork was rendered from.
+pub const RENDERED_KEY_PREFIX: &[u8] = b"kitties::rendered::";
+/// Offchain storage key of the next kitty id the offchain worker has to render, and of the next
+/// rendered kitty it has to check again.
+pub const RENDER_CURSOR_KEY: &[u8] = b"kitties::render-cursor";
+/// Offchain storage key of the lock held while rendering.
+pub const RENDER_LOCK_KEY: &[u8] = b"kitties::render-lock";
+/// How many kitties the offchain worker renders or checks per block.
+pub const MAX_RENDERS_PER_BLOCK: KittyId = 32;
+
+const PATTERNS: [&str; 4] = ["solid", "stripes", "spots", "tuxedo"];
+
+/// Offchain storage key of `kitty_id` under `prefix`.
+pub fn storage_key(prefix: &[u8], kitty_id: KittyId) -> Vec<u8> {
+	[prefix, &kitty_id.to_le_bytes()[..]].concat()
+}
+
+fn color(genes: &[u8]) -> String {
+	format!("#{:02x}{:02x}{:02x}", genes[0], genes[1], genes[2])
+}
+
+fn ear_size(dna: &[u8; 16]) -> u32 {
+	14 + dna[13] as u32 % 12
+}
+
+fn eye_size(dna: &[u8; 16]) -> u32 {
+	5 + dna[14] as u32 % 6
+}
+
+fn pattern(dna: &[u8; 16]) -> &'static str {
+	PATTERNS[dna[9] as usize % PATTERNS.len()]
+}
+
+/// The visual traits encoded in the DNA, in the order they are listed in the metadata.
+fn traits(dna: &[u8; 16]) -> [(&'static str, String); 7] {
+	[
+		("body", color(&dna[0..3])),
+		("belly", color(&dna[3..6])),
+		("eyes", color(&dna[6..9])),
+		("pattern", String::from(pattern(dna))),
+		("pattern_color", color(&dna[10..13])),
+		("ear_size", format!("{}", ear_size(dna))),
+		("eye_size", format!("{}", eye_size(dna))),
+	]
+}
+
+/// Only keep characters that are safe inside a JSON string without escaping.
+fn sanitize_name(name: &[u8; 8]) -> String {
+	name.iter()
+		.filter(|c| c.is_ascii_alphanumeric() || matches!(c, b' ' | b'-' | b'_' | b'.'))
+		.map(|c| *c as char)
+		.collect()
+}
+
+/// Render the SVG image of a kitty.
+pub fn svg(dna: &[u8; 16]) -> String {
+	let body = color(&dna[0..3]);
+	let pattern_color = color(&dna[10..13]);
+	let markings = match pattern(dna) {
+		"stripes" => format!(
+			"<path d=\"M70 70h60M64 90h72M64 110h72\" stroke=\"{}\" stroke-width=\"6\"/>",
+			pattern_color
+		),
+		"spots" => format!(
+			"<g fill=\"{0}\"><circle cx=\"78\" cy=\"70\" r=\"7\"/>\
+			 <circle cx=\"128\" cy=\"110\" r=\"9\"/><circle cx=\"70\" cy=\"125\" r=\"6\"/></g>",
+			pattern_color
+		),
+		"tuxedo" => format!("<path d=\"M80 120 L100 168 L120 120 Z\" fill=\"{}\"/>", pattern_color),
+		_ => String::new(),
+	};
+
+	format!(
+		"<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 200 200\">\
+		 <polygon points=\"55,{ear_top} 60,65 90,45\" fill=\"{body}\"/>\
+		 <polygon points=\"145,{ear_top} 140,65 110,45\" fill=\"{body}\"/>\
+		 <ellipse cx=\"100\" cy=\"105\" rx=\"60\" ry=\"65\" fill=\"{body}\"/>\
+		 <ellipse cx=\"100\" cy=\"135\" rx=\"32\" ry=\"28\" fill=\"{belly}\"/>\
+		 {markings}\
+		 <circle cx=\"78\" cy=\"95\" r=\"{eye_size}\" fill=\"{eyes}\"/>\
+		 <circle cx=\"122\" cy=\"95\" r=\"{eye_size}\" fill=\"{eyes}\"/>\
+		 <path d=\"M92 118 Q100 126 108 118\" stroke=\"#000\" fill=\"none\" stroke-width=\"2\"/>\
+		 </svg>",
+		ear_top = 45 - ear_size(dna),
+		body = body,
+		belly = color(&dna[3..6]),
+		markings = markings,
+		eye_size = eye_size(dna),
+		eyes = color(&dna[6..9]),
+	)
+}
+
+/// Render the JSON metadata of a kitty, in the common `name` / `attributes` layout.
+pub fn metadata(kitty_id: KittyId, kitty: &Kitty) -> String {
+	let dna = kitty.dna.iter().map(|gene| format!("{:02x}", gene)).collect::<String>();
+	let attributes = traits(&kitty.dna)
+		.iter()
+		.map(|(trait_type, value)| {
+			format!("{{\"trait_type\":\"{}\",\"value\":\"{}\"}}", trait_type, value)
+		})
+		.collect::<Vec<_>>()
+		.join(",");
+
+	format!(
+		"{{\"id\":{},\"name\":\"{}\",\"dna\":\"0x{}\",\"attributes\":[{}]}}",
+		kitty_id,
+		sanitize_name(&kitty.name),
+		dna,
+		attributes
+	)
+}
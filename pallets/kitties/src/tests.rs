@@ -1,7 +1,25 @@
-use crate::{mock::*, Error, Event};
-use frame_support::{assert_noop, assert_ok};
+use crate::{
+	mock::*,
+	pricing::{
+		ExponentialParameters, ExponentialPrice, LinearParameters, LinearPrice, PriceBasis,
+		PriceCurve, PriceInput,
+	},
+	Error, Event,
+};
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{fungibles::Mutate, GenesisBuild, GetStorageVersion, Hooks, StorageVersion},
+	unsigned::ValidateUnsigned,
+};
 use once_cell::sync::Lazy;
-use sp_runtime::traits::AccountIdConversion;
+use proptest::prelude::*;
+use sp_core::offchain::{testing::TestOffchainExt, OffchainDbExt, OffchainWorkerExt, StorageKind};
+use sp_runtime::{
+	testing::TestSignature,
+	traits::AccountIdConversion,
+	transaction_validity::{InvalidTransaction, TransactionSource},
+	Permill,
+};
 
 const KITTY_ID: u32 = 0;
 const KITTY_NAME: [u8; 8] = *b"test0000";
@@ -14,11 +32,12 @@ static PALLET_ACCOUNT_ID: Lazy<u64> = Lazy::new(|| {
 	KittyPalletId::get().into_account_truncating()
 });
 const PALLET_BALANCE: u128 = 0;
+const SALE_PRICE: u128 = EXISTENTIAL_DEPOSIT * 10;
 
 
 #[test]
 fn it_works_for_create() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
 
 		// 成功创建一个 kitty 的情况
@@ -48,14 +67,14 @@ fn it_works_for_create() {
 		crate::NextKittyId::<Test>::set(crate::KittyId::max_value());
 		assert_noop!(
 			KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME),
-			Error::<Test>::InvalidKittyId
+			Error::<Test>::KittyIdOverflow
 		);
 	});
 }
 
 #[test]
 fn it_works_for_breed() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
 
 		// 当两个 kitty_id 相同时, breed 失败
@@ -122,7 +141,7 @@ fn it_works_for_breed() {
 
 #[test]
 fn it_works_for_transfer() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		// 账号充值
 		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
 
@@ -140,22 +159,85 @@ fn it_works_for_transfer() {
 			Event::KittyTransferred { who: ACCOUNT_ID, recipient: ACCOUNT_ID2, kitty_id: 0 }.into(),
 		);
 
-		assert_ok!(KittiesModule::transfer(RuntimeOrigin::signed(ACCOUNT_ID2), ACCOUNT_ID2, KITTY_ID));
-		System::assert_last_event(
-			Event::KittyTransferred { who: ACCOUNT_ID2, recipient: ACCOUNT_ID2, kitty_id: 0 }.into(),
+		// 不能转给自己
+		assert_noop!(
+			KittiesModule::transfer(RuntimeOrigin::signed(ACCOUNT_ID2), ACCOUNT_ID2, KITTY_ID),
+			Error::<Test>::TransferToSelf
+		);
+
+		// 不存在的 kitty
+		assert_noop!(
+			KittiesModule::transfer(RuntimeOrigin::signed(ACCOUNT_ID2), ACCOUNT_ID, KITTY_ID + 1),
+			Error::<Test>::InvalidKittyId
+		);
+	});
+}
+
+#[test]
+fn transfer_clears_sale() {
+	build_and_execute(|| {
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID2, ACCOUNT_BALANCE2, 0));
+
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+		assert_ok!(KittiesModule::sale(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID, SALE_PRICE));
+		assert_ok!(KittiesModule::transfer(
+			RuntimeOrigin::signed(ACCOUNT_ID),
+			ACCOUNT_ID2,
+			KITTY_ID
+		));
+		assert!(KittiesModule::kitty_on_sale(KITTY_ID).is_none());
+
+		// 旧主人的上架价格不再有效
+		assert_noop!(
+			KittiesModule::buy(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID),
+			Error::<Test>::NotOnSale
 		);
 	});
 }
 
+#[test]
+fn breed_requires_owning_both_parents() {
+	build_and_execute(|| {
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID2, ACCOUNT_BALANCE2, 0));
+
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID2), KITTY_NAME));
+
+		for who in [ACCOUNT_ID, ACCOUNT_ID2] {
+			assert_noop!(
+				KittiesModule::breed(RuntimeOrigin::signed(who), KITTY_ID, KITTY_ID + 1, KITTY_NAME),
+				Error::<Test>::NotOwnerOfParent
+			);
+		}
+	});
+}
+
+#[test]
+fn failed_payment_leaves_storage_untouched() {
+	build_and_execute(|| {
+		// 余额不足以支付铸造价格
+		let balance = EXISTENTIAL_DEPOSIT * 2;
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, balance, 0));
+
+		assert_noop!(
+			KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME),
+			pallet_balances::Error::<Test>::InsufficientBalance
+		);
+		assert_eq!(KittiesModule::next_kitty_id(), KITTY_ID);
+	});
+}
+
 #[test]
 fn it_works_for_sale() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		// 账户充值
 		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
 
 		// 当不存在 kitty 时失败
 		assert_noop!(
-			KittiesModule::sale(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID),
+			KittiesModule::sale(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID, SALE_PRICE),
 			Error::<Test>::InvalidKittyId
 		);
 
@@ -167,18 +249,20 @@ fn it_works_for_sale() {
 		);
 		// 当所有者不正确时失败
 		assert_noop!(
-			KittiesModule::sale(RuntimeOrigin::signed(ACCOUNT_ID2), KITTY_ID),
+			KittiesModule::sale(RuntimeOrigin::signed(ACCOUNT_ID2), KITTY_ID, SALE_PRICE),
 			Error::<Test>::NotOwner
 		);
 
 		// 所有者正确，成功
-		assert_ok!(KittiesModule::sale(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID));
+		assert_ok!(KittiesModule::sale(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID, SALE_PRICE));
 		assert!(KittiesModule::kitty_on_sale(KITTY_ID).is_some());
-		System::assert_last_event(Event::KittyOnSale { who: ACCOUNT_ID, kitty_id: 0 }.into());
+		System::assert_last_event(
+			Event::KittyOnSale { who: ACCOUNT_ID, kitty_id: 0, price: SALE_PRICE }.into(),
+		);
 
 		// 重复 sale, 失败
 		assert_noop!(
-			KittiesModule::sale(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID),
+			KittiesModule::sale(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID, SALE_PRICE),
 			Error::<Test>::AlreadyOnSale
 		);
 	});
@@ -186,7 +270,7 @@ fn it_works_for_sale() {
 
 #[test]
 fn it_works_for_buy() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		// 账户充值
 		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
 		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID2, ACCOUNT_BALANCE2, 0));
@@ -213,7 +297,7 @@ fn it_works_for_buy() {
 		);
 
 		// 上述失败条件不存在时，成功
-		assert_ok!(KittiesModule::sale(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID));
+		assert_ok!(KittiesModule::sale(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID, SALE_PRICE));
 		assert_ok!(KittiesModule::buy(RuntimeOrigin::signed(ACCOUNT_ID2), KITTY_ID));
 		assert!(KittiesModule::kitty_on_sale(KITTY_ID).is_none());
 		assert_eq!(KittiesModule::kitty_owner(KITTY_ID), Some(ACCOUNT_ID2));
@@ -222,3 +306,1270 @@ fn it_works_for_buy() {
 		System::assert_last_event(Event::KittyBought { who: ACCOUNT_ID2, kitty_id: 0 }.into());
 	});
 }
+
+#[test]
+fn genesis_config_works() {
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	crate::GenesisConfig::<Test> {
+		kitties: vec![(ACCOUNT_ID, KITTY_NAME, [1u8; 16]), (ACCOUNT_ID2, *b"test0001", [2u8; 16])],
+		minters: vec![ACCOUNT_ID2],
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	execute_with_try_state(storage.into(), || {
+		// 创世 kitty 按顺序分配 id
+		assert_eq!(KittiesModule::next_kitty_id(), KITTY_ID + 2);
+		assert_eq!(KittiesModule::kitty_owner(KITTY_ID), Some(ACCOUNT_ID));
+		assert_eq!(KittiesModule::kitty_owner(KITTY_ID + 1), Some(ACCOUNT_ID2));
+		assert_eq!(
+			KittiesModule::kitties(KITTY_ID + 1),
+			Some(crate::Kitty { dna: [2u8; 16], name: *b"test0001" })
+		);
+		assert!(crate::AuthorizedMinters::<Test>::contains_key(ACCOUNT_ID2));
+		assert!(!crate::AuthorizedMinters::<Test>::contains_key(ACCOUNT_ID));
+	});
+}
+
+#[test]
+#[should_panic(expected = "duplicate kitty dna in genesis config")]
+fn genesis_config_rejects_duplicate_dna() {
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let _ = crate::GenesisConfig::<Test> {
+		kitties: vec![(ACCOUNT_ID, KITTY_NAME, [1u8; 16]), (ACCOUNT_ID2, KITTY_NAME, [1u8; 16])],
+		minters: vec![],
+	}
+	.assimilate_storage(&mut storage);
+}
+
+#[test]
+fn breed_maintains_children_index() {
+	build_and_execute(|| {
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
+
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+		assert_ok!(KittiesModule::breed(RuntimeOrigin::signed(ACCOUNT_ID), 0, 1, KITTY_NAME));
+		assert_ok!(KittiesModule::breed(RuntimeOrigin::signed(ACCOUNT_ID), 0, 2, KITTY_NAME));
+
+		let children = |id| crate::KittyChildren::<Test>::iter_key_prefix(id).collect::<Vec<_>>();
+		let mut children_of_0 = children(0);
+		children_of_0.sort();
+		assert_eq!(children_of_0, vec![2, 3]);
+		assert_eq!(children(1), vec![2]);
+		assert_eq!(children(2), vec![3]);
+		assert!(children(3).is_empty());
+	});
+}
+
+#[test]
+fn lineage_queries_work() {
+	build_and_execute(|| {
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
+
+		// 0 + 1 => 2, 0 + 2 => 3, 3 + 1 => 4
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+		assert_ok!(KittiesModule::breed(RuntimeOrigin::signed(ACCOUNT_ID), 0, 1, KITTY_NAME));
+		assert_ok!(KittiesModule::breed(RuntimeOrigin::signed(ACCOUNT_ID), 0, 2, KITTY_NAME));
+		assert_ok!(KittiesModule::breed(RuntimeOrigin::signed(ACCOUNT_ID), 3, 1, KITTY_NAME));
+
+		let summary = |relatives: Vec<crate::KittyRelative>| {
+			let mut summary =
+				relatives.into_iter().map(|r| (r.depth, r.kitty_id)).collect::<Vec<_>>();
+			summary.sort();
+			summary
+		};
+
+		// 共同祖先只出现一次
+		assert_eq!(
+			summary(KittiesModule::ancestors(4, 10)),
+			vec![(0, 4), (1, 1), (1, 3), (2, 0), (2, 2)]
+		);
+		assert_eq!(summary(KittiesModule::ancestors(4, 1)), vec![(0, 4), (1, 1), (1, 3)]);
+		assert_eq!(
+			summary(KittiesModule::descendants(0, 10)),
+			vec![(0, 0), (1, 2), (1, 3), (2, 4)]
+		);
+		assert_eq!(KittiesModule::ancestors(4, 0).len(), 1);
+		assert_eq!(KittiesModule::ancestors(4, 0)[0].parents, Some((3, 1)));
+
+		// 不存在的 kitty 返回空
+		assert!(KittiesModule::ancestors(99, 10).is_empty());
+	});
+}
+
+#[test]
+fn lineage_queries_are_bounded() {
+	build_and_execute(|| {
+		assert_ok!(Balances::set_balance(
+			RuntimeOrigin::root(),
+			ACCOUNT_ID,
+			ACCOUNT_BALANCE * 10,
+			0
+		));
+
+		// 每一代都和 0 号繁殖, 形成一条很长的链
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+		for parent in 1..8 {
+			assert_ok!(KittiesModule::breed(
+				RuntimeOrigin::signed(ACCOUNT_ID),
+				0,
+				parent,
+				KITTY_NAME
+			));
+		}
+
+		// MaxLineageDepth = 4
+		let ancestors = KittiesModule::ancestors(8, 100);
+		assert_eq!(ancestors.iter().map(|r| r.depth).max(), Some(4));
+
+		// MaxLineageSize = 16, 0 号有 17 个子代
+		for _ in 0..10 {
+			assert_ok!(KittiesModule::breed(RuntimeOrigin::signed(ACCOUNT_ID), 0, 1, KITTY_NAME));
+		}
+		assert_eq!(KittiesModule::descendants(0, 1).len(), 16);
+	});
+}
+
+#[test]
+fn staked_kitty_is_locked() {
+	build_and_execute(|| {
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+
+		assert_noop!(
+			KittiesModule::stake(RuntimeOrigin::signed(ACCOUNT_ID2), KITTY_ID),
+			Error::<Test>::NotOwner
+		);
+		assert_ok!(KittiesModule::stake(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID));
+		System::assert_last_event(
+			Event::KittyStaked { who: ACCOUNT_ID, kitty_id: KITTY_ID, unlock_at: 21 }.into(),
+		);
+		assert_eq!(KittiesModule::total_staked(), 1);
+
+		// 质押期间不能转移或出售
+		assert_noop!(
+			KittiesModule::stake(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID),
+			Error::<Test>::KittyStaked
+		);
+		assert_noop!(
+			KittiesModule::transfer(RuntimeOrigin::signed(ACCOUNT_ID), ACCOUNT_ID2, KITTY_ID),
+			Error::<Test>::KittyStaked
+		);
+		assert_noop!(
+			KittiesModule::sale(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID, SALE_PRICE),
+			Error::<Test>::KittyStaked
+		);
+
+		// 锁定期未结束
+		System::set_block_number(20);
+		assert_noop!(
+			KittiesModule::unstake(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID),
+			Error::<Test>::StakeLocked
+		);
+
+		System::set_block_number(21);
+		assert_ok!(KittiesModule::unstake(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID));
+		System::assert_last_event(
+			Event::KittyUnstaked { who: ACCOUNT_ID, kitty_id: KITTY_ID }.into(),
+		);
+		assert_eq!(KittiesModule::total_staked(), 0);
+		assert_noop!(
+			KittiesModule::unstake(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID),
+			Error::<Test>::NotStaked
+		);
+		assert_ok!(KittiesModule::transfer(
+			RuntimeOrigin::signed(ACCOUNT_ID),
+			ACCOUNT_ID2,
+			KITTY_ID
+		));
+	});
+}
+
+#[test]
+fn listed_kitty_cannot_be_staked() {
+	build_and_execute(|| {
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+		assert_ok!(KittiesModule::sale(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID, SALE_PRICE));
+
+		assert_noop!(
+			KittiesModule::stake(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID),
+			Error::<Test>::AlreadyOnSale
+		);
+	});
+}
+
+#[test]
+fn staking_rewards_are_shared_per_era() {
+	build_and_execute(|| {
+		let price = EXISTENTIAL_DEPOSIT * 10;
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID2, ACCOUNT_BALANCE2, 0));
+
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID2), KITTY_NAME));
+		assert_ok!(KittiesModule::stake(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID));
+
+		// 不是 era 边界时不分配
+		KittiesModule::on_initialize(5);
+		assert_eq!(KittiesModule::reward_per_kitty(), 0);
+
+		// 第一个 era: 只有 0 号质押, 获得账户里除存在性押金外的全部费用
+		System::set_block_number(10);
+		KittiesModule::on_initialize(10);
+		let first_era = 2 * price - EXISTENTIAL_DEPOSIT;
+		assert_eq!(KittiesModule::pending_rewards(KITTY_ID), first_era);
+		System::assert_last_event(
+			Event::EraRewardsDistributed { reward_per_kitty: first_era, total: first_era }.into(),
+		);
+
+		// 1 号之后加入, 只分享之后的奖励
+		assert_ok!(KittiesModule::stake(RuntimeOrigin::signed(ACCOUNT_ID2), KITTY_ID + 1));
+		assert_eq!(KittiesModule::pending_rewards(KITTY_ID + 1), 0);
+
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID2), KITTY_NAME));
+		System::set_block_number(20);
+		KittiesModule::on_initialize(20);
+		assert_eq!(KittiesModule::pending_rewards(KITTY_ID), first_era + price / 2);
+		assert_eq!(KittiesModule::pending_rewards(KITTY_ID + 1), price / 2);
+		assert_eq!(KittiesModule::unclaimed_rewards(), first_era + price);
+
+		// 没有新的费用时不再分配
+		System::set_block_number(30);
+		KittiesModule::on_initialize(30);
+		assert_eq!(KittiesModule::pending_rewards(KITTY_ID + 1), price / 2);
+
+		assert_ok!(KittiesModule::claim_rewards(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID));
+		System::assert_last_event(
+			Event::RewardsClaimed {
+				who: ACCOUNT_ID,
+				kitty_id: KITTY_ID,
+				amount: first_era + price / 2,
+			}
+			.into(),
+		);
+		assert_eq!(
+			Balances::free_balance(ACCOUNT_ID),
+			ACCOUNT_BALANCE - price + first_era + price / 2
+		);
+		assert_eq!(KittiesModule::pending_rewards(KITTY_ID), 0);
+
+		assert_ok!(KittiesModule::unstake(RuntimeOrigin::signed(ACCOUNT_ID2), KITTY_ID + 1));
+		assert_eq!(Balances::free_balance(ACCOUNT_ID2), ACCOUNT_BALANCE2 - 2 * price + price / 2);
+		assert_eq!(KittiesModule::unclaimed_rewards(), 0);
+		assert_eq!(Balances::free_balance(*PALLET_ACCOUNT_ID), EXISTENTIAL_DEPOSIT);
+		assert_eq!(Balances::free_balance(TREASURY), 0);
+	});
+}
+
+#[test]
+fn revenue_not_owed_to_stakers_is_swept() {
+	build_and_execute(|| {
+		let price = EXISTENTIAL_DEPOSIT * 10;
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+
+		// 没有质押时, era 结束把存在性押金之外的费用全部转给国库
+		System::set_block_number(10);
+		KittiesModule::on_initialize(10);
+		let swept = 2 * price - EXISTENTIAL_DEPOSIT;
+		System::assert_last_event(Event::RevenueSwept { amount: swept }.into());
+		assert_eq!(Balances::free_balance(TREASURY), swept);
+		assert_eq!(Balances::free_balance(*PALLET_ACCOUNT_ID), EXISTENTIAL_DEPOSIT);
+
+		// 有质押时先分配奖励, 只转走除不尽的余数, 未领取的奖励留在账户里
+		assert_ok!(KittiesModule::stake(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID));
+		assert_ok!(KittiesModule::stake(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID + 1));
+		assert_ok!(Balances::transfer(RuntimeOrigin::signed(ACCOUNT_ID), *PALLET_ACCOUNT_ID, 101));
+		System::set_block_number(20);
+		KittiesModule::on_initialize(20);
+		assert_eq!(KittiesModule::unclaimed_rewards(), 100);
+		System::assert_last_event(Event::RevenueSwept { amount: 1 }.into());
+		assert_eq!(Balances::free_balance(TREASURY), swept + 1);
+		assert_eq!(Balances::free_balance(*PALLET_ACCOUNT_ID), EXISTENTIAL_DEPOSIT + 100);
+
+		// 没有新收入时不再转账
+		System::set_block_number(30);
+		KittiesModule::on_initialize(30);
+		assert_eq!(Balances::free_balance(TREASURY), swept + 1);
+	});
+}
+
+#[test]
+fn treasury_keeps_its_share_while_kitties_are_staked() {
+	build_and_execute(|| {
+		let price = EXISTENTIAL_DEPOSIT * 10;
+		set_staker_share(Permill::from_percent(40));
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+		assert_ok!(KittiesModule::stake(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID));
+
+		// 质押者只分到 40%, 其余在同一个 era 转给国库
+		System::set_block_number(10);
+		KittiesModule::on_initialize(10);
+		let revenue = 2 * price - EXISTENTIAL_DEPOSIT;
+		let shared = Permill::from_percent(40) * revenue;
+		assert_eq!(KittiesModule::pending_rewards(KITTY_ID), shared);
+		assert_eq!(KittiesModule::unclaimed_rewards(), shared);
+		System::assert_has_event(
+			Event::EraRewardsDistributed { reward_per_kitty: shared, total: shared }.into(),
+		);
+		System::assert_last_event(Event::RevenueSwept { amount: revenue - shared }.into());
+		assert_eq!(Balances::free_balance(TREASURY), revenue - shared);
+		assert_eq!(Balances::free_balance(*PALLET_ACCOUNT_ID), EXISTENTIAL_DEPOSIT + shared);
+
+		assert_ok!(KittiesModule::claim_rewards(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID));
+		assert_eq!(Balances::free_balance(ACCOUNT_ID), ACCOUNT_BALANCE - 2 * price + shared);
+		assert_eq!(Balances::free_balance(*PALLET_ACCOUNT_ID), EXISTENTIAL_DEPOSIT);
+	});
+}
+
+#[derive(Debug, Clone)]
+enum Action {
+	Create(u64),
+	Breed(u64, crate::KittyId, crate::KittyId),
+	Transfer(u64, u64, crate::KittyId),
+	Sale(u64, crate::KittyId),
+	Buy(u64, crate::KittyId),
+}
+
+fn action() -> impl Strategy<Value = Action> {
+	let account = 1u64..=3;
+	let kitty_id = 0u32..8;
+	prop_oneof![
+		account.clone().prop_map(Action::Create),
+		(account.clone(), kitty_id.clone(), kitty_id.clone())
+			.prop_map(|(who, kitty_id_1, kitty_id_2)| Action::Breed(who, kitty_id_1, kitty_id_2)),
+		(account.clone(), account.clone(), kitty_id.clone())
+			.prop_map(|(who, recipient, kitty_id)| Action::Transfer(who, recipient, kitty_id)),
+		(account.clone(), kitty_id.clone()).prop_map(|(who, kitty_id)| Action::Sale(who, kitty_id)),
+		(account, kitty_id).prop_map(|(who, kitty_id)| Action::Buy(who, kitty_id)),
+	]
+}
+
+fn apply(action: &Action) -> sp_runtime::DispatchResult {
+	match *action {
+		Action::Create(who) => KittiesModule::create(RuntimeOrigin::signed(who), KITTY_NAME),
+		Action::Breed(who, kitty_id_1, kitty_id_2) =>
+			KittiesModule::breed(RuntimeOrigin::signed(who), kitty_id_1, kitty_id_2, KITTY_NAME),
+		Action::Transfer(who, recipient, kitty_id) =>
+			KittiesModule::transfer(RuntimeOrigin::signed(who), recipient, kitty_id),
+		Action::Sale(who, kitty_id) =>
+			KittiesModule::sale(RuntimeOrigin::signed(who), kitty_id, SALE_PRICE),
+		Action::Buy(who, kitty_id) => KittiesModule::buy(RuntimeOrigin::signed(who), kitty_id),
+	}
+}
+
+proptest! {
+	#[test]
+	fn kitty_invariants_hold(actions in prop::collection::vec(action(), 1..40)) {
+		build_and_execute(|| {
+			for who in 1..=3 {
+				assert_ok!(Balances::set_balance(RuntimeOrigin::root(), who, ACCOUNT_BALANCE, 0));
+			}
+
+			for action in &actions {
+				let owner_of = |kitty_id| KittiesModule::kitty_owner(kitty_id);
+				let next_kitty_id = KittiesModule::next_kitty_id();
+				let storage_root = frame_support::storage_root(sp_runtime::StateVersion::V1);
+
+				let result = apply(action);
+
+				// 失败的调用不会修改任何存储
+				if result.is_err() {
+					assert_eq!(
+						storage_root,
+						frame_support::storage_root(sp_runtime::StateVersion::V1)
+					);
+				}
+
+				match *action {
+					Action::Create(_) if result.is_ok() => {
+						assert_eq!(KittiesModule::next_kitty_id(), next_kitty_id + 1);
+					},
+					Action::Breed(who, kitty_id_1, kitty_id_2) => {
+						if result.is_ok() {
+							assert_eq!(owner_of(kitty_id_1), Some(who));
+							assert_eq!(owner_of(kitty_id_2), Some(who));
+						} else if kitty_id_1 != kitty_id_2 &&
+							owner_of(kitty_id_1).is_some() &&
+							owner_of(kitty_id_2).is_some() &&
+							(owner_of(kitty_id_1) != Some(who) || owner_of(kitty_id_2) != Some(who))
+						{
+							assert_eq!(result, Err(Error::<Test>::NotOwnerOfParent.into()));
+						}
+					},
+					Action::Transfer(who, recipient, kitty_id) => {
+						if result.is_ok() {
+							assert_eq!(owner_of(kitty_id), Some(recipient));
+							assert!(KittiesModule::kitty_on_sale(kitty_id).is_none());
+						} else if who == recipient && owner_of(kitty_id) == Some(who) {
+							assert_eq!(result, Err(Error::<Test>::TransferToSelf.into()));
+						}
+					},
+					Action::Buy(who, kitty_id) if result.is_ok() => {
+						assert_eq!(owner_of(kitty_id), Some(who));
+						assert!(KittiesModule::kitty_on_sale(kitty_id).is_none());
+					},
+					_ => {},
+				}
+
+				assert_ok!(KittiesModule::do_try_state());
+			}
+		});
+	}
+}
+
+#[test]
+fn try_state_detects_corruption() {
+	build_and_execute(|| {
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+		assert_ok!(KittiesModule::do_try_state());
+
+		crate::KittyOwner::<Test>::remove(KITTY_ID);
+		assert_eq!(KittiesModule::do_try_state(), Err("kitty has no owner"));
+
+		crate::KittyOwner::<Test>::insert(KITTY_ID, ACCOUNT_ID);
+		crate::KittyOnSale::<Test>::insert(KITTY_ID + 1, SALE_PRICE);
+		assert_eq!(KittiesModule::do_try_state(), Err("listed kitty does not exist"));
+
+		crate::KittyOnSale::<Test>::remove(KITTY_ID + 1);
+		crate::NextKittyId::<Test>::set(KITTY_ID);
+		assert_eq!(KittiesModule::do_try_state(), Err("kitty id is not below NextKittyId"));
+
+		crate::NextKittyId::<Test>::set(KITTY_ID + 1);
+	});
+}
+
+/// Like `build_and_execute`, with offchain storage for the offchain worker.
+fn build_and_execute_offchain(test: impl FnOnce()) {
+	let (offchain, _state) = TestOffchainExt::new();
+	let mut ext = new_test_ext();
+	ext.register_extension(OffchainDbExt::new(offchain.clone()));
+	ext.register_extension(OffchainWorkerExt::new(offchain));
+	execute_with_try_state(ext, test)
+}
+
+fn local_storage(prefix: &[u8], kitty_id: u32) -> Option<String> {
+	sp_io::offchain::local_storage_get(
+		StorageKind::PERSISTENT,
+		&crate::render::storage_key(prefix, kitty_id),
+	)
+	.map(|bytes| String::from_utf8(bytes).unwrap())
+}
+
+#[test]
+fn render_is_deterministic() {
+	let dna = [1u8; 16];
+	assert_eq!(crate::render::svg(&dna), crate::render::svg(&dna));
+	assert_ne!(crate::render::svg(&dna), crate::render::svg(&[2u8; 16]));
+	assert!(crate::render::svg(&dna).starts_with("<svg"));
+	assert!(crate::render::svg(&dna).contains("fill=\"#010101\""));
+
+	let kitty = crate::Kitty { dna, name: *b"a\"b<c>\0\0" };
+	assert_eq!(
+		crate::render::metadata(7, &kitty),
+		"{\"id\":7,\"name\":\"abc\",\"dna\":\"0x01010101010101010101010101010101\",\
+		 \"attributes\":[{\"trait_type\":\"body\",\"value\":\"#010101\"},\
+		 {\"trait_type\":\"belly\",\"value\":\"#010101\"},\
+		 {\"trait_type\":\"eyes\",\"value\":\"#010101\"},\
+		 {\"trait_type\":\"pattern\",\"value\":\"stripes\"},\
+		 {\"trait_type\":\"pattern_color\",\"value\":\"#010101\"},\
+		 {\"trait_type\":\"ear_size\",\"value\":\"15\"},\
+		 {\"trait_type\":\"eye_size\",\"value\":\"6\"}]}"
+	);
+}
+
+#[test]
+fn offchain_worker_renders_new_kitties() {
+	build_and_execute_offchain(|| {
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+
+		KittiesModule::offchain_worker(1);
+
+		for kitty_id in [KITTY_ID, KITTY_ID + 1] {
+			let kitty = KittiesModule::kitties(kitty_id).unwrap();
+			assert_eq!(
+				local_storage(crate::render::SVG_KEY_PREFIX, kitty_id),
+				Some(crate::render::svg(&kitty.dna))
+			);
+			assert_eq!(
+				local_storage(crate::render::METADATA_KEY_PREFIX, kitty_id),
+				Some(crate::render::metadata(kitty_id, &kitty))
+			);
+		}
+
+		// 之后创建的 kitty 在下一次运行时渲染
+		assert_ok!(KittiesModule::breed(
+			RuntimeOrigin::signed(ACCOUNT_ID),
+			KITTY_ID,
+			KITTY_ID + 1,
+			KITTY_NAME
+		));
+		assert_eq!(local_storage(crate::render::SVG_KEY_PREFIX, KITTY_ID + 2), None);
+		KittiesModule::offchain_worker(2);
+		assert!(local_storage(crate::render::SVG_KEY_PREFIX, KITTY_ID + 2).is_some());
+	});
+}
+
+#[test]
+fn offchain_worker_renders_in_batches() {
+	build_and_execute_offchain(|| {
+		let batch = crate::render::MAX_RENDERS_PER_BLOCK;
+		let balance = EXISTENTIAL_DEPOSIT * 10 * (batch as u128 + 2);
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, balance, 0));
+		for _ in 0..batch + 1 {
+			assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+		}
+
+		KittiesModule::offchain_worker(1);
+		assert!(local_storage(crate::render::SVG_KEY_PREFIX, batch - 1).is_some());
+		assert_eq!(local_storage(crate::render::SVG_KEY_PREFIX, batch), None);
+
+		KittiesModule::offchain_worker(2);
+		assert!(local_storage(crate::render::SVG_KEY_PREFIX, batch).is_some());
+	});
+}
+
+#[test]
+fn offchain_worker_renders_again_after_reorg() {
+	build_and_execute_offchain(|| {
+		let svg = |gene| Some(crate::render::svg(&[gene; 16]));
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+		crate::Kitties::<Test>::insert(0, crate::Kitty { dna: [1; 16], name: KITTY_NAME });
+		KittiesModule::offchain_worker(1);
+		assert_eq!(local_storage(crate::render::SVG_KEY_PREFIX, 0), svg(1));
+
+		// 另一个分叉上同一个 id 的 kitty 有不同的 dna
+		crate::Kitties::<Test>::insert(0, crate::Kitty { dna: [2; 16], name: KITTY_NAME });
+		KittiesModule::offchain_worker(2);
+		assert_eq!(local_storage(crate::render::SVG_KEY_PREFIX, 0), svg(2));
+
+		// 重组到还没有这个 kitty 的分叉, 之后再回到有它的分叉
+		crate::Kitties::<Test>::remove(0);
+		crate::NextKittyId::<Test>::set(0);
+		KittiesModule::offchain_worker(2);
+		assert_eq!(local_storage(crate::render::SVG_KEY_PREFIX, 0), None);
+		assert_eq!(local_storage(crate::render::METADATA_KEY_PREFIX, 0), None);
+
+		crate::Kitties::<Test>::insert(0, crate::Kitty { dna: [3; 16], name: KITTY_NAME });
+		crate::NextKittyId::<Test>::set(1);
+		KittiesModule::offchain_worker(3);
+		assert_eq!(local_storage(crate::render::SVG_KEY_PREFIX, 0), svg(3));
+	});
+}
+
+#[test]
+fn offchain_worker_waits_for_the_render_lock() {
+	use sp_runtime::offchain::storage_lock::{StorageLock, Time};
+
+	build_and_execute_offchain(|| {
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+
+		let mut lock = StorageLock::<Time>::new(crate::render::RENDER_LOCK_KEY);
+		let guard = lock.try_lock().unwrap();
+		KittiesModule::offchain_worker(1);
+		assert_eq!(local_storage(crate::render::SVG_KEY_PREFIX, 0), None);
+
+		drop(guard);
+		KittiesModule::offchain_worker(1);
+		assert!(local_storage(crate::render::SVG_KEY_PREFIX, 0).is_some());
+	});
+}
+
+const MINTER: u64 = 3;
+
+fn signed_voucher(nonce: u32, expires_at: u64) -> (crate::KittyVoucher<u64, u64>, TestSignature) {
+	let voucher = crate::KittyVoucher {
+		minter: MINTER,
+		recipient: ACCOUNT_ID,
+		name: KITTY_NAME,
+		nonce,
+		expires_at,
+	};
+	let signature = TestSignature(MINTER, voucher.signing_payload());
+	(voucher, signature)
+}
+
+#[test]
+fn claim_voucher_mints_for_free() {
+	build_and_execute(|| {
+		assert_ok!(KittiesModule::set_minter(RuntimeOrigin::root(), MINTER, true));
+		System::assert_last_event(Event::MinterUpdated { minter: MINTER, authorized: true }.into());
+
+		// 领取者没有任何余额
+		let (voucher, signature) = signed_voucher(0, 10);
+		assert_ok!(KittiesModule::claim_voucher(
+			RuntimeOrigin::none(),
+			voucher.clone(),
+			signature.clone()
+		));
+		assert_eq!(KittiesModule::kitty_owner(KITTY_ID), Some(ACCOUNT_ID));
+		assert_eq!(KittiesModule::kitties(KITTY_ID).unwrap().name, KITTY_NAME);
+		assert_eq!(Balances::free_balance(ACCOUNT_ID), 0);
+		System::assert_last_event(
+			Event::VoucherClaimed { minter: MINTER, recipient: ACCOUNT_ID, kitty_id: KITTY_ID }
+				.into(),
+		);
+
+		// 同一张券不能重复领取，不同 nonce 的券可以
+		assert_noop!(
+			KittiesModule::claim_voucher(RuntimeOrigin::none(), voucher, signature),
+			Error::<Test>::VoucherAlreadyUsed
+		);
+		let (voucher, signature) = signed_voucher(1, 10);
+		assert_ok!(KittiesModule::claim_voucher(RuntimeOrigin::none(), voucher, signature));
+		assert_eq!(KittiesModule::kitty_owner(KITTY_ID + 1), Some(ACCOUNT_ID));
+	});
+}
+
+#[test]
+fn claim_voucher_checks_voucher() {
+	build_and_execute(|| {
+		let (voucher, signature) = signed_voucher(0, 10);
+		assert_noop!(
+			KittiesModule::set_minter(RuntimeOrigin::signed(MINTER), MINTER, true),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			KittiesModule::claim_voucher(RuntimeOrigin::none(), voucher.clone(), signature.clone()),
+			Error::<Test>::UnauthorizedMinter
+		);
+
+		assert_ok!(KittiesModule::set_minter(RuntimeOrigin::root(), MINTER, true));
+		assert_noop!(
+			KittiesModule::claim_voucher(
+				RuntimeOrigin::signed(ACCOUNT_ID),
+				voucher.clone(),
+				signature.clone()
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+
+		// 券的内容被篡改后签名失效
+		let mut forged = voucher.clone();
+		forged.recipient = ACCOUNT_ID2;
+		assert_noop!(
+			KittiesModule::claim_voucher(RuntimeOrigin::none(), forged, signature.clone()),
+			Error::<Test>::InvalidVoucherSignature
+		);
+		let other_signer = TestSignature(ACCOUNT_ID2, voucher.signing_payload());
+		assert_noop!(
+			KittiesModule::claim_voucher(RuntimeOrigin::none(), voucher.clone(), other_signer),
+			Error::<Test>::InvalidVoucherSignature
+		);
+
+		System::set_block_number(11);
+		assert_noop!(
+			KittiesModule::claim_voucher(RuntimeOrigin::none(), voucher.clone(), signature.clone()),
+			Error::<Test>::VoucherExpired
+		);
+
+		System::set_block_number(1);
+		assert_ok!(KittiesModule::set_minter(RuntimeOrigin::root(), MINTER, false));
+		assert_noop!(
+			KittiesModule::claim_voucher(RuntimeOrigin::none(), voucher, signature),
+			Error::<Test>::UnauthorizedMinter
+		);
+	});
+}
+
+#[test]
+fn validate_unsigned_checks_voucher() {
+	build_and_execute(|| {
+		assert_ok!(KittiesModule::set_minter(RuntimeOrigin::root(), MINTER, true));
+		let validate = |voucher: &crate::KittyVoucher<u64, u64>, signature: &TestSignature| {
+			let call = crate::Call::claim_voucher {
+				voucher: voucher.clone(),
+				signature: signature.clone(),
+			};
+			KittiesModule::validate_unsigned(TransactionSource::External, &call)
+		};
+
+		let (voucher, signature) = signed_voucher(0, 10);
+		let valid = validate(&voucher, &signature).unwrap();
+		assert_eq!(valid.priority, VoucherPriority::get());
+		assert_eq!(valid.longevity, 9);
+		assert_eq!(valid.provides.len(), 1);
+
+		let bad_signature = TestSignature(MINTER, b"something else".to_vec());
+		assert_eq!(validate(&voucher, &bad_signature), InvalidTransaction::BadProof.into());
+		let (unknown_minter, _) = signed_voucher(0, 10);
+		let unknown_minter = crate::KittyVoucher { minter: ACCOUNT_ID2, ..unknown_minter };
+		assert_eq!(validate(&unknown_minter, &signature), InvalidTransaction::BadSigner.into());
+
+		// 已领取或过期的券从交易池中移除
+		assert_ok!(KittiesModule::claim_voucher(
+			RuntimeOrigin::none(),
+			voucher.clone(),
+			signature.clone()
+		));
+		assert_eq!(validate(&voucher, &signature), InvalidTransaction::Stale.into());
+		let (voucher, signature) = signed_voucher(1, 10);
+		System::set_block_number(11);
+		assert_eq!(validate(&voucher, &signature), InvalidTransaction::Stale.into());
+
+		let other_call = crate::Call::<Test>::create { name: KITTY_NAME };
+		assert_eq!(
+			KittiesModule::validate_unsigned(TransactionSource::External, &other_call),
+			InvalidTransaction::Call.into()
+		);
+	});
+}
+
+#[test]
+fn claim_voucher_is_weighed() {
+	use frame_support::dispatch::GetDispatchInfo;
+
+	let (voucher, signature) = signed_voucher(0, 10);
+	let call = crate::Call::<Test>::claim_voucher { voucher, signature };
+	let weight = call.get_dispatch_info().weight;
+	assert_eq!(weight, KittiesModule::claim_voucher_weight());
+	let execution = crate::VOUCHER_SIGNATURE_WEIGHT + crate::VOUCHER_EXECUTION_WEIGHT;
+	assert!(weight.ref_time() > execution.ref_time());
+}
+
+#[test]
+fn expired_used_vouchers_are_pruned() {
+	build_and_execute(|| {
+		assert_ok!(KittiesModule::set_minter(RuntimeOrigin::root(), MINTER, true));
+		for (nonce, expires_at) in [(0, 10), (1, 20)] {
+			let (voucher, signature) = signed_voucher(nonce, expires_at);
+			assert_ok!(KittiesModule::claim_voucher(RuntimeOrigin::none(), voucher, signature));
+		}
+		let used = || crate::UsedVouchers::<Test>::iter_keys().count();
+
+		// 到期的那个区块里还能被领取, 不能删除
+		System::set_block_number(10);
+		KittiesModule::on_idle(10, frame_support::weights::Weight::MAX);
+		assert_eq!(used(), 2);
+
+		System::set_block_number(11);
+		KittiesModule::on_idle(11, frame_support::weights::Weight::MAX);
+		assert_eq!(used(), 1);
+		assert_eq!(crate::VoucherPruneCursor::<Test>::get(), 11);
+		let (voucher, _) = signed_voucher(1, 20);
+		assert!(crate::UsedVouchers::<Test>::contains_key(20, voucher.hash()));
+	});
+}
+
+const CREATOR: u64 = 4;
+
+fn metadata(bytes: &[u8]) -> frame_support::BoundedVec<u8, sp_core::ConstU32<32>> {
+	bytes.to_vec().try_into().unwrap()
+}
+
+#[test]
+fn create_puts_kitties_into_default_collection() {
+	build_and_execute(|| {
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+
+		assert_eq!(KittiesModule::kitty_collection(KITTY_ID), Some((crate::DEFAULT_COLLECTION, 0)));
+		assert_eq!(
+			KittiesModule::kitty_collection(KITTY_ID + 1),
+			Some((crate::DEFAULT_COLLECTION, 1))
+		);
+		let collection = KittiesModule::collections(crate::DEFAULT_COLLECTION).unwrap();
+		assert_eq!(collection.minted, 2);
+		assert_eq!(collection.creator, *PALLET_ACCOUNT_ID);
+	});
+}
+
+#[test]
+fn collections_have_their_own_numbering_and_price() {
+	build_and_execute(|| {
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+
+		assert_noop!(
+			KittiesModule::create_collection(
+				RuntimeOrigin::signed(CREATOR),
+				metadata(b""),
+				Some(0)
+			),
+			Error::<Test>::InvalidMaxSupply
+		);
+		assert_ok!(KittiesModule::create_collection(
+			RuntimeOrigin::signed(CREATOR),
+			metadata(b"series 1"),
+			Some(2)
+		));
+		let collection_id = 1;
+		assert_eq!(KittiesModule::next_collection_id(), collection_id + 1);
+		System::assert_last_event(
+			Event::CollectionCreated { creator: CREATOR, collection_id, max_supply: Some(2) }
+				.into(),
+		);
+
+		// 只有创建者可以设置价格
+		assert_noop!(
+			KittiesModule::set_collection_price(
+				RuntimeOrigin::signed(ACCOUNT_ID),
+				collection_id,
+				Some(EXISTENTIAL_DEPOSIT)
+			),
+			Error::<Test>::NotCollectionCreator
+		);
+		assert_ok!(KittiesModule::set_collection_price(
+			RuntimeOrigin::signed(CREATOR),
+			collection_id,
+			Some(EXISTENTIAL_DEPOSIT)
+		));
+
+		let balance = Balances::free_balance(ACCOUNT_ID);
+		assert_ok!(KittiesModule::mint(
+			RuntimeOrigin::signed(ACCOUNT_ID),
+			collection_id,
+			KITTY_NAME
+		));
+		assert_eq!(Balances::free_balance(ACCOUNT_ID), balance - EXISTENTIAL_DEPOSIT);
+		System::assert_last_event(
+			Event::KittyMinted { who: ACCOUNT_ID, collection_id, serial: 0, kitty_id: KITTY_ID + 1 }
+				.into(),
+		);
+		assert_ok!(KittiesModule::mint(
+			RuntimeOrigin::signed(ACCOUNT_ID),
+			collection_id,
+			KITTY_NAME
+		));
+		assert_eq!(KittiesModule::kitty_collection(KITTY_ID + 2), Some((collection_id, 1)));
+		assert_eq!(crate::CollectionKitties::<Test>::get(collection_id, 1), Some(KITTY_ID + 2));
+
+		assert_noop!(
+			KittiesModule::mint(RuntimeOrigin::signed(ACCOUNT_ID), collection_id, KITTY_NAME),
+			Error::<Test>::CollectionSoldOut
+		);
+		assert_noop!(
+			KittiesModule::mint(RuntimeOrigin::signed(ACCOUNT_ID), collection_id + 1, KITTY_NAME),
+			Error::<Test>::UnknownCollection
+		);
+	});
+}
+
+#[test]
+fn frozen_collections_are_locked() {
+	build_and_execute(|| {
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
+		assert_ok!(KittiesModule::create_collection(
+			RuntimeOrigin::signed(CREATOR),
+			metadata(b""),
+			None
+		));
+		assert_ok!(KittiesModule::mint(RuntimeOrigin::signed(ACCOUNT_ID), 1, KITTY_NAME));
+
+		assert_noop!(
+			KittiesModule::freeze_collection(RuntimeOrigin::signed(ACCOUNT_ID), 1),
+			Error::<Test>::NotCollectionCreator
+		);
+		assert_ok!(KittiesModule::freeze_collection(RuntimeOrigin::signed(CREATOR), 1));
+		System::assert_last_event(Event::CollectionFrozen { collection_id: 1 }.into());
+
+		assert_noop!(
+			KittiesModule::mint(RuntimeOrigin::signed(ACCOUNT_ID), 1, KITTY_NAME),
+			Error::<Test>::CollectionFrozen
+		);
+		assert_noop!(
+			KittiesModule::transfer(RuntimeOrigin::signed(ACCOUNT_ID), ACCOUNT_ID2, KITTY_ID),
+			Error::<Test>::CollectionFrozen
+		);
+		assert_noop!(
+			KittiesModule::sale(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID, SALE_PRICE),
+			Error::<Test>::CollectionFrozen
+		);
+
+		// root 也可以解冻
+		assert_ok!(KittiesModule::thaw_collection(RuntimeOrigin::root(), 1));
+		System::assert_last_event(Event::CollectionThawed { collection_id: 1 }.into());
+		assert_ok!(KittiesModule::transfer(
+			RuntimeOrigin::signed(ACCOUNT_ID),
+			ACCOUNT_ID2,
+			KITTY_ID
+		));
+	});
+}
+
+#[test]
+fn migration_v4_assigns_default_collection() {
+	build_and_execute(|| {
+		for kitty_id in [0, 2, 3] {
+			let kitty = crate::Kitty { dna: [kitty_id as u8; 16], name: KITTY_NAME };
+			crate::Kitties::<Test>::insert(kitty_id, kitty);
+			crate::KittyOwner::<Test>::insert(kitty_id, ACCOUNT_ID);
+		}
+		crate::NextKittyId::<Test>::set(4);
+		StorageVersion::new(3).put::<KittiesModule>();
+
+		crate::migrations::v4::migrate::<Test>();
+
+		assert_eq!(KittiesModule::on_chain_storage_version(), 4);
+		assert_eq!(KittiesModule::kitty_collection(0), Some((crate::DEFAULT_COLLECTION, 0)));
+		assert_eq!(KittiesModule::kitty_collection(2), Some((crate::DEFAULT_COLLECTION, 1)));
+		assert_eq!(KittiesModule::kitty_collection(3), Some((crate::DEFAULT_COLLECTION, 2)));
+		assert_eq!(KittiesModule::collections(crate::DEFAULT_COLLECTION).unwrap().minted, 3);
+
+		// 新的 kitty 接着编号
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+		assert_eq!(KittiesModule::kitty_collection(4), Some((crate::DEFAULT_COLLECTION, 3)));
+	});
+}
+
+fn linear(base: u128, slope: u128, basis: PriceBasis) -> LinearParameters<u128> {
+	LinearParameters { base, slope, basis }
+}
+
+#[test]
+fn linear_price_grows_with_its_basis() {
+	let parameters = linear(100, 10, PriceBasis::Supply);
+	assert_eq!(LinearPrice::price(&parameters, PriceInput { supply: 0, generation: 5 }), 100);
+	assert_eq!(LinearPrice::price(&parameters, PriceInput { supply: 3, generation: 5 }), 130);
+
+	let parameters = linear(100, 10, PriceBasis::Generation);
+	assert_eq!(LinearPrice::price(&parameters, PriceInput { supply: 3, generation: 5 }), 150);
+
+	// 溢出时取最大值
+	let parameters = linear(u128::MAX - 1, 10, PriceBasis::Supply);
+	assert_eq!(LinearPrice::price(&parameters, PriceInput { supply: 1, generation: 0 }), u128::MAX);
+}
+
+#[test]
+fn exponential_price_grows_with_its_basis_up_to_max() {
+	let parameters = ExponentialParameters {
+		base: 1_000u128,
+		growth: Permill::from_percent(10),
+		basis: PriceBasis::Generation,
+		max: 2_000,
+	};
+	let price = |generation| {
+		ExponentialPrice::price(&parameters, PriceInput { supply: 100, generation })
+	};
+	assert_eq!(price(0), 1_000);
+	assert_eq!(price(1), 1_100);
+	assert_eq!(price(2), 1_210);
+	assert_eq!(price(8), 2_000);
+	assert_eq!(price(u32::MAX), 2_000);
+}
+
+#[test]
+fn set_price_parameters_is_root_only() {
+	build_and_execute(|| {
+		let parameters = linear(EXISTENTIAL_DEPOSIT * 2, EXISTENTIAL_DEPOSIT, PriceBasis::Supply);
+		assert_noop!(
+			KittiesModule::set_price_parameters(
+				RuntimeOrigin::signed(ACCOUNT_ID),
+				parameters.clone()
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+
+		assert_ok!(KittiesModule::set_price_parameters(RuntimeOrigin::root(), parameters.clone()));
+		assert_eq!(KittiesModule::price_parameters(), parameters);
+		System::assert_last_event(Event::PriceParametersSet { parameters }.into());
+	});
+}
+
+#[test]
+fn mint_price_follows_supply() {
+	build_and_execute(|| {
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
+		let parameters = linear(EXISTENTIAL_DEPOSIT * 2, EXISTENTIAL_DEPOSIT, PriceBasis::Supply);
+		assert_ok!(KittiesModule::set_price_parameters(RuntimeOrigin::root(), parameters));
+
+		let mut paid = 0;
+		for supply in 0..3 {
+			let price = EXISTENTIAL_DEPOSIT * (2 + supply);
+			assert_eq!(KittiesModule::quote_mint_price(crate::DEFAULT_COLLECTION), Some(price));
+			assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+			paid += price;
+			assert_eq!(Balances::free_balance(ACCOUNT_ID), ACCOUNT_BALANCE - paid);
+		}
+
+		// 集合自己的价格优先
+		assert_ok!(KittiesModule::create_collection(
+			RuntimeOrigin::signed(CREATOR),
+			metadata(b"fixed"),
+			None
+		));
+		assert_ok!(KittiesModule::set_collection_price(
+			RuntimeOrigin::signed(CREATOR),
+			1,
+			Some(EXISTENTIAL_DEPOSIT)
+		));
+		assert_eq!(KittiesModule::quote_mint_price(1), Some(EXISTENTIAL_DEPOSIT));
+		assert_eq!(KittiesModule::quote_mint_price(2), None);
+	});
+}
+
+#[test]
+fn breed_price_follows_generation() {
+	build_and_execute(|| {
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+		let parameters = linear(EXISTENTIAL_DEPOSIT, EXISTENTIAL_DEPOSIT, PriceBasis::Generation);
+		assert_ok!(KittiesModule::set_price_parameters(RuntimeOrigin::root(), parameters));
+
+		// 第一代
+		assert_eq!(KittiesModule::quote_breed_price(0, 1), Some(EXISTENTIAL_DEPOSIT * 2));
+		let balance = Balances::free_balance(ACCOUNT_ID);
+		assert_ok!(KittiesModule::breed(RuntimeOrigin::signed(ACCOUNT_ID), 0, 1, KITTY_NAME));
+		assert_eq!(KittiesModule::kitty_generation(2), 1);
+		assert_eq!(Balances::free_balance(ACCOUNT_ID), balance - EXISTENTIAL_DEPOSIT * 2);
+
+		// 第二代, 按年长的父母计算
+		assert_eq!(KittiesModule::quote_breed_price(0, 2), Some(EXISTENTIAL_DEPOSIT * 3));
+		let balance = Balances::free_balance(ACCOUNT_ID);
+		assert_ok!(KittiesModule::breed(RuntimeOrigin::signed(ACCOUNT_ID), 0, 2, KITTY_NAME));
+		assert_eq!(KittiesModule::kitty_generation(3), 2);
+		assert_eq!(Balances::free_balance(ACCOUNT_ID), balance - EXISTENTIAL_DEPOSIT * 3);
+
+		assert_eq!(KittiesModule::quote_breed_price(0, 9), None);
+	});
+}
+
+#[test]
+fn buy_pays_the_listed_price() {
+	build_and_execute(|| {
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID2, ACCOUNT_BALANCE2, 0));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+		let balance = Balances::free_balance(ACCOUNT_ID);
+
+		let price = EXISTENTIAL_DEPOSIT * 123;
+		assert_ok!(KittiesModule::sale(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID, price));
+		assert_eq!(KittiesModule::kitty_on_sale(KITTY_ID), Some(price));
+		assert_ok!(KittiesModule::buy(RuntimeOrigin::signed(ACCOUNT_ID2), KITTY_ID));
+
+		assert_eq!(Balances::free_balance(ACCOUNT_ID), balance + price);
+		assert_eq!(Balances::free_balance(ACCOUNT_ID2), ACCOUNT_BALANCE2 - price);
+	});
+}
+
+#[test]
+fn migration_v5_prices_listings_and_computes_generations() {
+	build_and_execute(|| {
+		for kitty_id in 0..4 {
+			let kitty = crate::Kitty { dna: [kitty_id as u8; 16], name: KITTY_NAME };
+			crate::Kitties::<Test>::insert(kitty_id, kitty);
+			crate::KittyOwner::<Test>::insert(kitty_id, ACCOUNT_ID);
+		}
+		crate::NextKittyId::<Test>::set(4);
+		crate::KittyParents::<Test>::insert(2, (0, 1));
+		crate::KittyParents::<Test>::insert(3, (2, 0));
+		crate::KittyChildren::<Test>::insert(0, 2, ());
+		crate::KittyChildren::<Test>::insert(1, 2, ());
+		crate::KittyChildren::<Test>::insert(2, 3, ());
+		crate::KittyChildren::<Test>::insert(0, 3, ());
+		frame_support::storage::unhashed::put(
+			&crate::KittyOnSale::<Test>::hashed_key_for(1),
+			&(),
+		);
+		// v4 之前的 kitty 没有系列, 先迁移到 v4
+		StorageVersion::new(3).put::<KittiesModule>();
+		crate::migrations::v4::migrate::<Test>();
+
+		crate::migrations::v5::migrate::<Test>();
+
+		assert_eq!(KittiesModule::on_chain_storage_version(), 5);
+		assert_eq!(KittiesModule::kitty_on_sale(1), Some(EXISTENTIAL_DEPOSIT * 10));
+		assert_eq!(KittiesModule::kitty_generation(1), 0);
+		assert_eq!(KittiesModule::kitty_generation(2), 1);
+		assert_eq!(KittiesModule::kitty_generation(3), 2);
+	});
+}
+
+#[test]
+fn transfer_and_buy_record_provenance() {
+	build_and_execute(|| {
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID2, ACCOUNT_BALANCE2, 0));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+		assert!(KittiesModule::provenance(KITTY_ID).is_empty());
+
+		System::set_block_number(5);
+		assert_ok!(KittiesModule::transfer(RuntimeOrigin::signed(ACCOUNT_ID), ACCOUNT_ID2, KITTY_ID));
+		System::set_block_number(7);
+		assert_ok!(KittiesModule::sale(RuntimeOrigin::signed(ACCOUNT_ID2), KITTY_ID, SALE_PRICE));
+		assert_ok!(KittiesModule::buy(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID));
+
+		assert_eq!(
+			KittiesModule::provenance(KITTY_ID),
+			vec![
+				crate::ProvenanceRecord {
+					owner: ACCOUNT_ID,
+					block: 5,
+					price: None,
+					asset_id: None,
+				},
+				crate::ProvenanceRecord {
+					owner: ACCOUNT_ID2,
+					block: 7,
+					price: Some(SALE_PRICE),
+					asset_id: None,
+				},
+			]
+		);
+	});
+}
+
+#[test]
+fn on_idle_prunes_old_provenance() {
+	build_and_execute(|| {
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+
+		// MaxProvenanceEntries 为 3, 转移 5 次
+		let owners = [ACCOUNT_ID, ACCOUNT_ID2];
+		for block in 0..5 {
+			System::set_block_number(block + 1);
+			let (from, to) = (owners[block as usize % 2], owners[(block as usize + 1) % 2]);
+			assert_ok!(KittiesModule::transfer(RuntimeOrigin::signed(from), to, KITTY_ID));
+		}
+		assert_eq!(crate::ProvenanceRange::<Test>::get(KITTY_ID), (0, 5));
+		assert!(crate::ProvenanceToPrune::<Test>::contains_key(KITTY_ID));
+		// 查询只返回最新的 3 条
+		let blocks: Vec<u64> =
+			KittiesModule::provenance(KITTY_ID).iter().map(|record| record.block).collect();
+		assert_eq!(blocks, vec![3, 4, 5]);
+
+		KittiesModule::on_idle(5, frame_support::weights::Weight::MAX);
+
+		assert_eq!(crate::ProvenanceRange::<Test>::get(KITTY_ID), (2, 5));
+		assert!(!crate::ProvenanceToPrune::<Test>::contains_key(KITTY_ID));
+		assert_eq!(crate::Provenance::<Test>::iter_prefix(KITTY_ID).count(), 3);
+		assert_eq!(KittiesModule::provenance(KITTY_ID).len(), 3);
+	});
+}
+
+const ASSET_ID: u32 = 7;
+const ASSET_BALANCE: u128 = 1_000;
+
+fn create_asset() {
+	assert_ok!(Assets::force_create(RuntimeOrigin::root(), ASSET_ID, CREATOR, true, 1));
+	assert_ok!(<Assets as Mutate<u64>>::mint_into(ASSET_ID, &ACCOUNT_ID2, ASSET_BALANCE));
+}
+
+#[test]
+fn sale_for_asset_settles_in_the_asset() {
+	build_and_execute(|| {
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID2, ACCOUNT_BALANCE2, 0));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+		create_asset();
+
+		assert_ok!(KittiesModule::sale_for_asset(
+			RuntimeOrigin::signed(ACCOUNT_ID),
+			KITTY_ID,
+			ASSET_ID,
+			300
+		));
+		assert_eq!(KittiesModule::kitty_on_sale(KITTY_ID), Some(300));
+		assert_eq!(KittiesModule::kitty_sale_asset(KITTY_ID), Some(ASSET_ID));
+		System::assert_last_event(
+			Event::KittyOnSaleForAsset {
+				who: ACCOUNT_ID,
+				kitty_id: KITTY_ID,
+				asset_id: ASSET_ID,
+				price: 300,
+			}
+			.into(),
+		);
+
+		let native_balance = Balances::free_balance(ACCOUNT_ID);
+		System::set_block_number(3);
+		assert_ok!(KittiesModule::buy(RuntimeOrigin::signed(ACCOUNT_ID2), KITTY_ID));
+
+		assert_eq!(KittiesModule::kitty_owner(KITTY_ID), Some(ACCOUNT_ID2));
+		assert_eq!(KittiesModule::kitty_sale_asset(KITTY_ID), None);
+		assert_eq!(Assets::balance(ASSET_ID, ACCOUNT_ID), 300);
+		assert_eq!(Assets::balance(ASSET_ID, ACCOUNT_ID2), ASSET_BALANCE - 300);
+		// 原生货币不变
+		assert_eq!(Balances::free_balance(ACCOUNT_ID), native_balance);
+		assert_eq!(Balances::free_balance(ACCOUNT_ID2), ACCOUNT_BALANCE2);
+		assert_eq!(
+			KittiesModule::provenance(KITTY_ID),
+			vec![crate::ProvenanceRecord {
+				owner: ACCOUNT_ID,
+				block: 3,
+				price: Some(300),
+				asset_id: Some(ASSET_ID),
+			}]
+		);
+	});
+}
+
+#[test]
+fn sale_for_asset_checks_the_asset() {
+	build_and_execute(|| {
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), ACCOUNT_ID, ACCOUNT_BALANCE, 0));
+		assert_ok!(KittiesModule::create(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_NAME));
+
+		assert_noop!(
+			KittiesModule::sale_for_asset(RuntimeOrigin::signed(ACCOUNT_ID), KITTY_ID, ASSET_ID, 1),
+			Error::<Test>::UnknownAsset
+		);
+
+		// 买家的资产不够时失败
+		create_asset();
+		assert_ok!(KittiesModule::sale_for_asset(
+			RuntimeOrigin::signed(ACCOUNT_ID),
+			KITTY_ID,
+			ASSET_ID,
+			ASSET_BALANCE * 2
+		));
+		assert_noop!(
+			KittiesModule::buy(RuntimeOrigin::signed(ACCOUNT_ID2), KITTY_ID),
+			pallet_assets::Error::<Test>::BalanceLow
+		);
+
+		// 转移后资产上架也失效
+		assert_ok!(KittiesModule::transfer(
+			RuntimeOrigin::signed(ACCOUNT_ID),
+			ACCOUNT_ID2,
+			KITTY_ID
+		));
+		assert_eq!(KittiesModule::kitty_on_sale(KITTY_ID), None);
+		assert_eq!(KittiesModule::kitty_sale_asset(KITTY_ID), None);
+	});
+}
+
+#[test]
+fn migration_v6_adds_sale_assets_to_provenance() {
+	build_and_execute(|| {
+		let old = crate::migrations::v6::OldProvenanceRecord {
+			owner: ACCOUNT_ID,
+			block: 5u64,
+			price: Some(SALE_PRICE),
+		};
+		frame_support::storage::unhashed::put(
+			&crate::Provenance::<Test>::hashed_key_for(KITTY_ID, 0),
+			&old,
+		);
+		StorageVersion::new(5).put::<KittiesModule>();
+
+		crate::migrations::v6::migrate::<Test>();
+
+		assert_eq!(KittiesModule::on_chain_storage_version(), 6);
+		assert_eq!(
+			crate::Provenance::<Test>::get(KITTY_ID, 0),
+			Some(crate::ProvenanceRecord {
+				owner: ACCOUNT_ID,
+				block: 5,
+				price: Some(SALE_PRICE),
+				asset_id: None,
+			})
+		);
+	});
+}
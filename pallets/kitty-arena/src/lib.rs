@@ -0,0 +1,395 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Kitty tournaments: owners register their kitties for a scheduled tournament by paying an
+//! entry fee, and when the tournament starts it is played out as a knockout bracket decided by
+//! DNA-derived stats plus on-chain randomness. The winner's owner takes the prize pool.
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{Currency, ExistenceRequirement, Randomness},
+		PalletId,
+	};
+	use frame_system::pallet_prelude::*;
+	use pallet_kitties::{KittyId, KittyInspect};
+	use sp_io::hashing::blake2_256;
+	use sp_runtime::traits::{AccountIdConversion, Saturating};
+	use sp_std::prelude::*;
+
+	pub type TournamentId = u32;
+	pub type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+	/// Fighting stats of a kitty, derived from its DNA.
+	#[derive(Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq, TypeInfo, MaxEncodedLen)]
+	pub struct KittyStats {
+		pub attack: u16,
+		pub defense: u16,
+		pub speed: u16,
+	}
+
+	impl KittyStats {
+		pub fn from_dna(dna: &[u8; 16]) -> Self {
+			let sum = |genes: &[u8]| genes.iter().map(|gene| *gene as u16).sum::<u16>();
+			Self { attack: sum(&dna[0..5]), defense: sum(&dna[5..10]), speed: sum(&dna[10..16]) }
+		}
+	}
+
+	#[derive(Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq, TypeInfo, MaxEncodedLen)]
+	pub enum TournamentStatus {
+		/// Open for registration until the start block.
+		Scheduled,
+		/// Played out, the prize pool went to the owner of `winner`.
+		Finished { winner: KittyId },
+		/// Not enough entrants, the entry fees were refunded.
+		Cancelled,
+	}
+
+	#[derive(Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq, TypeInfo, MaxEncodedLen)]
+	pub struct Tournament<BlockNumber, Balance> {
+		pub start: BlockNumber,
+		pub entry_fee: Balance,
+		pub prize_pool: Balance,
+		pub status: TournamentStatus,
+	}
+
+	/// Win/loss record of a kitty across all tournaments.
+	#[derive(
+		Encode, Decode, Clone, Copy, Default, RuntimeDebug, PartialEq, Eq, TypeInfo, MaxEncodedLen,
+	)]
+	pub struct KittyRecord {
+		pub wins: u32,
+		pub losses: u32,
+		pub tournaments_won: u32,
+	}
+
+	#[pallet::pallet]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+		type Currency: Currency<Self::AccountId>;
+		/// Where kitty DNA and ownership are read from.
+		type Kitties: KittyInspect<Self::AccountId>;
+		type Randomness: Randomness<Self::Hash, Self::BlockNumber>;
+		/// The origin allowed to schedule tournaments.
+		type ScheduleOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+		/// The account holding the prize pools is derived from this id.
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+		/// The maximum number of kitties in a single tournament.
+		#[pallet::constant]
+		type MaxEntrants: Get<u32>;
+		/// The maximum number of tournaments starting in the same block.
+		#[pallet::constant]
+		type MaxTournamentsPerBlock: Get<u32>;
+	}
+
+	#[pallet::storage]
+	#[pallet::getter(fn next_tournament_id)]
+	pub type NextTournamentId<T> = StorageValue<_, TournamentId, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn tournaments)]
+	pub type Tournaments<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		TournamentId,
+		Tournament<T::BlockNumber, BalanceOf<T>>,
+		OptionQuery,
+	>;
+
+	/// Kitties registered for a scheduled tournament, with the account that registered them.
+	#[pallet::storage]
+	#[pallet::getter(fn entrants)]
+	pub type Entrants<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		TournamentId,
+		BoundedVec<(KittyId, T::AccountId), T::MaxEntrants>,
+		ValueQuery,
+	>;
+
+	/// Tournaments to play out at a given block.
+	#[pallet::storage]
+	pub type TournamentsStarting<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		T::BlockNumber,
+		BoundedVec<TournamentId, T::MaxTournamentsPerBlock>,
+		ValueQuery,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_records)]
+	pub type KittyRecords<T> = StorageMap<_, Blake2_128Concat, KittyId, KittyRecord, ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		TournamentScheduled {
+			tournament_id: TournamentId,
+			start: T::BlockNumber,
+			entry_fee: BalanceOf<T>,
+		},
+		KittyRegistered { tournament_id: TournamentId, kitty_id: KittyId, who: T::AccountId },
+		MatchResolved { tournament_id: TournamentId, round: u32, winner: KittyId, loser: KittyId },
+		TournamentFinished {
+			tournament_id: TournamentId,
+			winner: KittyId,
+			owner: T::AccountId,
+			prize: BalanceOf<T>,
+		},
+		TournamentCancelled { tournament_id: TournamentId },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The tournament does not exist.
+		TournamentNotFound,
+		/// No more tournament ids are available.
+		TournamentIdOverflow,
+		/// Tournaments must start in a future block.
+		StartInPast,
+		/// Too many tournaments already start in that block.
+		TooManyTournaments,
+		/// The tournament has already started.
+		RegistrationClosed,
+		/// The tournament has reached `MaxEntrants`.
+		TournamentFull,
+		/// The kitty is already registered for the tournament.
+		AlreadyRegistered,
+		/// The kitty does not exist.
+		KittyNotFound,
+		/// Only the owner of a kitty can register it.
+		NotKittyOwner,
+		/// The entry fee is below the existential deposit, so the first entry could not open
+		/// the prize pool account.
+		EntryFeeBelowMinimum,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(n: BlockNumberFor<T>) -> Weight {
+			let mut weight = T::DbWeight::get().reads_writes(1, 1);
+			for tournament_id in TournamentsStarting::<T>::take(n) {
+				weight.saturating_accrue(Self::play_tournament(tournament_id));
+			}
+			weight
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Schedule a tournament starting at block `start` with the given entry fee.
+		#[pallet::call_index(0)]
+		// 包括开赛时 `play_tournament` 与参赛者数量无关的部分
+		#[pallet::weight(T::DbWeight::get().reads_writes(2 + 2, 3 + 3))]
+		pub fn schedule_tournament(
+			origin: OriginFor<T>,
+			start: T::BlockNumber,
+			entry_fee: BalanceOf<T>,
+		) -> DispatchResult {
+			T::ScheduleOrigin::ensure_origin(origin)?;
+			ensure!(start > frame_system::Pallet::<T>::block_number(), Error::<T>::StartInPast);
+			ensure!(
+				entry_fee >= T::Currency::minimum_balance(),
+				Error::<T>::EntryFeeBelowMinimum
+			);
+
+			let tournament_id = Self::next_tournament_id();
+			let next_tournament_id =
+				tournament_id.checked_add(1).ok_or(Error::<T>::TournamentIdOverflow)?;
+
+			TournamentsStarting::<T>::try_mutate(start, |ids| ids.try_push(tournament_id))
+				.map_err(|_| Error::<T>::TooManyTournaments)?;
+			NextTournamentId::<T>::put(next_tournament_id);
+			Tournaments::<T>::insert(
+				tournament_id,
+				Tournament {
+					start,
+					entry_fee,
+					prize_pool: Default::default(),
+					status: TournamentStatus::Scheduled,
+				},
+			);
+
+			Self::deposit_event(Event::TournamentScheduled { tournament_id, start, entry_fee });
+
+			Ok(())
+		}
+
+		/// Register a kitty for a tournament, paying its entry fee into the prize pool.
+		#[pallet::call_index(1)]
+		// 包括开赛时这只 kitty 在 `play_tournament` 里的比赛
+		#[pallet::weight(T::DbWeight::get().reads_writes(5 + 3, 4 + 3))]
+		pub fn register(
+			origin: OriginFor<T>,
+			tournament_id: TournamentId,
+			kitty_id: KittyId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let mut tournament =
+				Self::tournaments(tournament_id).ok_or(Error::<T>::TournamentNotFound)?;
+			ensure!(
+				tournament.status == TournamentStatus::Scheduled &&
+					frame_system::Pallet::<T>::block_number() < tournament.start,
+				Error::<T>::RegistrationClosed
+			);
+
+			let owner = T::Kitties::owner(kitty_id).ok_or(Error::<T>::KittyNotFound)?;
+			ensure!(owner == who, Error::<T>::NotKittyOwner);
+
+			let mut entrants = Entrants::<T>::get(tournament_id);
+			ensure!(!entrants.iter().any(|(id, _)| *id == kitty_id), Error::<T>::AlreadyRegistered);
+			entrants.try_push((kitty_id, who.clone())).map_err(|_| Error::<T>::TournamentFull)?;
+
+			T::Currency::transfer(
+				&who,
+				&Self::account_id(),
+				tournament.entry_fee,
+				ExistenceRequirement::KeepAlive,
+			)?;
+
+			tournament.prize_pool.saturating_accrue(tournament.entry_fee);
+			Tournaments::<T>::insert(tournament_id, tournament);
+			Entrants::<T>::insert(tournament_id, entrants);
+
+			Self::deposit_event(Event::KittyRegistered { tournament_id, kitty_id, who });
+
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// The account holding the prize pools.
+		pub fn account_id() -> T::AccountId {
+			T::PalletId::get().into_account_truncating()
+		}
+
+		/// The fighting stats of a kitty, if it exists.
+		pub fn kitty_stats(kitty_id: KittyId) -> Option<KittyStats> {
+			T::Kitties::dna(kitty_id).map(|dna| KittyStats::from_dna(&dna))
+		}
+
+		/// Play out a tournament as a knockout bracket in registration order. An odd kitty out
+		/// in a round advances without a match; kitties that no longer exist forfeit.
+		fn play_tournament(tournament_id: TournamentId) -> Weight {
+			let mut tournament = match Tournaments::<T>::get(tournament_id) {
+				Some(tournament) if tournament.status == TournamentStatus::Scheduled => tournament,
+				_ => return T::DbWeight::get().reads(1),
+			};
+			let entrants = Entrants::<T>::take(tournament_id);
+			let weight = T::DbWeight::get()
+				.reads_writes(2 + 3 * entrants.len() as u64, 3 + 3 * entrants.len() as u64);
+
+			let mut bracket = entrants
+				.iter()
+				.filter_map(|(kitty_id, _)| Some((*kitty_id, Self::kitty_stats(*kitty_id)?)))
+				.collect::<Vec<_>>();
+
+			// 参赛者不足两个时退还报名费
+			if bracket.len() < 2 {
+				for (_, who) in &entrants {
+					let _ = T::Currency::transfer(
+						&Self::account_id(),
+						who,
+						tournament.entry_fee,
+						ExistenceRequirement::AllowDeath,
+					);
+				}
+				tournament.status = TournamentStatus::Cancelled;
+				Tournaments::<T>::insert(tournament_id, tournament);
+				Self::deposit_event(Event::TournamentCancelled { tournament_id });
+				return weight
+			}
+
+			let (seed, _) = T::Randomness::random(&(b"kitty_arena", tournament_id).encode());
+			let mut round = 0u32;
+			while bracket.len() > 1 {
+				let mut next_round = Vec::with_capacity(bracket.len() / 2 + 1);
+				for (index, pair) in bracket.chunks(2).enumerate() {
+					let (a, b) = match pair {
+						[a, b] => (*a, *b),
+						_ => {
+							next_round.push(pair[0]);
+							continue
+						},
+					};
+					let (winner, loser) = Self::fight(&seed, round, index as u32, a, b);
+					KittyRecords::<T>::mutate(winner.0, |record| record.wins.saturating_inc());
+					KittyRecords::<T>::mutate(loser.0, |record| record.losses.saturating_inc());
+					Self::deposit_event(Event::MatchResolved {
+						tournament_id,
+						round,
+						winner: winner.0,
+						loser: loser.0,
+					});
+					next_round.push(winner);
+				}
+				bracket = next_round;
+				round.saturating_inc();
+			}
+
+			let winner = bracket[0].0;
+			// 冠军 kitty 比赛期间被销毁时奖金发给报名者
+			let owner = T::Kitties::owner(winner)
+				.or_else(|| {
+					entrants.iter().find(|(id, _)| *id == winner).map(|(_, who)| who.clone())
+				})
+				.expect("the winner was registered by an entrant; qed");
+			let prize = tournament.prize_pool;
+			let _ = T::Currency::transfer(
+				&Self::account_id(),
+				&owner,
+				prize,
+				ExistenceRequirement::AllowDeath,
+			);
+
+			KittyRecords::<T>::mutate(winner, |record| record.tournaments_won.saturating_inc());
+			tournament.status = TournamentStatus::Finished { winner };
+			Tournaments::<T>::insert(tournament_id, tournament);
+
+			Self::deposit_event(Event::TournamentFinished { tournament_id, winner, owner, prize });
+
+			weight
+		}
+
+		/// Decide a match: each kitty scores its attack plus half its speed plus a random roll,
+		/// minus half the opponent's defense. Ties go to the faster, then the older kitty.
+		/// Returns `(winner, loser)`.
+		pub(crate) fn fight(
+			seed: &T::Hash,
+			round: u32,
+			index: u32,
+			a: (KittyId, KittyStats),
+			b: (KittyId, KittyStats),
+		) -> ((KittyId, KittyStats), (KittyId, KittyStats)) {
+			let roll = blake2_256(&(seed, round, index).encode());
+			let score = |attacker: &KittyStats, defender: &KittyStats, roll: u8| {
+				(attacker.attack as u32 + attacker.speed as u32 / 2 + roll as u32)
+					.saturating_sub(defender.defense as u32 / 2)
+			};
+			let score_a = (score(&a.1, &b.1, roll[0]), a.1.speed, sp_std::cmp::Reverse(a.0));
+			let score_b = (score(&b.1, &a.1, roll[1]), b.1.speed, sp_std::cmp::Reverse(b.0));
+
+			if score_a > score_b {
+				(a, b)
+			} else {
+				(b, a)
+			}
+		}
+	}
+}
@@ -0,0 +1,128 @@
+use crate as pallet_kitty_arena;
+use frame_support::{
+	parameter_types,
+	traits::{ConstU16, ConstU64},
+	PalletId,
+};
+use frame_system::EnsureRoot;
+use pallet_balances::AccountData;
+use pallet_kitties::{KittyId, KittyInspect};
+use sp_core::{ConstU128, ConstU32, H256};
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+use std::{cell::RefCell, collections::BTreeMap};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system,
+		KittyArena: pallet_kitty_arena,
+		Randomness: pallet_insecure_randomness_collective_flip,
+		Balances: pallet_balances,
+	}
+);
+
+pub type Balance = u128;
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = AccountData<Balance>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ConstU16<42>;
+	type OnSetCode = ();
+	type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+pub const EXISTENTIAL_DEPOSIT: u128 = 500;
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ConstU32<50>;
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = Balance;
+	type RuntimeEvent = RuntimeEvent;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU128<EXISTENTIAL_DEPOSIT>;
+	type AccountStore = System;
+	type WeightInfo = pallet_balances::weights::SubstrateWeight<Test>;
+}
+
+impl pallet_insecure_randomness_collective_flip::Config for Test {}
+
+thread_local! {
+	static KITTIES: RefCell<BTreeMap<KittyId, (u64, [u8; 16])>> = RefCell::new(BTreeMap::new());
+}
+
+/// Kitties pallet stand-in, so tests can pick DNA and owners directly.
+pub struct MockKitties;
+
+impl MockKitties {
+	pub fn insert(kitty_id: KittyId, owner: u64, dna: [u8; 16]) {
+		KITTIES.with(|kitties| kitties.borrow_mut().insert(kitty_id, (owner, dna)));
+	}
+
+	pub fn remove(kitty_id: KittyId) {
+		KITTIES.with(|kitties| kitties.borrow_mut().remove(&kitty_id));
+	}
+}
+
+impl KittyInspect<u64> for MockKitties {
+	fn dna(kitty_id: KittyId) -> Option<[u8; 16]> {
+		KITTIES.with(|kitties| kitties.borrow().get(&kitty_id).map(|(_, dna)| *dna))
+	}
+
+	fn owner(kitty_id: KittyId) -> Option<u64> {
+		KITTIES.with(|kitties| kitties.borrow().get(&kitty_id).map(|(owner, _)| *owner))
+	}
+}
+
+parameter_types! {
+	pub ArenaPalletId: PalletId = PalletId(*b"py/arena");
+}
+
+impl pallet_kitty_arena::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type Kitties = MockKitties;
+	type Randomness = Randomness;
+	type ScheduleOrigin = EnsureRoot<u64>;
+	type PalletId = ArenaPalletId;
+	type MaxEntrants = ConstU32<4>;
+	type MaxTournamentsPerBlock = ConstU32<2>;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	KITTIES.with(|kitties| kitties.borrow_mut().clear());
+	let mut ext: sp_io::TestExternalities =
+		frame_system::GenesisConfig::default().build_storage::<Test>().unwrap().into();
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
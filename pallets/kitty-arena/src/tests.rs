@@ -0,0 +1,217 @@
+use crate::{mock::*, Error, Event, KittyStats, TournamentStatus};
+use codec::Encode;
+use frame_support::{assert_noop, assert_ok, traits::Hooks};
+use sp_io::hashing::blake2_256;
+
+const ALICE: u64 = 1;
+const BOB: u64 = 2;
+const CHARLIE: u64 = 3;
+const ACCOUNT_BALANCE: u128 = 100000;
+const ENTRY_FEE: u128 = EXISTENTIAL_DEPOSIT * 2;
+const START: u64 = 10;
+
+fn fund(accounts: &[u64]) {
+	for account in accounts {
+		assert_ok!(Balances::set_balance(RuntimeOrigin::root(), *account, ACCOUNT_BALANCE, 0));
+	}
+}
+
+fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		System::set_block_number(System::block_number() + 1);
+		KittyArena::on_initialize(System::block_number());
+	}
+}
+
+#[test]
+fn stats_are_derived_from_dna() {
+	let mut dna = [0u8; 16];
+	dna[0] = 10;
+	dna[4] = 5;
+	dna[5] = 7;
+	dna[15] = 3;
+	assert_eq!(KittyStats::from_dna(&dna), KittyStats { attack: 15, defense: 7, speed: 3 });
+}
+
+#[test]
+fn schedule_tournament_works() {
+	new_test_ext().execute_with(|| {
+		// 只有 root 可以创建比赛
+		assert_noop!(
+			KittyArena::schedule_tournament(RuntimeOrigin::signed(ALICE), START, ENTRY_FEE),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			KittyArena::schedule_tournament(RuntimeOrigin::root(), 1, ENTRY_FEE),
+			Error::<Test>::StartInPast
+		);
+		assert_noop!(
+			KittyArena::schedule_tournament(RuntimeOrigin::root(), START, EXISTENTIAL_DEPOSIT - 1),
+			Error::<Test>::EntryFeeBelowMinimum
+		);
+
+		assert_ok!(KittyArena::schedule_tournament(RuntimeOrigin::root(), START, ENTRY_FEE));
+		assert_eq!(KittyArena::next_tournament_id(), 1);
+		assert_eq!(KittyArena::tournaments(0).unwrap().status, TournamentStatus::Scheduled);
+		System::assert_last_event(
+			Event::TournamentScheduled { tournament_id: 0, start: START, entry_fee: ENTRY_FEE }
+				.into(),
+		);
+
+		// 同一区块开始的比赛数量有上限
+		assert_ok!(KittyArena::schedule_tournament(RuntimeOrigin::root(), START, ENTRY_FEE));
+		assert_noop!(
+			KittyArena::schedule_tournament(RuntimeOrigin::root(), START, ENTRY_FEE),
+			Error::<Test>::TooManyTournaments
+		);
+	});
+}
+
+#[test]
+fn register_works() {
+	new_test_ext().execute_with(|| {
+		fund(&[ALICE, BOB]);
+		MockKitties::insert(0, ALICE, [1; 16]);
+		assert_ok!(KittyArena::schedule_tournament(RuntimeOrigin::root(), START, ENTRY_FEE));
+
+		assert_noop!(
+			KittyArena::register(RuntimeOrigin::signed(ALICE), 1, 0),
+			Error::<Test>::TournamentNotFound
+		);
+		assert_noop!(
+			KittyArena::register(RuntimeOrigin::signed(ALICE), 0, 1),
+			Error::<Test>::KittyNotFound
+		);
+		assert_noop!(
+			KittyArena::register(RuntimeOrigin::signed(BOB), 0, 0),
+			Error::<Test>::NotKittyOwner
+		);
+
+		assert_ok!(KittyArena::register(RuntimeOrigin::signed(ALICE), 0, 0));
+		assert_eq!(Balances::free_balance(ALICE), ACCOUNT_BALANCE - ENTRY_FEE);
+		assert_eq!(Balances::free_balance(KittyArena::account_id()), ENTRY_FEE);
+		assert_eq!(KittyArena::tournaments(0).unwrap().prize_pool, ENTRY_FEE);
+		assert_eq!(KittyArena::entrants(0).into_inner(), vec![(0, ALICE)]);
+		System::assert_last_event(
+			Event::KittyRegistered { tournament_id: 0, kitty_id: 0, who: ALICE }.into(),
+		);
+
+		assert_noop!(
+			KittyArena::register(RuntimeOrigin::signed(ALICE), 0, 0),
+			Error::<Test>::AlreadyRegistered
+		);
+	});
+}
+
+#[test]
+fn register_is_bounded() {
+	new_test_ext().execute_with(|| {
+		fund(&[ALICE]);
+		for kitty_id in 0..5 {
+			MockKitties::insert(kitty_id, ALICE, [kitty_id as u8; 16]);
+		}
+		assert_ok!(KittyArena::schedule_tournament(RuntimeOrigin::root(), START, ENTRY_FEE));
+
+		for kitty_id in 0..4 {
+			assert_ok!(KittyArena::register(RuntimeOrigin::signed(ALICE), 0, kitty_id));
+		}
+		assert_noop!(
+			KittyArena::register(RuntimeOrigin::signed(ALICE), 0, 4),
+			Error::<Test>::TournamentFull
+		);
+
+		// 比赛开始后不能再报名
+		run_to_block(START);
+		assert_noop!(
+			KittyArena::register(RuntimeOrigin::signed(ALICE), 0, 4),
+			Error::<Test>::RegistrationClosed
+		);
+	});
+}
+
+#[test]
+fn tournament_pays_the_winner() {
+	new_test_ext().execute_with(|| {
+		fund(&[ALICE, BOB, CHARLIE]);
+		// 属性差距足够大，随机数无法改变结果
+		MockKitties::insert(0, ALICE, [0; 16]);
+		MockKitties::insert(1, BOB, [255; 16]);
+		MockKitties::insert(2, CHARLIE, [0; 16]);
+		assert_ok!(KittyArena::schedule_tournament(RuntimeOrigin::root(), START, ENTRY_FEE));
+		assert_ok!(KittyArena::register(RuntimeOrigin::signed(ALICE), 0, 0));
+		assert_ok!(KittyArena::register(RuntimeOrigin::signed(BOB), 0, 1));
+		assert_ok!(KittyArena::register(RuntimeOrigin::signed(CHARLIE), 0, 2));
+
+		// 夺冠之前 kitty 被转让，奖金发给当前主人
+		MockKitties::insert(1, CHARLIE, [255; 16]);
+		run_to_block(START);
+
+		assert_eq!(
+			KittyArena::tournaments(0).unwrap().status,
+			TournamentStatus::Finished { winner: 1 }
+		);
+		assert!(KittyArena::entrants(0).is_empty());
+		assert_eq!(Balances::free_balance(ALICE), ACCOUNT_BALANCE - ENTRY_FEE);
+		assert_eq!(Balances::free_balance(BOB), ACCOUNT_BALANCE - ENTRY_FEE);
+		assert_eq!(Balances::free_balance(CHARLIE), ACCOUNT_BALANCE + ENTRY_FEE * 2);
+		assert_eq!(Balances::free_balance(KittyArena::account_id()), 0);
+
+		// 第一轮 0 对 1，2 轮空；决赛 1 对 2
+		let record = KittyArena::kitty_records(1);
+		assert_eq!((record.wins, record.losses, record.tournaments_won), (2, 0, 1));
+		assert_eq!(KittyArena::kitty_records(0).losses, 1);
+		assert_eq!(KittyArena::kitty_records(2).losses, 1);
+		System::assert_has_event(
+			Event::MatchResolved { tournament_id: 0, round: 0, winner: 1, loser: 0 }.into(),
+		);
+		System::assert_last_event(
+			Event::TournamentFinished {
+				tournament_id: 0,
+				winner: 1,
+				owner: CHARLIE,
+				prize: ENTRY_FEE * 3,
+			}
+			.into(),
+		);
+	});
+}
+
+#[test]
+fn ties_go_to_the_older_kitty() {
+	new_test_ext().execute_with(|| {
+		let seed = sp_core::H256::repeat_byte(1);
+		let a = (3, KittyStats::from_dna(&[7; 16]));
+		let b = (5, KittyStats::from_dna(&[7; 16]));
+
+		// 相同属性下只有随机数不同，找一个平局的回合
+		let tie = (0..u32::MAX)
+			.find(|round| {
+				let roll = blake2_256(&(seed, *round, 0u32).encode());
+				roll[0] == roll[1]
+			})
+			.unwrap();
+		assert_eq!(KittyArena::fight(&seed, tie, 0, b, a), (a, b));
+	});
+}
+
+#[test]
+fn tournament_without_opponents_is_cancelled() {
+	new_test_ext().execute_with(|| {
+		fund(&[ALICE, BOB]);
+		MockKitties::insert(0, ALICE, [1; 16]);
+		MockKitties::insert(1, BOB, [2; 16]);
+		assert_ok!(KittyArena::schedule_tournament(RuntimeOrigin::root(), START, ENTRY_FEE));
+		assert_ok!(KittyArena::register(RuntimeOrigin::signed(ALICE), 0, 0));
+		assert_ok!(KittyArena::register(RuntimeOrigin::signed(BOB), 0, 1));
+
+		// 不存在的 kitty 弃权，只剩一个参赛者
+		MockKitties::remove(1);
+		run_to_block(START);
+
+		assert_eq!(KittyArena::tournaments(0).unwrap().status, TournamentStatus::Cancelled);
+		assert_eq!(Balances::free_balance(ALICE), ACCOUNT_BALANCE);
+		assert_eq!(Balances::free_balance(BOB), ACCOUNT_BALANCE);
+		assert_eq!(KittyArena::kitty_records(0), Default::default());
+		System::assert_last_event(Event::TournamentCancelled { tournament_id: 0 }.into());
+	});
+}
@@ -11,6 +11,7 @@ mod tests;
 pub mod pallet {
 	use frame_support::pallet_prelude::*;
 	use frame_system::pallet_prelude::*;
+	use sp_std::prelude::*;
 
 	// 通过继承拥有了 frame_system::Config 里定义的数据类型
 	#[pallet::config]
@@ -55,9 +56,41 @@ pub mod pallet {
 		NotClaimOwner,
 	}
 
+	// 定义创世配置
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// Claims registered at genesis as `(claim, owner)`.
+		pub claims: Vec<(Vec<u8>, T::AccountId)>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { claims: Default::default() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			for (claim, owner) in &self.claims {
+				let claim: BoundedVec<u8, T::MaxClaimLength> =
+					claim.clone().try_into().expect("genesis claim exceeds MaxClaimLength");
+				assert!(!Proofs::<T>::contains_key(&claim), "duplicate claim in genesis config");
+
+				Proofs::<T>::insert(&claim, (owner.clone(), T::BlockNumber::default()));
+			}
+		}
+	}
+
 	// 用于定义回调函数，在区块的不同时期执行
 	#[pallet::hooks]
-	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		#[cfg(feature = "try-runtime")]
+		fn try_state(_n: BlockNumberFor<T>) -> Result<(), &'static str> {
+			Self::do_try_state()
+		}
+	}
 
 	// 定义可调用函数
 	#[pallet::call]
@@ -127,4 +160,23 @@ pub mod pallet {
 			Ok(().into())
 		}
 	}
+
+	impl<T: Config> Pallet<T> {
+		/// Check the storage invariants of the pallet.
+		#[cfg(any(feature = "try-runtime", test))]
+		pub fn do_try_state() -> Result<(), &'static str> {
+			use frame_support::storage::{KeyPrefixIterator, StoragePrefixedMap};
+
+			// 存证只能以 BoundedVec 撤销和转移, 调小 MaxClaimLength 后更长的存证再也无法操作;
+			// iter 会跳过解码失败的存证, 所以和原始 key 的数量比较
+			let prefix = Proofs::<T>::final_prefix().to_vec();
+			let stored = KeyPrefixIterator::new(prefix.clone(), prefix, |_| Ok(())).count();
+			ensure!(
+				Proofs::<T>::iter().count() == stored,
+				"claim does not decode with the current MaxClaimLength"
+			);
+
+			Ok(())
+		}
+	}
 }
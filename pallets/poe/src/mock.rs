@@ -57,3 +57,11 @@ impl pallet_poe::Config for Test {
 pub fn new_test_ext() -> sp_io::TestExternalities {
 	frame_system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
 }
+
+/// Run `test` in a fresh externality and check the pallet's storage invariants afterwards.
+pub fn build_and_execute(test: impl FnOnce()) {
+	new_test_ext().execute_with(|| {
+		test();
+		PoeModule::do_try_state().expect("storage invariants violated");
+	});
+}
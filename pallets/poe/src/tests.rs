@@ -1,9 +1,15 @@
 use crate::{mock::*, Error, Proofs};
-use frame_support::{assert_noop, assert_ok, BoundedVec};
+use codec::Encode;
+use frame_support::{
+	assert_noop, assert_ok,
+	storage::{unhashed, StoragePrefixedMap},
+	traits::GenesisBuild,
+	Blake2_128Concat, BoundedVec, StorageHasher,
+};
 
 #[test]
 fn create_claim_works() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		let claim = BoundedVec::try_from(vec![0u8; 32]).unwrap();
 		assert_ok!(PoeModule::create_claim(RuntimeOrigin::signed(1), claim.clone()));
 
@@ -16,7 +22,7 @@ fn create_claim_works() {
 
 #[test]
 fn create_claim_failed_when_claim_already_exist() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		let claim = BoundedVec::try_from(vec![0u8; 32]).unwrap();
 		let _ = PoeModule::create_claim(RuntimeOrigin::signed(1), claim.clone());
 
@@ -29,7 +35,7 @@ fn create_claim_failed_when_claim_already_exist() {
 
 #[test]
 fn revoke_claim_works() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		let claim = BoundedVec::try_from(vec![0u8; 32]).unwrap();
 		assert_ok!(PoeModule::create_claim(RuntimeOrigin::signed(1), claim.clone()));
 
@@ -39,7 +45,7 @@ fn revoke_claim_works() {
 
 #[test]
 fn revoke_claim_failed_when_claim_is_not_exist() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		let claim = BoundedVec::try_from(vec![0u8; 32]).unwrap();
 
 		assert_noop!(
@@ -51,7 +57,7 @@ fn revoke_claim_failed_when_claim_is_not_exist() {
 
 #[test]
 fn revoke_claim_failed_with_wrong_owner() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		let claim = BoundedVec::try_from(vec![0u8; 32]).unwrap();
 		assert_ok!(PoeModule::create_claim(RuntimeOrigin::signed(1), claim.clone()));
 
@@ -64,7 +70,7 @@ fn revoke_claim_failed_with_wrong_owner() {
 
 #[test]
 fn transfer_claim_works() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		let claim = BoundedVec::try_from(vec![0u8; 32]).unwrap();
 		assert_ok!(PoeModule::create_claim(RuntimeOrigin::signed(1), claim.clone()));
 
@@ -74,7 +80,7 @@ fn transfer_claim_works() {
 
 #[test]
 fn transfer_claim_failed_when_claim_is_not_exist() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		let claim = BoundedVec::try_from(vec![0u8; 32]).unwrap();
 
 		assert_noop!(
@@ -86,7 +92,7 @@ fn transfer_claim_failed_when_claim_is_not_exist() {
 
 #[test]
 fn transfer_claim_failed_with_wrong_owner() {
-	new_test_ext().execute_with(|| {
+	build_and_execute(|| {
 		let claim = BoundedVec::try_from(vec![0u8; 32]).unwrap();
 		assert_ok!(PoeModule::create_claim(RuntimeOrigin::signed(1), claim.clone()));
 
@@ -96,3 +102,53 @@ fn transfer_claim_failed_with_wrong_owner() {
 		);
 	});
 }
+
+#[test]
+fn genesis_config_works() {
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	crate::GenesisConfig::<Test> { claims: vec![(vec![1u8; 32], 1), (vec![2u8; 8], 2)] }
+		.assimilate_storage(&mut storage)
+		.unwrap();
+
+	sp_io::TestExternalities::from(storage).execute_with(|| {
+		let claim = BoundedVec::try_from(vec![2u8; 8]).unwrap();
+		assert_eq!(Proofs::<Test>::get(&claim), Some((2, 0)));
+
+		assert_noop!(
+			PoeModule::revoke_claim(RuntimeOrigin::signed(1), claim),
+			Error::<Test>::NotClaimOwner
+		);
+		assert_ok!(PoeModule::do_try_state());
+	});
+}
+
+#[test]
+#[should_panic(expected = "genesis claim exceeds MaxClaimLength")]
+fn genesis_config_rejects_oversized_claim() {
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let _ = crate::GenesisConfig::<Test> { claims: vec![(vec![0u8; 33], 1)] }
+		.assimilate_storage(&mut storage);
+}
+
+#[test]
+fn try_state_detects_claims_longer_than_max_claim_length() {
+	build_and_execute(|| {
+		let claim = BoundedVec::try_from(vec![0u8; 32]).unwrap();
+		Proofs::<Test>::insert(&claim, (1, 1));
+		assert_ok!(PoeModule::do_try_state());
+
+		// 调小 MaxClaimLength 之前存下的存证
+		let key = [
+			&Proofs::<Test>::final_prefix()[..],
+			&Blake2_128Concat::hash(&vec![0u8; 33].encode()),
+		]
+		.concat();
+		unhashed::put(&key, &(1u64, 1u64));
+		assert_eq!(
+			PoeModule::do_try_state(),
+			Err("claim does not decode with the current MaxClaimLength")
+		);
+
+		unhashed::kill(&key);
+	});
+}
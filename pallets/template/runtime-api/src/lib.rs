@@ -0,0 +1,17 @@
+//! Runtime API definition for the template registry pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	pub trait TemplateApi<AccountId: Codec> {
+		/// The value of an entry, `None` if it does not exist.
+		fn entry(owner: AccountId, namespace: Vec<u8>, key: Vec<u8>) -> Option<Vec<u8>>;
+		/// All keys and values of an account under `namespace`.
+		fn entries(owner: AccountId, namespace: Vec<u8>) -> Vec<(Vec<u8>, Vec<u8>)>;
+		/// The value `do_something` stored in the namespace-less entry of `owner`.
+		fn something(owner: AccountId) -> Option<u32>;
+	}
+}
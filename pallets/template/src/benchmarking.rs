@@ -5,15 +5,78 @@ use super::*;
 #[allow(unused)]
 use crate::Pallet as Template;
 use frame_benchmarking::v1::{benchmarks, whitelisted_caller};
+use frame_support::{
+	traits::{Currency, Get},
+	BoundedVec,
+};
 use frame_system::RawOrigin;
+use sp_runtime::traits::Bounded;
+use sp_std::vec;
+
+fn funded_caller<T: Config>() -> T::AccountId {
+	let caller: T::AccountId = whitelisted_caller();
+	T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value() / 2u32.into());
+	caller
+}
+
+/// The longest vector that fits into the bound `S`.
+fn longest<S: Get<u32>>() -> BoundedVec<u8, S> {
+	vec![0u8; S::get() as usize].try_into().expect("exactly the bound")
+}
 
 benchmarks! {
 	do_something {
 		let s in 0 .. 100;
+		let caller = funded_caller::<T>();
+	}: _(RawOrigin::Signed(caller.clone()), s)
+	verify {
+		assert_eq!(Template::<T>::something(caller), Some(s));
+	}
+
+	cause_error {
+		let caller = funded_caller::<T>();
+		Template::<T>::do_something(RawOrigin::Signed(caller.clone()).into(), 1)?;
+	}: _(RawOrigin::Signed(caller.clone()))
+	verify {
+		assert_eq!(Template::<T>::something(caller), Some(2));
+	}
+
+	cause_error_none_value {
 		let caller: T::AccountId = whitelisted_caller();
-	}: _(RawOrigin::Signed(caller), s)
+	}: {
+		assert_eq!(
+			Template::<T>::cause_error(RawOrigin::Signed(caller.clone()).into()),
+			Err(Error::<T>::NoneValue.into())
+		);
+	}
+	verify {
+		assert_eq!(Template::<T>::something(caller), None);
+	}
+
+	set_entry {
+		let caller = funded_caller::<T>();
+		let namespace = longest::<T::MaxNamespaceLength>();
+		let key = longest::<T::MaxKeyLength>();
+		let value = longest::<T::MaxValueLength>();
+	}: _(RawOrigin::Signed(caller.clone()), namespace.clone(), key.clone(), value)
+	verify {
+		assert!(Entries::<T>::contains_key((&caller, &namespace, &key)));
+	}
+
+	remove_entry {
+		let caller = funded_caller::<T>();
+		let namespace = longest::<T::MaxNamespaceLength>();
+		let key = longest::<T::MaxKeyLength>();
+		let value = longest::<T::MaxValueLength>();
+		Template::<T>::set_entry(
+			RawOrigin::Signed(caller.clone()).into(),
+			namespace.clone(),
+			key.clone(),
+			value,
+		)?;
+	}: _(RawOrigin::Signed(caller.clone()), namespace.clone(), key.clone())
 	verify {
-		assert_eq!(Something::<T>::get(), Some(s));
+		assert!(!Entries::<T>::contains_key((&caller, &namespace, &key)));
 	}
 
 	impl_benchmark_test_suite!(Template, crate::mock::new_test_ext(), crate::mock::Test);
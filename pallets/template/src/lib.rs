@@ -13,13 +13,41 @@ mod tests;
 
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
+pub mod migrations;
+pub mod weights;
+pub use weights::*;
 
 #[frame_support::pallet]
 pub mod pallet {
-	use frame_support::pallet_prelude::*;
+	use super::WeightInfo;
+	use codec::DecodeAll;
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{ReservableCurrency, StorageVersion},
+	};
 	use frame_system::pallet_prelude::*;
+	use sp_runtime::traits::{Saturating, Zero};
+	use sp_std::prelude::*;
+
+	pub type BalanceOf<T> = <<T as Config>::Currency as frame_support::traits::Currency<
+		<T as frame_system::Config>::AccountId,
+	>>::Balance;
+	pub type NamespaceOf<T> = BoundedVec<u8, <T as Config>::MaxNamespaceLength>;
+	pub type KeyOf<T> = BoundedVec<u8, <T as Config>::MaxKeyLength>;
+	pub type ValueOf<T> = BoundedVec<u8, <T as Config>::MaxValueLength>;
+	pub type EntryOf<T> = Entry<ValueOf<T>, BalanceOf<T>>;
+
+	/// A registry entry together with the deposit reserved for it.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct Entry<Value, Balance> {
+		pub value: Value,
+		pub deposit: Balance,
+	}
+
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
 
 	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	/// Configure the pallet by specifying the parameters and types on which it depends.
@@ -27,15 +55,53 @@ pub mod pallet {
 	pub trait Config: frame_system::Config {
 		/// Because this pallet emits events, it depends on the runtime's definition of an event.
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+		/// The currency entry deposits are reserved in.
+		type Currency: ReservableCurrency<Self::AccountId>;
+		/// The deposit reserved for every entry.
+		#[pallet::constant]
+		type EntryDepositBase: Get<BalanceOf<Self>>;
+		/// The deposit reserved per byte of namespace, key and value.
+		#[pallet::constant]
+		type EntryDepositPerByte: Get<BalanceOf<Self>>;
+		/// The maximum length of a namespace.
+		#[pallet::constant]
+		type MaxNamespaceLength: Get<u32>;
+		/// The maximum length of a key.
+		#[pallet::constant]
+		type MaxKeyLength: Get<u32>;
+		/// The maximum length of a value.
+		#[pallet::constant]
+		type MaxValueLength: Get<u32>;
+		/// The maximum number of entries a single account can hold.
+		#[pallet::constant]
+		type MaxEntriesPerAccount: Get<u32>;
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
 	}
 
 	// The pallet's runtime storage items.
 	// https://docs.substrate.io/main-docs/build/runtime-storage/
-	#[pallet::storage]
-	#[pallet::getter(fn something)]
 	// Learn more about declaring storage items:
 	// https://docs.substrate.io/main-docs/build/runtime-storage/#declaring-storage-items
-	pub type Something<T> = StorageValue<_, u32>;
+	/// Registry entries by owner, namespace and key. `do_something` writes the entry with the
+	/// empty namespace and key.
+	#[pallet::storage]
+	pub type Entries<T: Config> = StorageNMap<
+		_,
+		(
+			NMapKey<Blake2_128Concat, T::AccountId>,
+			NMapKey<Blake2_128Concat, NamespaceOf<T>>,
+			NMapKey<Blake2_128Concat, KeyOf<T>>,
+		),
+		EntryOf<T>,
+		OptionQuery,
+	>;
+
+	/// Number of entries held by an account.
+	#[pallet::storage]
+	#[pallet::getter(fn entry_count)]
+	pub type EntryCount<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
 
 	// Pallets use events to inform users when important changes are made.
 	// https://docs.substrate.io/main-docs/build/events-errors/
@@ -45,6 +111,15 @@ pub mod pallet {
 		/// Event documentation should end with an array that provides descriptive names for event
 		/// parameters. [something, who]
 		SomethingStored { something: u32, who: T::AccountId },
+		/// An entry was created or updated. [who, namespace, key, deposit]
+		EntrySet {
+			who: T::AccountId,
+			namespace: NamespaceOf<T>,
+			key: KeyOf<T>,
+			deposit: BalanceOf<T>,
+		},
+		/// An entry was removed and its deposit returned. [who, namespace, key]
+		EntryRemoved { who: T::AccountId, namespace: NamespaceOf<T>, key: KeyOf<T> },
 	}
 
 	// Errors inform users that something went wrong.
@@ -54,6 +129,20 @@ pub mod pallet {
 		NoneValue,
 		/// Errors should have helpful documentation associated with them.
 		StorageOverflow,
+		/// The account already holds `MaxEntriesPerAccount` entries.
+		TooManyEntries,
+		/// The caller has no entry under this namespace and key.
+		UnknownEntry,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn integrity_test() {
+			assert!(
+				T::MaxValueLength::get() as usize >= 0u32.encoded_size(),
+				"`do_something` values must fit into an entry"
+			);
+		}
 	}
 
 	// Dispatchable functions allows users to interact with the pallet and invoke state changes.
@@ -62,9 +151,10 @@ pub mod pallet {
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
 		/// An example dispatchable that takes a singles value as a parameter, writes the value to
-		/// storage and emits an event. This function must be dispatched by a signed extrinsic.
+		/// the caller's namespace-less entry and emits an event. This function must be dispatched
+		/// by a signed extrinsic.
 		#[pallet::call_index(0)]
-		#[pallet::weight(10_000 + T::DbWeight::get().writes(1).ref_time())]
+		#[pallet::weight(T::WeightInfo::do_something())]
 		pub fn do_something(origin: OriginFor<T>, something: u32) -> DispatchResult {
 			// Check that the extrinsic was signed and get the signer.
 			// This function will return an error if the extrinsic is not signed.
@@ -72,7 +162,7 @@ pub mod pallet {
 			let who = ensure_signed(origin)?;
 
 			// Update storage.
-			<Something<T>>::put(something);
+			Self::set_something(&who, something)?;
 
 			// Emit an event.
 			Self::deposit_event(Event::SomethingStored { something, who });
@@ -82,22 +172,131 @@ pub mod pallet {
 
 		/// An example dispatchable that may throw a custom error.
 		#[pallet::call_index(1)]
-		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1,1).ref_time())]
+		#[pallet::weight(
+			T::WeightInfo::cause_error().max(T::WeightInfo::cause_error_none_value())
+		)]
 		pub fn cause_error(origin: OriginFor<T>) -> DispatchResult {
-			let _who = ensure_signed(origin)?;
+			let who = ensure_signed(origin)?;
 
 			// Read a value from storage.
-			match <Something<T>>::get() {
+			match Self::something(who.clone()) {
 				// Return an error if the value has not been set.
 				None => return Err(Error::<T>::NoneValue.into()),
 				Some(old) => {
 					// Increment the value read from storage; will error in the event of overflow.
 					let new = old.checked_add(1).ok_or(Error::<T>::StorageOverflow)?;
 					// Update the value in storage with the incremented result.
-					<Something<T>>::put(new);
-					Ok(())
+					Self::set_something(&who, new)
+				},
+			}
+		}
+
+		/// Create or update an entry of the caller, reserving a deposit for its size.
+		#[pallet::call_index(2)]
+		#[pallet::weight(T::WeightInfo::set_entry())]
+		pub fn set_entry(
+			origin: OriginFor<T>,
+			namespace: NamespaceOf<T>,
+			key: KeyOf<T>,
+			value: ValueOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::do_set_entry(who, namespace, key, value)
+		}
+
+		/// Remove an entry of the caller and return its deposit.
+		#[pallet::call_index(3)]
+		#[pallet::weight(T::WeightInfo::remove_entry())]
+		pub fn remove_entry(
+			origin: OriginFor<T>,
+			namespace: NamespaceOf<T>,
+			key: KeyOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let entry =
+				Entries::<T>::take((&who, &namespace, &key)).ok_or(Error::<T>::UnknownEntry)?;
+			T::Currency::unreserve(&who, entry.deposit);
+			EntryCount::<T>::mutate(&who, |count| count.saturating_dec());
+
+			Self::deposit_event(Event::EntryRemoved { who, namespace, key });
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		fn do_set_entry(
+			who: T::AccountId,
+			namespace: NamespaceOf<T>,
+			key: KeyOf<T>,
+			value: ValueOf<T>,
+		) -> DispatchResult {
+			let entry_key = (&who, &namespace, &key);
+			let old_deposit = Entries::<T>::get(entry_key).map(|entry| entry.deposit);
+			let count = EntryCount::<T>::get(&who);
+			if old_deposit.is_none() {
+				ensure!(count < T::MaxEntriesPerAccount::get(), Error::<T>::TooManyEntries);
+			}
+
+			// Only reserve or return the difference to the previous deposit.
+			let deposit = Self::deposit_for(&namespace, &key, &value);
+			match old_deposit {
+				Some(old) if old >= deposit => {
+					T::Currency::unreserve(&who, old - deposit);
+				},
+				old => {
+					T::Currency::reserve(&who, deposit - old.unwrap_or_else(Zero::zero))?;
 				},
 			}
+
+			if old_deposit.is_none() {
+				EntryCount::<T>::insert(&who, count + 1);
+			}
+			Entries::<T>::insert(entry_key, Entry { value, deposit });
+
+			Self::deposit_event(Event::EntrySet { who, namespace, key, deposit });
+			Ok(())
+		}
+
+		/// Store `something` as the namespace-less entry of `who`.
+		fn set_something(who: &T::AccountId, something: u32) -> DispatchResult {
+			// Checked by `integrity_test`.
+			let value = something.encode().try_into().map_err(|_| Error::<T>::StorageOverflow)?;
+			Self::do_set_entry(who.clone(), Default::default(), Default::default(), value)
+		}
+
+		/// The value `do_something` stored for `owner`, `None` if the namespace-less entry of
+		/// `owner` does not exist or does not hold one.
+		pub fn something(owner: T::AccountId) -> Option<u32> {
+			let entry_key = (owner, NamespaceOf::<T>::default(), KeyOf::<T>::default());
+			let entry = Entries::<T>::get(entry_key)?;
+			u32::decode_all(&mut &entry.value[..]).ok()
+		}
+
+		/// The deposit for an entry: the base deposit plus a deposit per stored byte.
+		pub fn deposit_for(namespace: &[u8], key: &[u8], value: &[u8]) -> BalanceOf<T> {
+			let bytes = (namespace.len() + key.len() + value.len()) as u32;
+			T::EntryDepositPerByte::get()
+				.saturating_mul(bytes.into())
+				.saturating_add(T::EntryDepositBase::get())
+		}
+
+		/// The value of an entry, `None` if it does not exist.
+		pub fn entry(owner: T::AccountId, namespace: Vec<u8>, key: Vec<u8>) -> Option<Vec<u8>> {
+			let namespace = NamespaceOf::<T>::try_from(namespace).ok()?;
+			let key = KeyOf::<T>::try_from(key).ok()?;
+			Entries::<T>::get((owner, namespace, key)).map(|entry| entry.value.into_inner())
+		}
+
+		/// All keys and values of an account under `namespace`, in no particular order.
+		pub fn entries(owner: T::AccountId, namespace: Vec<u8>) -> Vec<(Vec<u8>, Vec<u8>)> {
+			let namespace = match NamespaceOf::<T>::try_from(namespace) {
+				Ok(namespace) => namespace,
+				Err(_) => return Vec::new(),
+			};
+			Entries::<T>::iter_prefix((owner, namespace))
+				.map(|(key, entry)| (key.into_inner(), entry.value.into_inner()))
+				.collect()
 		}
 	}
 }
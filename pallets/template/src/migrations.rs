@@ -0,0 +1,78 @@
+//! Storage migrations of the template pallet.
+
+/// Version 1 keeps the `do_something` value in the registry, as the namespace-less entry of the
+/// caller.
+pub mod v1 {
+	use crate::{Config, Entries, Entry, EntryCount, KeyOf, NamespaceOf, Pallet};
+	use codec::Encode;
+	use frame_support::{
+		storage::migration::take_storage_value,
+		traits::{Get, GetStorageVersion, OnRuntimeUpgrade, PalletInfoAccess, StorageVersion},
+		weights::Weight,
+	};
+	use sp_runtime::traits::Zero;
+	use sp_std::marker::PhantomData;
+	#[cfg(feature = "try-runtime")]
+	use sp_std::vec::Vec;
+
+	/// Move the single `Something` value of version 0 into the namespace-less entry of
+	/// `Owner`, e.g. the sudo key, where `something(owner)` keeps returning it.
+	///
+	/// The old value was not stored per account, so it cannot go back to whoever stored it.
+	/// `Owner` holds it without a deposit, as nobody paid one for it; the deposit is reserved
+	/// once `Owner` sets the entry again. The value is dropped if `Owner` is `None`.
+	///
+	/// Listed in the runtime's `Migrations`.
+	pub struct MigrateToV1<T, Owner>(PhantomData<(T, Owner)>);
+
+	impl<T: Config, Owner: Get<Option<T::AccountId>>> OnRuntimeUpgrade for MigrateToV1<T, Owner> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 0 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let something =
+				take_storage_value::<u32>(Pallet::<T>::name().as_bytes(), b"Something", &[]);
+			let mut weight = T::DbWeight::get().reads_writes(2, 2);
+			if let (Some(something), Some(owner)) = (something, Owner::get()) {
+				// `integrity_test` checks that a `u32` fits into a value.
+				let value = something.encode().try_into().expect("checked by integrity_test; qed");
+				let entry_key = (&owner, NamespaceOf::<T>::default(), KeyOf::<T>::default());
+				// Version 0 has no entries, so this is the owner's first.
+				Entries::<T>::insert(entry_key, Entry { value, deposit: Zero::zero() });
+				EntryCount::<T>::mutate(&owner, |count| *count = count.saturating_add(1));
+				weight.saturating_accrue(T::DbWeight::get().reads_writes(1, 2));
+			}
+			StorageVersion::new(1).put::<Pallet<T>>();
+
+			weight
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			let something = frame_support::storage::migration::get_storage_value::<u32>(
+				Pallet::<T>::name().as_bytes(),
+				b"Something",
+				&[],
+			);
+			Ok(something.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			use codec::Decode;
+
+			let something = Option::<u32>::decode(&mut &state[..])
+				.map_err(|_| "invalid pre_upgrade state")?;
+			if let (Some(something), Some(owner)) = (something, Owner::get()) {
+				if Pallet::<T>::something(owner) != Some(something) {
+					return Err("Something not moved to the owner's entry")
+				}
+			}
+			if Pallet::<T>::on_chain_storage_version() != 1 {
+				return Err("template storage version not updated")
+			}
+			Ok(())
+		}
+	}
+}
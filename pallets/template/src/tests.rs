@@ -1,5 +1,15 @@
-use crate::{mock::*, Error, Event};
-use frame_support::{assert_noop, assert_ok};
+use crate::{migrations::v1::MigrateToV1, mock::*, Error, Event};
+use codec::Encode;
+use frame_support::{
+	assert_noop, assert_ok,
+	dispatch::DispatchResult,
+	parameter_types,
+	traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+};
+
+parameter_types! {
+	pub const Owner: Option<u64> = Some(1);
+}
 
 #[test]
 fn it_works_for_default_value() {
@@ -9,12 +19,78 @@ fn it_works_for_default_value() {
 		// Dispatch a signed extrinsic.
 		assert_ok!(TemplateModule::do_something(RuntimeOrigin::signed(1), 42));
 		// Read pallet storage and assert an expected result.
-		assert_eq!(TemplateModule::something(), Some(42));
+		assert_eq!(TemplateModule::something(1), Some(42));
+		assert_eq!(TemplateModule::something(2), None);
 		// Assert that the correct event was deposited
 		System::assert_last_event(Event::SomethingStored { something: 42, who: 1 }.into());
 	});
 }
 
+#[test]
+fn something_is_a_namespace_less_entry() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TemplateModule::do_something(RuntimeOrigin::signed(1), 42));
+		assert_eq!(TemplateModule::entry(1, vec![], vec![]), Some(42u32.encode()));
+		assert_eq!(TemplateModule::entries(1, vec![]), vec![(vec![], 42u32.encode())]);
+		// 10 base + 4 bytes
+		assert_eq!(Balances::reserved_balance(1), 14);
+		assert_eq!(TemplateModule::entry_count(1), 1);
+
+		assert_ok!(TemplateModule::cause_error(RuntimeOrigin::signed(1)));
+		assert_eq!(TemplateModule::something(1), Some(43));
+		assert_eq!(Balances::reserved_balance(1), 14);
+		assert_noop!(
+			TemplateModule::cause_error(RuntimeOrigin::signed(2)),
+			Error::<Test>::NoneValue
+		);
+		assert_noop!(
+			TemplateModule::do_something(RuntimeOrigin::signed(3), 42),
+			pallet_balances::Error::<Test>::InsufficientBalance
+		);
+	});
+}
+
+#[test]
+fn migration_v1_moves_the_global_value_to_the_owner() {
+	new_test_ext().execute_with(|| {
+		let key = frame_support::storage::storage_prefix(b"TemplateModule", b"Something");
+		frame_support::storage::unhashed::put(&key, &42u32);
+		StorageVersion::new(0).put::<TemplateModule>();
+
+		MigrateToV1::<Test, Owner>::on_runtime_upgrade();
+
+		assert!(!frame_support::storage::unhashed::exists(&key));
+		assert_eq!(TemplateModule::on_chain_storage_version(), 1);
+		assert_eq!(TemplateModule::something(1), Some(42));
+		assert_eq!(TemplateModule::entry_count(1), 1);
+		// Nobody paid for the old value, so no deposit is held until the owner sets it again.
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_ok!(TemplateModule::cause_error(RuntimeOrigin::signed(1)));
+		assert_eq!(TemplateModule::something(1), Some(43));
+		assert_eq!(Balances::reserved_balance(1), 14);
+
+		// Only runs once.
+		frame_support::storage::unhashed::put(&key, &7u32);
+		MigrateToV1::<Test, Owner>::on_runtime_upgrade();
+		assert_eq!(TemplateModule::something(1), Some(43));
+	});
+}
+
+#[test]
+fn migration_v1_drops_the_global_value_without_owner() {
+	new_test_ext().execute_with(|| {
+		let key = frame_support::storage::storage_prefix(b"TemplateModule", b"Something");
+		frame_support::storage::unhashed::put(&key, &42u32);
+		StorageVersion::new(0).put::<TemplateModule>();
+
+		MigrateToV1::<Test, ()>::on_runtime_upgrade();
+
+		assert!(!frame_support::storage::unhashed::exists(&key));
+		assert_eq!(TemplateModule::on_chain_storage_version(), 1);
+		assert_eq!(TemplateModule::entry_count(1), 0);
+	});
+}
+
 #[test]
 fn correct_error_for_none_value() {
 	new_test_ext().execute_with(|| {
@@ -25,3 +101,96 @@ fn correct_error_for_none_value() {
 		);
 	});
 }
+
+fn set(who: u64, namespace: &[u8], key: &[u8], value: &[u8]) -> DispatchResult {
+	TemplateModule::set_entry(
+		RuntimeOrigin::signed(who),
+		namespace.to_vec().try_into().unwrap(),
+		key.to_vec().try_into().unwrap(),
+		value.to_vec().try_into().unwrap(),
+	)
+}
+
+fn remove(who: u64, namespace: &[u8], key: &[u8]) -> DispatchResult {
+	TemplateModule::remove_entry(
+		RuntimeOrigin::signed(who),
+		namespace.to_vec().try_into().unwrap(),
+		key.to_vec().try_into().unwrap(),
+	)
+}
+
+fn get(who: u64, namespace: &[u8], key: &[u8]) -> Option<Vec<u8>> {
+	TemplateModule::entry(who, namespace.to_vec(), key.to_vec())
+}
+
+#[test]
+fn set_entry_reserves_a_deposit() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(set(1, b"app", b"name", b"alice"));
+		// 10 base + 12 bytes
+		assert_eq!(Balances::reserved_balance(1), 22);
+		assert_eq!(get(1, b"app", b"name"), Some(b"alice".to_vec()));
+		assert_eq!(TemplateModule::entry_count(1), 1);
+		System::assert_last_event(
+			Event::EntrySet {
+				who: 1,
+				namespace: b"app".to_vec().try_into().unwrap(),
+				key: b"name".to_vec().try_into().unwrap(),
+				deposit: 22,
+			}
+			.into(),
+		);
+
+		// Updating only reserves or returns the difference.
+		assert_ok!(set(1, b"app", b"name", b"al"));
+		assert_eq!(Balances::reserved_balance(1), 19);
+		assert_ok!(set(1, b"app", b"name", b"alexandra"));
+		assert_eq!(Balances::reserved_balance(1), 26);
+		assert_eq!(TemplateModule::entry_count(1), 1);
+	});
+}
+
+#[test]
+fn entries_are_owned_and_namespaced() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(set(1, b"app", b"key", b"one"));
+		assert_ok!(set(2, b"app", b"key", b"two"));
+		assert_ok!(set(1, b"other", b"key", b"three"));
+
+		let entries = |who, namespace: &[u8]| TemplateModule::entries(who, namespace.to_vec());
+		assert_eq!(entries(1, b"app"), vec![(b"key".to_vec(), b"one".to_vec())]);
+		assert_eq!(entries(2, b"app"), vec![(b"key".to_vec(), b"two".to_vec())]);
+		assert!(entries(2, b"other").is_empty());
+		assert!(entries(2, b"namespace too long").is_empty());
+
+		// Removing only touches the caller's entry and returns its deposit.
+		assert_noop!(remove(2, b"other", b"key"), Error::<Test>::UnknownEntry);
+		assert_ok!(remove(1, b"app", b"key"));
+		assert_eq!(get(1, b"app", b"key"), None);
+		assert_eq!(get(2, b"app", b"key"), Some(b"two".to_vec()));
+		assert_eq!(TemplateModule::entry_count(1), 1);
+		assert_eq!(Balances::reserved_balance(1), 10 + 13);
+	});
+}
+
+#[test]
+fn entries_are_limited_per_account() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(set(1, b"", b"a", b""));
+		assert_ok!(set(1, b"", b"b", b""));
+		assert_noop!(set(1, b"", b"c", b""), Error::<Test>::TooManyEntries);
+		// Existing entries can still be updated.
+		assert_ok!(set(1, b"", b"a", b"new"));
+	});
+}
+
+#[test]
+fn set_entry_requires_the_deposit() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			set(3, b"app", b"key", b"value"),
+			pallet_balances::Error::<Test>::InsufficientBalance
+		);
+	});
+}
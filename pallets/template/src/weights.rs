@@ -0,0 +1,107 @@
+//! Placeholder weights for pallet_template.
+//!
+//! These were written by hand, not generated by the benchmark CLI: every call takes a flat
+//! `10_000` of execution time plus the storage accesses listed above it. Replace them with
+//! measured values by running on reference hardware:
+//!
+//! ./target/release/node-template benchmark pallet --chain dev --pallet pallet_template
+//! --extrinsic '*' --steps 50 --repeat 20 --output pallets/template/src/weights.rs
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_template.
+pub trait WeightInfo {
+	fn do_something() -> Weight;
+	fn cause_error() -> Weight;
+	fn cause_error_none_value() -> Weight;
+	fn set_entry() -> Weight;
+	fn remove_entry() -> Weight;
+}
+
+/// Placeholder weights for pallet_template, see the module docs.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// Storage: TemplateModule Entries (r:1 w:1)
+	// Storage: TemplateModule EntryCount (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn do_something() -> Weight {
+		Weight::from_ref_time(10_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(3 as u64))
+			.saturating_add(T::DbWeight::get().writes(3 as u64))
+	}
+	// Storage: TemplateModule Entries (r:2 w:1)
+	// Storage: TemplateModule EntryCount (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn cause_error() -> Weight {
+		Weight::from_ref_time(10_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(4 as u64))
+			.saturating_add(T::DbWeight::get().writes(3 as u64))
+	}
+	// Storage: TemplateModule Entries (r:1 w:0)
+	fn cause_error_none_value() -> Weight {
+		Weight::from_ref_time(10_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(1 as u64))
+	}
+	// Storage: TemplateModule Entries (r:1 w:1)
+	// Storage: TemplateModule EntryCount (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn set_entry() -> Weight {
+		Weight::from_ref_time(10_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(3 as u64))
+			.saturating_add(T::DbWeight::get().writes(3 as u64))
+	}
+	// Storage: TemplateModule Entries (r:1 w:1)
+	// Storage: TemplateModule EntryCount (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn remove_entry() -> Weight {
+		Weight::from_ref_time(10_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(3 as u64))
+			.saturating_add(T::DbWeight::get().writes(3 as u64))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	// Storage: TemplateModule Entries (r:1 w:1)
+	// Storage: TemplateModule EntryCount (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn do_something() -> Weight {
+		Weight::from_ref_time(10_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(3 as u64))
+			.saturating_add(RocksDbWeight::get().writes(3 as u64))
+	}
+	// Storage: TemplateModule Entries (r:2 w:1)
+	// Storage: TemplateModule EntryCount (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn cause_error() -> Weight {
+		Weight::from_ref_time(10_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(4 as u64))
+			.saturating_add(RocksDbWeight::get().writes(3 as u64))
+	}
+	// Storage: TemplateModule Entries (r:1 w:0)
+	fn cause_error_none_value() -> Weight {
+		Weight::from_ref_time(10_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(1 as u64))
+	}
+	// Storage: TemplateModule Entries (r:1 w:1)
+	// Storage: TemplateModule EntryCount (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn set_entry() -> Weight {
+		Weight::from_ref_time(10_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(3 as u64))
+			.saturating_add(RocksDbWeight::get().writes(3 as u64))
+	}
+	// Storage: TemplateModule Entries (r:1 w:1)
+	// Storage: TemplateModule EntryCount (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn remove_entry() -> Weight {
+		Weight::from_ref_time(10_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(3 as u64))
+			.saturating_add(RocksDbWeight::get().writes(3 as u64))
+	}
+}
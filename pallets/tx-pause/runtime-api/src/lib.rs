@@ -0,0 +1,14 @@
+//! Runtime API definition for the transaction pause pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	pub trait TxPauseApi {
+		/// The paused `(pallet, call)` names, a `None` call pauses the whole pallet.
+		fn paused_calls() -> Vec<(Vec<u8>, Option<Vec<u8>>)>;
+		/// Whether `call` of `pallet` is paused, on its own or with its pallet.
+		fn is_paused(pallet: Vec<u8>, call: Vec<u8>) -> bool;
+	}
+}
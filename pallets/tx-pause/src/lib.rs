@@ -0,0 +1,202 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Pause calls without a runtime upgrade.
+//!
+//! The pause origin can pause a single call or every call of a pallet by name, e.g.
+//! `("KittiesModule", Some("breed"))` or `("KittiesModule", None)`. The pallet is meant to be
+//! the runtime's `BaseCallFilter`, which then rejects paused calls, also when nested in batches
+//! or proxies. Whitelisted calls, such as those of `System`, can never be paused.
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{CallMetadata, Contains, GetCallMetadata},
+	};
+	use frame_system::pallet_prelude::*;
+	use sp_std::prelude::*;
+
+	/// The name of a pallet or a call, as in the runtime metadata.
+	pub type NameOf<T> = BoundedVec<u8, <T as Config>::MaxNameLen>;
+
+	/// A pallet name with a call name, or without one for all calls of the pallet.
+	pub type FullNameOf<T> = (NameOf<T>, Option<NameOf<T>>);
+
+	#[pallet::pallet]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// The runtime call type, to look up pallet and call names.
+		type RuntimeCall: Parameter
+			+ GetCallMetadata
+			+ IsType<<Self as frame_system::Config>::RuntimeCall>;
+
+		/// The origin allowed to pause calls.
+		type PauseOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// The origin allowed to unpause calls.
+		type UnpauseOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Pallets and calls that can never be paused.
+		type WhitelistedCalls: Contains<FullNameOf<Self>>;
+
+		/// The maximum length of a pallet or call name.
+		#[pallet::constant]
+		type MaxNameLen: Get<u32>;
+	}
+
+	// 被暂停的调用, 调用名为空时暂停整个 pallet
+	#[pallet::storage]
+	pub type PausedCalls<T: Config> = StorageMap<_, Blake2_128Concat, FullNameOf<T>, ()>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A call, or all calls of a pallet, were paused.
+		CallPaused { pallet: NameOf<T>, call: Option<NameOf<T>> },
+		/// A call, or all calls of a pallet, were unpaused.
+		CallUnpaused { pallet: NameOf<T>, call: Option<NameOf<T>> },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		UnknownPallet,
+		UnknownCall,
+		Unpausable,
+		IsPaused,
+		IsUnpaused,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		#[cfg(feature = "try-runtime")]
+		fn try_state(_n: BlockNumberFor<T>) -> Result<(), &'static str> {
+			Self::do_try_state()
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Pause `call` of `pallet`, or all calls of `pallet` if `call` is `None`.
+		#[pallet::call_index(0)]
+		#[pallet::weight({0})]
+		pub fn pause(
+			origin: OriginFor<T>,
+			pallet: NameOf<T>,
+			call: Option<NameOf<T>>,
+		) -> DispatchResult {
+			T::PauseOrigin::ensure_origin(origin)?;
+
+			let full_name = (pallet, call);
+			Self::ensure_pausable(&full_name)?;
+			ensure!(!PausedCalls::<T>::contains_key(&full_name), Error::<T>::IsPaused);
+			PausedCalls::<T>::insert(&full_name, ());
+
+			let (pallet, call) = full_name;
+			Self::deposit_event(Event::CallPaused { pallet, call });
+			Ok(())
+		}
+
+		/// Unpause what `pause` paused with the same arguments.
+		#[pallet::call_index(1)]
+		#[pallet::weight({0})]
+		pub fn unpause(
+			origin: OriginFor<T>,
+			pallet: NameOf<T>,
+			call: Option<NameOf<T>>,
+		) -> DispatchResult {
+			T::UnpauseOrigin::ensure_origin(origin)?;
+
+			let full_name = (pallet, call);
+			ensure!(PausedCalls::<T>::contains_key(&full_name), Error::<T>::IsUnpaused);
+			PausedCalls::<T>::remove(&full_name);
+
+			let (pallet, call) = full_name;
+			Self::deposit_event(Event::CallUnpaused { pallet, call });
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Whether `call` of `pallet` is paused, on its own or with its pallet.
+		pub fn is_paused(pallet: &[u8], call: &[u8]) -> bool {
+			// 名字超长的调用不可能被暂停
+			let pallet = match NameOf::<T>::try_from(pallet.to_vec()) {
+				Ok(pallet) => pallet,
+				Err(_) => return false,
+			};
+			if PausedCalls::<T>::contains_key((pallet.clone(), None::<NameOf<T>>)) {
+				return true
+			}
+			NameOf::<T>::try_from(call.to_vec())
+				.map_or(false, |call| PausedCalls::<T>::contains_key((pallet, Some(call))))
+		}
+
+		/// The paused `(pallet, call)` names, for the runtime API.
+		pub fn paused_calls() -> Vec<(Vec<u8>, Option<Vec<u8>>)> {
+			PausedCalls::<T>::iter_keys()
+				.map(|(pallet, call)| (pallet.into_inner(), call.map(|call| call.into_inner())))
+				.collect()
+		}
+
+		fn ensure_pausable(full_name: &FullNameOf<T>) -> DispatchResult {
+			let (pallet, call) = full_name;
+			let pallet_name =
+				sp_std::str::from_utf8(pallet).map_err(|_| Error::<T>::UnknownPallet)?;
+			let pallet_names = <T as Config>::RuntimeCall::get_module_names();
+			ensure!(
+				pallet_names.iter().any(|name| *name == pallet_name),
+				Error::<T>::UnknownPallet
+			);
+			ensure!(!T::WhitelistedCalls::contains(full_name), Error::<T>::Unpausable);
+
+			let call_names = <T as Config>::RuntimeCall::get_call_names(pallet_name);
+			match call {
+				Some(call) => {
+					let call = sp_std::str::from_utf8(call).map_err(|_| Error::<T>::UnknownCall)?;
+					ensure!(call_names.iter().any(|name| *name == call), Error::<T>::UnknownCall);
+				},
+				// 暂停整个 pallet 时, 其中不能有白名单里的调用
+				None =>
+					for name in call_names {
+						if let Ok(name) = NameOf::<T>::try_from(name.as_bytes().to_vec()) {
+							ensure!(
+								!T::WhitelistedCalls::contains(&(pallet.clone(), Some(name))),
+								Error::<T>::Unpausable
+							);
+						}
+					},
+			}
+			Ok(())
+		}
+
+		/// Check the storage invariants of the pallet.
+		#[cfg(any(feature = "try-runtime", test))]
+		pub fn do_try_state() -> Result<(), &'static str> {
+			for full_name in PausedCalls::<T>::iter_keys() {
+				ensure!(!T::WhitelistedCalls::contains(&full_name), "whitelisted call paused");
+			}
+
+			Ok(())
+		}
+	}
+
+	// 作为 BaseCallFilter 使用, 放行没有被暂停的调用
+	impl<T: Config> Contains<<T as Config>::RuntimeCall> for Pallet<T> {
+		fn contains(call: &<T as Config>::RuntimeCall) -> bool {
+			let CallMetadata { pallet_name, function_name } = call.get_call_metadata();
+			!Self::is_paused(pallet_name.as_bytes(), function_name.as_bytes())
+		}
+	}
+}
@@ -0,0 +1,166 @@
+use crate::{mock::*, Error, Event, NameOf};
+use frame_support::{assert_noop, assert_ok, dispatch::DispatchResult};
+use sp_runtime::{traits::Dispatchable, DispatchError};
+
+fn name(name: &str) -> NameOf<Test> {
+	name.as_bytes().to_vec().try_into().unwrap()
+}
+
+fn transfer(value: u64) -> RuntimeCall {
+	RuntimeCall::Balances(pallet_balances::Call::transfer { dest: 2, value })
+}
+
+fn transfer_keep_alive(value: u64) -> RuntimeCall {
+	RuntimeCall::Balances(pallet_balances::Call::transfer_keep_alive { dest: 2, value })
+}
+
+fn dispatch(call: RuntimeCall) -> DispatchResult {
+	call.dispatch(RuntimeOrigin::signed(1)).map(|_| ()).map_err(|e| e.error)
+}
+
+fn call_filtered() -> DispatchResult {
+	Err(frame_system::Error::<Test>::CallFiltered.into())
+}
+
+#[test]
+fn paused_call_is_filtered() {
+	build_and_execute(|| {
+		assert_ok!(TxPause::pause(RuntimeOrigin::root(), name("Balances"), Some(name("transfer"))));
+		System::assert_last_event(
+			Event::CallPaused { pallet: name("Balances"), call: Some(name("transfer")) }.into(),
+		);
+
+		assert_eq!(dispatch(transfer(10)), call_filtered());
+		assert_ok!(dispatch(transfer_keep_alive(10)));
+		assert_eq!(Balances::free_balance(2), 110);
+		assert!(TxPause::is_paused(b"Balances", b"transfer"));
+		assert!(!TxPause::is_paused(b"Balances", b"transfer_keep_alive"));
+	});
+}
+
+#[test]
+fn unpaused_call_goes_through_again() {
+	build_and_execute(|| {
+		assert_ok!(TxPause::pause(RuntimeOrigin::root(), name("Balances"), Some(name("transfer"))));
+		assert_ok!(TxPause::unpause(
+			RuntimeOrigin::root(),
+			name("Balances"),
+			Some(name("transfer"))
+		));
+		System::assert_last_event(
+			Event::CallUnpaused { pallet: name("Balances"), call: Some(name("transfer")) }.into(),
+		);
+
+		assert_ok!(dispatch(transfer(10)));
+		assert_eq!(TxPause::paused_calls(), vec![]);
+	});
+}
+
+#[test]
+fn paused_pallet_filters_all_its_calls() {
+	build_and_execute(|| {
+		assert_ok!(TxPause::pause(RuntimeOrigin::root(), name("Balances"), None));
+		System::assert_last_event(
+			Event::CallPaused { pallet: name("Balances"), call: None }.into(),
+		);
+
+		assert_eq!(dispatch(transfer(10)), call_filtered());
+		assert_eq!(dispatch(transfer_keep_alive(10)), call_filtered());
+		assert!(TxPause::is_paused(b"Balances", b"set_balance"));
+		assert!(!TxPause::is_paused(b"System", b"remark"));
+		assert_eq!(TxPause::paused_calls(), vec![(b"Balances".to_vec(), None)]);
+	});
+}
+
+#[test]
+fn pallet_with_whitelisted_calls_cannot_be_paused() {
+	build_and_execute(|| {
+		whitelist_transfer_keep_alive();
+		assert_noop!(
+			TxPause::pause(RuntimeOrigin::root(), name("Balances"), None),
+			Error::<Test>::Unpausable
+		);
+		assert_noop!(
+			TxPause::pause(
+				RuntimeOrigin::root(),
+				name("Balances"),
+				Some(name("transfer_keep_alive"))
+			),
+			Error::<Test>::Unpausable
+		);
+		assert_ok!(TxPause::pause(RuntimeOrigin::root(), name("Balances"), Some(name("transfer"))));
+	});
+}
+
+#[test]
+fn system_and_tx_pause_cannot_be_paused() {
+	build_and_execute(|| {
+		assert_noop!(
+			TxPause::pause(RuntimeOrigin::root(), name("System"), None),
+			Error::<Test>::Unpausable
+		);
+		assert_noop!(
+			TxPause::pause(RuntimeOrigin::root(), name("System"), Some(name("remark"))),
+			Error::<Test>::Unpausable
+		);
+		assert_noop!(
+			TxPause::pause(RuntimeOrigin::root(), name("TxPause"), Some(name("unpause"))),
+			Error::<Test>::Unpausable
+		);
+	});
+}
+
+#[test]
+fn names_must_exist() {
+	build_and_execute(|| {
+		assert_noop!(
+			TxPause::pause(RuntimeOrigin::root(), name("Kitties"), None),
+			Error::<Test>::UnknownPallet
+		);
+		assert_noop!(
+			TxPause::pause(RuntimeOrigin::root(), name("Balances"), Some(name("steal"))),
+			Error::<Test>::UnknownCall
+		);
+	});
+}
+
+#[test]
+fn pause_and_unpause_are_not_repeated() {
+	build_and_execute(|| {
+		assert_noop!(
+			TxPause::unpause(RuntimeOrigin::root(), name("Balances"), Some(name("transfer"))),
+			Error::<Test>::IsUnpaused
+		);
+		assert_ok!(TxPause::pause(RuntimeOrigin::root(), name("Balances"), Some(name("transfer"))));
+		assert_noop!(
+			TxPause::pause(RuntimeOrigin::root(), name("Balances"), Some(name("transfer"))),
+			Error::<Test>::IsPaused
+		);
+	});
+}
+
+#[test]
+fn only_the_pause_origin_pauses() {
+	build_and_execute(|| {
+		assert_noop!(
+			TxPause::pause(RuntimeOrigin::signed(1), name("Balances"), Some(name("transfer"))),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(TxPause::pause(RuntimeOrigin::root(), name("Balances"), Some(name("transfer"))));
+		assert_noop!(
+			TxPause::unpause(RuntimeOrigin::signed(1), name("Balances"), Some(name("transfer"))),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn root_is_not_filtered() {
+	build_and_execute(|| {
+		assert_ok!(TxPause::pause(RuntimeOrigin::root(), name("Balances"), None));
+
+		let call = pallet_balances::Call::force_transfer { source: 1, dest: 2, value: 10 };
+		assert_ok!(RuntimeCall::Balances(call).dispatch(RuntimeOrigin::root()));
+		assert_eq!(Balances::free_balance(2), 110);
+	});
+}
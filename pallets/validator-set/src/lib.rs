@@ -0,0 +1,301 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Validators managed by governance on top of an elected validator set.
+//!
+//! The pallet sits between `pallet_session` and the session manager electing validators (e.g.
+//! `pallet_staking`): every new session it takes the elected set and adds the validators that
+//! governance approved with [`Pallet::add_validator`]. Added and removed validators take part
+//! from the next session on, and added validators that are reported offline by `im-online` are
+//! removed automatically. Validators elected by the inner session manager are managed by it.
+//!
+//! Added validators set their session keys like any other validator once
+//! [`ValidatorOf`] is used as the session's `ValidatorIdOf`.
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+	use pallet_im_online::UnresponsivenessOffence;
+	use pallet_session::historical::{self, IdentificationTuple};
+	use sp_runtime::traits::Convert;
+	use sp_staking::{
+		offence::{OffenceError, ReportOffence},
+		SessionIndex,
+	};
+	use sp_std::prelude::*;
+
+	#[pallet::pallet]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + historical::Config {
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// The origin allowed to add and remove validators.
+		type AddRemoveOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// The session manager electing validators, whose set is extended by this pallet.
+		type Elected: historical::SessionManager<Self::ValidatorId, Self::FullIdentification>;
+
+		/// Where `im-online` reports are forwarded to after removing offline validators.
+		type ReportUnresponsiveness: ReportOffence<
+			Self::AccountId,
+			IdentificationTuple<Self>,
+			UnresponsivenessOffence<IdentificationTuple<Self>>,
+		>;
+
+		/// The maximum number of validators governance can add.
+		#[pallet::constant]
+		type MaxValidators: Get<u32>;
+
+		/// The maximum number of validators `Elected` elects, e.g. its `MaxWinners`. Further
+		/// validators still join the next session, but not the ones recomputed after a change.
+		#[pallet::constant]
+		type MaxElected: Get<u32>;
+	}
+
+	// 治理添加的验证人
+	#[pallet::storage]
+	#[pallet::getter(fn validators)]
+	pub type Validators<T: Config> =
+		StorageValue<_, BoundedVec<T::ValidatorId, T::MaxValidators>, ValueQuery>;
+
+	// 内层会话管理器最近一次选出的验证人, 只记录 id
+	#[pallet::storage]
+	pub type Elected<T: Config> =
+		StorageValue<_, BoundedVec<T::ValidatorId, T::MaxElected>, ValueQuery>;
+
+	// 验证人有变动, 下一个会话需要重新计算验证人集合
+	#[pallet::storage]
+	pub type SetChanged<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A validator was added, it takes part from the next session on.
+		ValidatorAdded { validator: T::ValidatorId },
+		/// A validator was removed, it leaves with the next session.
+		ValidatorRemoved { validator: T::ValidatorId },
+		/// A validator was reported offline and removed.
+		OfflineValidatorRemoved { validator: T::ValidatorId },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		AlreadyValidator,
+		NotValidator,
+		TooManyValidators,
+	}
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// Validators added at genesis, on top of the elected ones.
+		pub validators: Vec<T::ValidatorId>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { validators: Default::default() }
+		}
+	}
+
+	// 必须在 pallet_session 之前构建, 会话创世时会读取这里的验证人
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			let mut validators = BoundedVec::<T::ValidatorId, T::MaxValidators>::default();
+			for validator in &self.validators {
+				assert!(!validators.contains(validator), "duplicate validator in genesis config");
+				validators
+					.try_push(validator.clone())
+					.expect("genesis validators exceed MaxValidators");
+			}
+			Validators::<T>::put(validators);
+		}
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		#[cfg(feature = "try-runtime")]
+		fn try_state(_n: BlockNumberFor<T>) -> Result<(), &'static str> {
+			Self::do_try_state()
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Add a validator from the next session on.
+		#[pallet::call_index(0)]
+		#[pallet::weight({0})]
+		pub fn add_validator(origin: OriginFor<T>, validator: T::ValidatorId) -> DispatchResult {
+			T::AddRemoveOrigin::ensure_origin(origin)?;
+
+			Validators::<T>::try_mutate(|validators| {
+				ensure!(!validators.contains(&validator), Error::<T>::AlreadyValidator);
+				validators.try_push(validator.clone()).map_err(|_| Error::<T>::TooManyValidators)
+			})?;
+			SetChanged::<T>::put(true);
+
+			Self::deposit_event(Event::ValidatorAdded { validator });
+			Ok(())
+		}
+
+		/// Remove a validator added with `add_validator` from the next session on.
+		#[pallet::call_index(1)]
+		#[pallet::weight({0})]
+		pub fn remove_validator(origin: OriginFor<T>, validator: T::ValidatorId) -> DispatchResult {
+			T::AddRemoveOrigin::ensure_origin(origin)?;
+
+			Validators::<T>::try_mutate(|validators| {
+				let index = validators
+					.iter()
+					.position(|v| v == &validator)
+					.ok_or(Error::<T>::NotValidator)?;
+				validators.remove(index);
+				Ok::<_, Error<T>>(())
+			})?;
+			SetChanged::<T>::put(true);
+
+			Self::deposit_event(Event::ValidatorRemoved { validator });
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Extend the elected validators, `None` if the set stays the same.
+		fn extend_elected(
+			elected: Option<Vec<IdentificationTuple<T>>>,
+		) -> Option<Vec<IdentificationTuple<T>>> {
+			let changed = SetChanged::<T>::take();
+			let mut validators = match elected {
+				Some(elected) => {
+					let ids = elected.iter().map(|(v, _)| v.clone()).collect();
+					Elected::<T>::put(BoundedVec::truncate_from(ids));
+					elected
+				},
+				// 选举结果没变, 用最近一次选出的验证人重新计算
+				None if changed => Elected::<T>::get()
+					.into_iter()
+					.filter_map(|v| T::FullIdentificationOf::convert(v.clone()).map(|id| (v, id)))
+					.collect(),
+				None => return None,
+			};
+
+			for validator in Validators::<T>::get() {
+				if validators.iter().any(|(v, _)| v == &validator) {
+					continue
+				}
+				match T::FullIdentificationOf::convert(validator.clone()) {
+					Some(id) => validators.push((validator, id)),
+					// 身份暂时未知 (例如创世时 staking 还没有开始 era), 下个会话再试
+					None => SetChanged::<T>::put(true),
+				}
+			}
+
+			// 宁可保留当前的验证人, 也不能让链停下来
+			(!validators.is_empty()).then_some(validators)
+		}
+
+		fn remove_offline(offenders: &[IdentificationTuple<T>]) {
+			let mut removed = Vec::new();
+			Validators::<T>::mutate(|validators| {
+				validators.retain(|v| {
+					let offline = offenders.iter().any(|(offender, _)| offender == v);
+					if offline {
+						removed.push(v.clone());
+					}
+					!offline
+				})
+			});
+			if removed.is_empty() {
+				return
+			}
+
+			SetChanged::<T>::put(true);
+			for validator in removed {
+				Self::deposit_event(Event::OfflineValidatorRemoved { validator });
+			}
+		}
+
+		/// Check the storage invariants of the pallet.
+		#[cfg(any(feature = "try-runtime", test))]
+		pub fn do_try_state() -> Result<(), &'static str> {
+			let validators = Validators::<T>::get();
+			for (i, validator) in validators.iter().enumerate() {
+				ensure!(!validators[..i].contains(validator), "validator added twice");
+			}
+
+			Ok(())
+		}
+	}
+
+	impl<T: Config> historical::SessionManager<T::ValidatorId, T::FullIdentification>
+		for Pallet<T>
+	{
+		fn new_session(new_index: SessionIndex) -> Option<Vec<IdentificationTuple<T>>> {
+			Self::extend_elected(T::Elected::new_session(new_index))
+		}
+
+		fn new_session_genesis(new_index: SessionIndex) -> Option<Vec<IdentificationTuple<T>>> {
+			Self::extend_elected(T::Elected::new_session_genesis(new_index))
+		}
+
+		fn start_session(start_index: SessionIndex) {
+			T::Elected::start_session(start_index)
+		}
+
+		fn end_session(end_index: SessionIndex) {
+			T::Elected::end_session(end_index)
+		}
+	}
+
+	// im-online 报告的离线验证人如果是治理添加的, 直接移除
+	impl<T: Config>
+		ReportOffence<
+			T::AccountId,
+			IdentificationTuple<T>,
+			UnresponsivenessOffence<IdentificationTuple<T>>,
+		> for Pallet<T>
+	{
+		fn report_offence(
+			reporters: Vec<T::AccountId>,
+			offence: UnresponsivenessOffence<IdentificationTuple<T>>,
+		) -> Result<(), OffenceError> {
+			let offenders = offence.offenders.clone();
+			T::ReportUnresponsiveness::report_offence(reporters, offence)?;
+			Self::remove_offline(&offenders);
+			Ok(())
+		}
+
+		fn is_known_offence(
+			offenders: &[IdentificationTuple<T>],
+			time_slot: &SessionIndex,
+		) -> bool {
+			T::ReportUnresponsiveness::is_known_offence(offenders, time_slot)
+		}
+	}
+
+	/// Map accounts to validators like `I`, and added validators to themselves.
+	pub struct ValidatorOf<T, I>(PhantomData<(T, I)>);
+
+	impl<T: Config, I: Convert<T::AccountId, Option<T::ValidatorId>>>
+		Convert<T::AccountId, Option<T::ValidatorId>> for ValidatorOf<T, I>
+	{
+		fn convert(account: T::AccountId) -> Option<T::ValidatorId> {
+			I::convert(account.clone()).or_else(|| {
+				T::ValidatorId::try_from(account)
+					.ok()
+					.filter(|validator| Validators::<T>::get().contains(validator))
+			})
+		}
+	}
+}
@@ -0,0 +1,193 @@
+use crate as pallet_validator_set;
+use frame_support::traits::{ConstU16, ConstU32, ConstU64, GenesisBuild};
+use frame_system::EnsureRoot;
+use pallet_im_online::UnresponsivenessOffence;
+use pallet_session::historical::IdentificationTuple;
+use sp_core::H256;
+use sp_runtime::{
+	key_types::DUMMY,
+	testing::{Header, UintAuthorityId},
+	traits::{BlakeTwo256, Convert, IdentityLookup, OpaqueKeys},
+	KeyTypeId,
+};
+use sp_staking::{
+	offence::{OffenceError, ReportOffence},
+	SessionIndex,
+};
+use std::cell::RefCell;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system,
+		ValidatorSet: pallet_validator_set,
+		Historical: pallet_session::historical::{Pallet},
+		Session: pallet_session,
+	}
+);
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ConstU16<42>;
+	type OnSetCode = ();
+	type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+/// The exposure of every elected validator, added validators have none.
+pub const ELECTED_EXPOSURE: u64 = 100;
+
+thread_local! {
+	// 模拟 staking: 下一次选举的结果, 以及收到的离线报告
+	static ELECTION: RefCell<Option<Vec<u64>>> = RefCell::new(None);
+	static STAKERS: RefCell<Vec<u64>> = RefCell::new(Vec::new());
+	static REPORTED: RefCell<Vec<Vec<u64>>> = RefCell::new(Vec::new());
+}
+
+/// Elect `validators` at the next new session, like staking does at the start of an era.
+pub fn elect(validators: Vec<u64>) {
+	STAKERS.with(|stakers| stakers.borrow_mut().extend(validators.iter().copied()));
+	ELECTION.with(|election| *election.borrow_mut() = Some(validators));
+}
+
+/// The validators of every offline report forwarded by the pallet.
+pub fn reported() -> Vec<Vec<u64>> {
+	REPORTED.with(|reported| reported.borrow().clone())
+}
+
+pub struct MockElection;
+
+impl pallet_session::historical::SessionManager<u64, u64> for MockElection {
+	fn new_session(_: SessionIndex) -> Option<Vec<(u64, u64)>> {
+		ELECTION.with(|election| election.borrow_mut().take()).map(|validators| {
+			validators.into_iter().map(|v| (v, ELECTED_EXPOSURE)).collect()
+		})
+	}
+	fn start_session(_: SessionIndex) {}
+	fn end_session(_: SessionIndex) {}
+}
+
+pub struct StakerOf;
+
+impl Convert<u64, Option<u64>> for StakerOf {
+	fn convert(account: u64) -> Option<u64> {
+		STAKERS.with(|stakers| stakers.borrow().contains(&account)).then_some(account)
+	}
+}
+
+pub struct ExposureOf;
+
+impl Convert<u64, Option<u64>> for ExposureOf {
+	fn convert(_: u64) -> Option<u64> {
+		Some(0)
+	}
+}
+
+type Offence = UnresponsivenessOffence<IdentificationTuple<Test>>;
+
+pub struct MockOffences;
+
+impl ReportOffence<u64, IdentificationTuple<Test>, Offence> for MockOffences {
+	fn report_offence(_: Vec<u64>, offence: Offence) -> Result<(), OffenceError> {
+		let offenders = offence.offenders.iter().map(|(v, _)| *v).collect();
+		REPORTED.with(|reported| reported.borrow_mut().push(offenders));
+		Ok(())
+	}
+
+	fn is_known_offence(_: &[IdentificationTuple<Test>], _: &SessionIndex) -> bool {
+		false
+	}
+}
+
+pub struct TestSessionHandler;
+
+impl pallet_session::SessionHandler<u64> for TestSessionHandler {
+	const KEY_TYPE_IDS: &'static [KeyTypeId] = &[DUMMY];
+
+	fn on_genesis_session<Ks: OpaqueKeys>(_: &[(u64, Ks)]) {}
+	fn on_new_session<Ks: OpaqueKeys>(_: bool, _: &[(u64, Ks)], _: &[(u64, Ks)]) {}
+	fn on_disabled(_: u32) {}
+}
+
+impl pallet_session::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type ValidatorId = u64;
+	type ValidatorIdOf = pallet_validator_set::ValidatorOf<Test, StakerOf>;
+	type ShouldEndSession = pallet_session::PeriodicSessions<ConstU64<10>, ConstU64<0>>;
+	type NextSessionRotation = pallet_session::PeriodicSessions<ConstU64<10>, ConstU64<0>>;
+	type SessionManager = pallet_session::historical::NoteHistoricalRoot<Test, ValidatorSet>;
+	type SessionHandler = TestSessionHandler;
+	type Keys = UintAuthorityId;
+	type WeightInfo = ();
+}
+
+impl pallet_session::historical::Config for Test {
+	type FullIdentification = u64;
+	type FullIdentificationOf = ExposureOf;
+}
+
+impl pallet_validator_set::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type AddRemoveOrigin = EnsureRoot<u64>;
+	type Elected = MockElection;
+	type ReportUnresponsiveness = MockOffences;
+	type MaxValidators = ConstU32<4>;
+	type MaxElected = ConstU32<3>;
+}
+
+/// Build genesis storage with validators 1 and 2 elected and validator 3 added by governance.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_validator_set::GenesisConfig::<Test> { validators: vec![3] }
+		.assimilate_storage(&mut storage)
+		.unwrap();
+	elect(vec![1, 2]);
+	pallet_session::GenesisConfig::<Test> {
+		keys: (1..=5).map(|i| (i, i, UintAuthorityId(i))).collect(),
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// Run `test` in a fresh externality and check the pallet's storage invariants afterwards.
+pub fn build_and_execute(test: impl FnOnce()) {
+	new_test_ext().execute_with(|| {
+		test();
+		ValidatorSet::do_try_state().expect("storage invariants violated");
+	});
+}
+
+/// The validators queued for the next session.
+pub fn queued() -> Vec<u64> {
+	Session::queued_keys().into_iter().map(|(v, _)| v).collect()
+}
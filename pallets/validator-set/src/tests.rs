@@ -0,0 +1,165 @@
+use crate::{mock::*, Elected, Error, Event, Validators};
+use frame_support::{assert_noop, assert_ok};
+use pallet_im_online::UnresponsivenessOffence;
+use sp_runtime::{testing::UintAuthorityId, traits::Convert, DispatchError};
+use sp_staking::offence::ReportOffence;
+
+type ValidatorOf = crate::ValidatorOf<Test, StakerOf>;
+
+fn report_offline(offenders: Vec<u64>) {
+	let offence = UnresponsivenessOffence {
+		session_index: Session::current_index(),
+		validator_set_count: Session::validators().len() as u32,
+		offenders: offenders.into_iter().map(|v| (v, 0)).collect(),
+	};
+	assert_ok!(ValidatorSet::report_offence(vec![], offence));
+}
+
+#[test]
+fn genesis_extends_the_elected_validators() {
+	build_and_execute(|| {
+		assert_eq!(Session::validators(), vec![1, 2, 3]);
+		assert_eq!(queued(), vec![1, 2, 3]);
+	});
+}
+
+#[test]
+fn added_validator_joins_at_the_next_session() {
+	build_and_execute(|| {
+		assert_ok!(ValidatorSet::add_validator(RuntimeOrigin::root(), 4));
+		System::assert_last_event(Event::ValidatorAdded { validator: 4 }.into());
+		assert_eq!(Session::validators(), vec![1, 2, 3]);
+
+		Session::rotate_session();
+		assert_eq!(queued(), vec![1, 2, 3, 4]);
+		Session::rotate_session();
+		assert_eq!(Session::validators(), vec![1, 2, 3, 4]);
+	});
+}
+
+#[test]
+fn removed_validator_leaves_at_the_next_session() {
+	build_and_execute(|| {
+		assert_ok!(ValidatorSet::remove_validator(RuntimeOrigin::root(), 3));
+		System::assert_last_event(Event::ValidatorRemoved { validator: 3 }.into());
+
+		Session::rotate_session();
+		assert_eq!(queued(), vec![1, 2]);
+	});
+}
+
+#[test]
+fn only_the_add_remove_origin_manages_validators() {
+	build_and_execute(|| {
+		assert_noop!(
+			ValidatorSet::add_validator(RuntimeOrigin::signed(4), 4),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			ValidatorSet::remove_validator(RuntimeOrigin::signed(3), 3),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn add_and_remove_validate_the_set() {
+	build_and_execute(|| {
+		assert_noop!(
+			ValidatorSet::add_validator(RuntimeOrigin::root(), 3),
+			Error::<Test>::AlreadyValidator
+		);
+		assert_noop!(
+			ValidatorSet::remove_validator(RuntimeOrigin::root(), 1),
+			Error::<Test>::NotValidator
+		);
+
+		for validator in 4..=6 {
+			assert_ok!(ValidatorSet::add_validator(RuntimeOrigin::root(), validator));
+		}
+		assert_noop!(
+			ValidatorSet::add_validator(RuntimeOrigin::root(), 7),
+			Error::<Test>::TooManyValidators
+		);
+	});
+}
+
+#[test]
+fn new_elections_are_extended() {
+	build_and_execute(|| {
+		elect(vec![2, 5]);
+		Session::rotate_session();
+		assert_eq!(queued(), vec![2, 5, 3]);
+
+		// 选举结果不变时沿用上一次的结果
+		assert_ok!(ValidatorSet::add_validator(RuntimeOrigin::root(), 1));
+		Session::rotate_session();
+		assert_eq!(queued(), vec![2, 5, 3, 1]);
+	});
+}
+
+#[test]
+fn elected_validators_beyond_max_elected_are_not_kept() {
+	build_and_execute(|| {
+		elect(vec![1, 2, 4, 5]);
+		Session::rotate_session();
+		assert_eq!(queued(), vec![1, 2, 4, 5, 3]);
+		assert_eq!(Elected::<Test>::get().into_inner(), vec![1, 2, 4]);
+
+		// 重新计算时只剩下记录的验证人
+		assert_ok!(ValidatorSet::remove_validator(RuntimeOrigin::root(), 3));
+		Session::rotate_session();
+		assert_eq!(queued(), vec![1, 2, 4]);
+	});
+}
+
+#[test]
+fn validators_both_elected_and_added_appear_once() {
+	build_and_execute(|| {
+		assert_ok!(ValidatorSet::add_validator(RuntimeOrigin::root(), 2));
+		Session::rotate_session();
+		assert_eq!(queued(), vec![1, 2, 3]);
+	});
+}
+
+#[test]
+fn offline_added_validators_are_removed() {
+	build_and_execute(|| {
+		assert_ok!(ValidatorSet::add_validator(RuntimeOrigin::root(), 4));
+
+		report_offline(vec![2, 3]);
+		assert_eq!(reported(), vec![vec![2, 3]]);
+		assert_eq!(ValidatorSet::validators().into_inner(), vec![4]);
+		System::assert_last_event(Event::OfflineValidatorRemoved { validator: 3 }.into());
+
+		// 被选出的验证人由 staking 处理
+		Session::rotate_session();
+		assert_eq!(queued(), vec![1, 2, 4]);
+	});
+}
+
+#[test]
+fn the_set_is_kept_when_nobody_is_left() {
+	build_and_execute(|| {
+		elect(vec![]);
+		Session::rotate_session();
+		assert_eq!(queued(), vec![3]);
+
+		report_offline(vec![3]);
+		assert!(Validators::<Test>::get().is_empty());
+		Session::rotate_session();
+		assert_eq!(queued(), vec![3]);
+	});
+}
+
+#[test]
+fn added_validators_can_set_keys() {
+	build_and_execute(|| {
+		assert_eq!(ValidatorOf::convert(4), None);
+		assert_ok!(ValidatorSet::add_validator(RuntimeOrigin::root(), 4));
+		assert_eq!(ValidatorOf::convert(4), Some(4));
+		assert_eq!(ValidatorOf::convert(1), Some(1));
+
+		assert_ok!(Session::set_keys(RuntimeOrigin::signed(4), UintAuthorityId(40), vec![]));
+	});
+}
@@ -0,0 +1,535 @@
+//! Auxiliary types of the runtime configuration.
+
+use crate::{
+	AccountId, Authorship, Balance, Balances, ExtrinsicBaseWeight, Runtime, RuntimeCall, Treasury,
+	TreasuryFeeShare, TRANSFER_FEE,
+};
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{
+	traits::{Contains, Currency, Imbalance, InstanceFilter, OnUnbalanced},
+	weights::{Weight, WeightToFeeCoefficient, WeightToFeeCoefficients, WeightToFeePolynomial},
+};
+use pallet_balances::WeightInfo;
+use smallvec::smallvec;
+use sp_runtime::{Perbill, RuntimeDebug};
+
+type NegativeImbalance = <Balances as Currency<AccountId>>::NegativeImbalance;
+
+/// Credit the block author, burning the funds if there is none.
+pub struct ToAuthor;
+
+impl OnUnbalanced<NegativeImbalance> for ToAuthor {
+	fn on_nonzero_unbalanced(amount: NegativeImbalance) {
+		if let Some(author) = Authorship::author() {
+			Balances::resolve_creating(&author, amount);
+		}
+	}
+}
+
+/// Split transaction fees between the treasury and the block author by [`TreasuryFeeShare`].
+/// Tips go to the author in full.
+pub struct DealWithFees;
+
+impl OnUnbalanced<NegativeImbalance> for DealWithFees {
+	fn on_unbalanceds<B>(mut fees_then_tips: impl Iterator<Item = NegativeImbalance>) {
+		if let Some(fees) = fees_then_tips.next() {
+			let treasury_share = TreasuryFeeShare::get();
+			let (to_treasury, mut to_author) = fees.ration(
+				treasury_share.deconstruct().into(),
+				treasury_share.left_from_one().deconstruct().into(),
+			);
+			if let Some(tips) = fees_then_tips.next() {
+				tips.merge_into(&mut to_author);
+			}
+			Treasury::on_unbalanced(to_treasury);
+			ToAuthor::on_unbalanced(to_author);
+		}
+	}
+}
+
+/// The weight of a balance transfer, including the base weight of any extrinsic.
+pub fn transfer_weight() -> Weight {
+	ExtrinsicBaseWeight::get()
+		.saturating_add(<Runtime as pallet_balances::Config>::WeightInfo::transfer())
+}
+
+/// Charge fees linearly in weight, such that a balance transfer costs [`TRANSFER_FEE`].
+pub struct WeightToFee;
+
+impl WeightToFeePolynomial for WeightToFee {
+	type Balance = Balance;
+
+	fn polynomial() -> WeightToFeeCoefficients<Self::Balance> {
+		let p = TRANSFER_FEE;
+		let q = Balance::from(transfer_weight().ref_time());
+		smallvec![WeightToFeeCoefficient {
+			degree: 1,
+			negative: false,
+			coeff_frac: Perbill::from_rational(p % q, q),
+			coeff_integer: p / q,
+		}]
+	}
+}
+
+/// The calls a proxy may make on behalf of the account it proxies for.
+#[derive(
+	Copy,
+	Clone,
+	Eq,
+	PartialEq,
+	Ord,
+	PartialOrd,
+	Encode,
+	Decode,
+	RuntimeDebug,
+	MaxEncodedLen,
+	scale_info::TypeInfo,
+)]
+pub enum ProxyType {
+	/// Any call.
+	Any,
+	/// Kitty calls only, e.g. for a minting account.
+	Kitties,
+	/// Proof of existence calls only.
+	Poe,
+}
+
+impl Default for ProxyType {
+	fn default() -> Self {
+		Self::Any
+	}
+}
+
+impl InstanceFilter<RuntimeCall> for ProxyType {
+	fn filter(&self, c: &RuntimeCall) -> bool {
+		match self {
+			ProxyType::Any => true,
+			// Batches go through, their calls are filtered one by one when dispatched.
+			ProxyType::Kitties =>
+				matches!(c, RuntimeCall::KittiesModule(..) | RuntimeCall::Utility(..)),
+			ProxyType::Poe => matches!(c, RuntimeCall::PoeModule(..) | RuntimeCall::Utility(..)),
+		}
+	}
+
+	fn is_superset(&self, o: &Self) -> bool {
+		match (self, o) {
+			(x, y) if x == y => true,
+			(ProxyType::Any, _) => true,
+			_ => false,
+		}
+	}
+}
+
+/// The pallets `TxPause` can never pause.
+pub struct UnpausableCalls;
+
+impl Contains<pallet_tx_pause::FullNameOf<Runtime>> for UnpausableCalls {
+	fn contains((pallet, _): &pallet_tx_pause::FullNameOf<Runtime>) -> bool {
+		// Inherents and consensus reports go through the call filter too, and pausing them
+		// would stall the chain. Governance must stay able to unpause and to fix the runtime.
+		matches!(
+			pallet.as_slice(),
+			b"System" | b"Timestamp" | b"Babe" | b"Grandpa" | b"ImOnline" | b"Sudo" | b"TxPause" |
+				b"Council" | b"Democracy" | b"Scheduler" | b"Preimage"
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{BuildStorage, System};
+	use codec::Encode;
+	use frame_support::traits::GenesisBuild;
+	use sp_consensus_babe::{
+		digests::{PreDigest, SecondaryPlainPreDigest},
+		Slot, BABE_ENGINE_ID,
+	};
+	use sp_runtime::DigestItem;
+
+	fn new_test_ext() -> sp_io::TestExternalities {
+		let mut storage =
+			frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+		<pallet_treasury::GenesisConfig as GenesisBuild<Runtime>>::assimilate_storage(
+			&pallet_treasury::GenesisConfig,
+			&mut storage,
+		)
+		.unwrap();
+		let mut ext = sp_io::TestExternalities::from(storage);
+		ext.execute_with(|| pallet_session::Validators::<Runtime>::put(vec![author()]));
+		ext
+	}
+
+	fn author() -> AccountId {
+		AccountId::from([1; 32])
+	}
+
+	fn authored_by_first_authority() {
+		let pre_digest = PreDigest::SecondaryPlain(SecondaryPlainPreDigest {
+			authority_index: 0,
+			slot: Slot::from(0u64),
+		});
+		System::deposit_log(DigestItem::PreRuntime(BABE_ENGINE_ID, pre_digest.encode()));
+	}
+
+	#[test]
+	fn babe_author_is_found() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(Authorship::author(), None);
+			authored_by_first_authority();
+			assert_eq!(Authorship::author(), Some(author()));
+		});
+	}
+
+	#[test]
+	fn fees_are_split_and_tips_go_to_author() {
+		new_test_ext().execute_with(|| {
+			authored_by_first_authority();
+			let treasury = Treasury::account_id();
+			let treasury_balance = Balances::free_balance(&treasury);
+			let issuance = Balances::total_issuance();
+
+			DealWithFees::on_unbalanceds(
+				vec![Balances::issue(10_000), Balances::issue(1_000)].into_iter(),
+			);
+
+			assert_eq!(Balances::free_balance(&treasury), treasury_balance + 8_000);
+			assert_eq!(Balances::free_balance(author()), 2_000 + 1_000);
+			assert_eq!(Balances::total_issuance(), issuance + 11_000);
+		});
+	}
+
+	#[test]
+	fn author_share_is_burned_without_author() {
+		new_test_ext().execute_with(|| {
+			let treasury = Treasury::account_id();
+			let treasury_balance = Balances::free_balance(&treasury);
+			let issuance = Balances::total_issuance();
+
+			DealWithFees::on_unbalanceds(
+				vec![Balances::issue(10_000), Balances::issue(1_000)].into_iter(),
+			);
+
+			assert_eq!(Balances::free_balance(&treasury), treasury_balance + 8_000);
+			assert_eq!(Balances::total_issuance(), issuance + 8_000);
+		});
+	}
+}
+
+#[cfg(test)]
+mod multiplier_tests {
+	use super::*;
+	use crate::{
+		BlockWeights, MinimumMultiplier, SlowAdjustingFeeUpdate, System, TargetBlockFullness,
+		DAYS,
+	};
+	use frame_support::{dispatch::DispatchClass, weights::WeightToFee as _};
+	use pallet_transaction_payment::Multiplier;
+	use sp_runtime::{traits::Convert, BuildStorage, FixedPointNumber};
+
+	fn max_normal() -> Weight {
+		BlockWeights::get()
+			.get(DispatchClass::Normal)
+			.max_total
+			.unwrap_or_else(|| BlockWeights::get().max_block)
+	}
+
+	fn target() -> Weight {
+		TargetBlockFullness::get() * max_normal()
+	}
+
+	/// Update the multiplier once per block of `block_weight`, starting from `multiplier`.
+	fn run_blocks(multiplier: Multiplier, block_weight: Weight, blocks: u32) -> Multiplier {
+		let mut ext: sp_io::TestExternalities =
+			frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap().into();
+		ext.execute_with(|| {
+			System::set_block_consumed_resources(block_weight, 0);
+			(0..blocks).fold(multiplier, |multiplier, _| {
+				let next = SlowAdjustingFeeUpdate::<Runtime>::convert(multiplier);
+				if block_weight.ref_time() > target().ref_time() {
+					assert!(next > multiplier);
+				} else if block_weight.ref_time() < target().ref_time() {
+					assert!(next <= multiplier);
+				}
+				next
+			})
+		})
+	}
+
+	#[test]
+	fn transfer_costs_target_fee() {
+		let fee = WeightToFee::weight_to_fee(&transfer_weight());
+		assert!(fee.abs_diff(TRANSFER_FEE) <= 1, "transfer fee {} is off target", fee);
+	}
+
+	#[test]
+	fn multiplier_stays_at_target_fullness() {
+		let multiplier = Multiplier::saturating_from_rational(3, 2);
+		assert_eq!(run_blocks(multiplier, target(), 100), multiplier);
+	}
+
+	#[test]
+	fn full_blocks_raise_fees() {
+		let after_a_day = run_blocks(Multiplier::saturating_from_integer(1), max_normal(), DAYS);
+		assert!(after_a_day > Multiplier::saturating_from_rational(22, 10), "{}", after_a_day);
+		assert!(after_a_day < Multiplier::saturating_from_rational(23, 10), "{}", after_a_day);
+	}
+
+	#[test]
+	fn empty_blocks_lower_fees_down_to_the_minimum() {
+		let after_a_day = run_blocks(Multiplier::saturating_from_integer(1), Weight::zero(), DAYS);
+		assert!(after_a_day > Multiplier::saturating_from_rational(3, 4), "{}", after_a_day);
+		assert!(after_a_day < Multiplier::saturating_from_rational(8, 10), "{}", after_a_day);
+
+		let after_ten_days = run_blocks(after_a_day, Weight::zero(), 9 * DAYS);
+		assert_eq!(after_ten_days, MinimumMultiplier::get());
+	}
+
+	#[test]
+	fn fees_recover_from_the_minimum() {
+		let multiplier = run_blocks(MinimumMultiplier::get(), max_normal(), 1);
+		assert!(multiplier > MinimumMultiplier::get());
+	}
+}
+
+#[cfg(test)]
+mod proxy_tests {
+	use super::*;
+	use crate::{
+		BuildStorage, Multisig, Proxy, RuntimeEvent, RuntimeOrigin, System, EXISTENTIAL_DEPOSIT,
+	};
+	use frame_support::{assert_ok, dispatch::GetDispatchInfo, traits::GenesisBuild};
+	use sp_runtime::DispatchResult;
+
+	const ENDOWMENT: Balance = EXISTENTIAL_DEPOSIT * 1_000_000;
+
+	fn alice() -> AccountId {
+		AccountId::from([1; 32])
+	}
+
+	fn bob() -> AccountId {
+		AccountId::from([2; 32])
+	}
+
+	fn charlie() -> AccountId {
+		AccountId::from([3; 32])
+	}
+
+	fn new_test_ext() -> sp_io::TestExternalities {
+		let mut storage =
+			frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+		pallet_balances::GenesisConfig::<Runtime> {
+			balances: vec![(alice(), ENDOWMENT), (bob(), ENDOWMENT), (charlie(), ENDOWMENT)],
+		}
+		.assimilate_storage(&mut storage)
+		.unwrap();
+		let mut ext = sp_io::TestExternalities::from(storage);
+		ext.execute_with(|| System::set_block_number(1));
+		ext
+	}
+
+	fn claim(byte: u8) -> pallet_poe::Call<Runtime> {
+		pallet_poe::Call::create_claim { claim: vec![byte; 32].try_into().unwrap() }
+	}
+
+	fn claim_owner(byte: u8) -> Option<AccountId> {
+		pallet_poe::Proofs::<Runtime>::get(
+			frame_support::BoundedVec::try_from(vec![byte; 32]).unwrap(),
+		)
+		.map(|(owner, _)| owner)
+	}
+
+	fn transfer(dest: AccountId, value: Balance) -> RuntimeCall {
+		RuntimeCall::Balances(pallet_balances::Call::transfer { dest: dest.into(), value })
+	}
+
+	/// Let bob dispatch `call` as a proxy of alice and return the result of the call.
+	fn proxy(call: RuntimeCall) -> DispatchResult {
+		assert_ok!(Proxy::proxy(RuntimeOrigin::signed(bob()), alice().into(), None, Box::new(call)));
+		System::events()
+			.into_iter()
+			.rev()
+			.find_map(|record| match record.event {
+				RuntimeEvent::Proxy(pallet_proxy::Event::ProxyExecuted { result }) => Some(result),
+				_ => None,
+			})
+			.expect("the call was dispatched")
+	}
+
+	fn call_filtered() -> DispatchResult {
+		Err(frame_system::Error::<Runtime>::CallFiltered.into())
+	}
+
+	#[test]
+	fn proxy_types_restrict_calls_to_their_pallet() {
+		let poe = RuntimeCall::PoeModule(claim(1));
+		let kitty = RuntimeCall::KittiesModule(pallet_kitties::Call::create { name: *b"kitty001" });
+		let batch = RuntimeCall::Utility(pallet_utility::Call::batch { calls: vec![] });
+		let transfer = transfer(charlie(), EXISTENTIAL_DEPOSIT);
+
+		for call in [&poe, &kitty, &batch, &transfer] {
+			assert!(ProxyType::Any.filter(call));
+		}
+		assert!(ProxyType::Kitties.filter(&kitty));
+		assert!(ProxyType::Kitties.filter(&batch));
+		assert!(!ProxyType::Kitties.filter(&poe));
+		assert!(!ProxyType::Kitties.filter(&transfer));
+		assert!(ProxyType::Poe.filter(&poe));
+		assert!(ProxyType::Poe.filter(&batch));
+		assert!(!ProxyType::Poe.filter(&kitty));
+		assert!(!ProxyType::Poe.filter(&transfer));
+	}
+
+	#[test]
+	fn only_any_is_a_superset() {
+		assert!(ProxyType::Any.is_superset(&ProxyType::Kitties));
+		assert!(ProxyType::Any.is_superset(&ProxyType::Poe));
+		assert!(ProxyType::Poe.is_superset(&ProxyType::Poe));
+		assert!(!ProxyType::Poe.is_superset(&ProxyType::Kitties));
+		assert!(!ProxyType::Kitties.is_superset(&ProxyType::Any));
+	}
+
+	#[test]
+	fn poe_proxy_claims_for_the_proxied_account() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(Proxy::add_proxy(
+				RuntimeOrigin::signed(alice()),
+				bob().into(),
+				ProxyType::Poe,
+				0
+			));
+
+			assert_ok!(proxy(RuntimeCall::PoeModule(claim(1))));
+			assert_eq!(claim_owner(1), Some(alice()));
+
+			let balance = Balances::free_balance(charlie());
+			assert_eq!(proxy(transfer(charlie(), EXISTENTIAL_DEPOSIT)), call_filtered());
+			assert_eq!(Balances::free_balance(charlie()), balance);
+		});
+	}
+
+	#[test]
+	fn batches_are_filtered_call_by_call() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(Proxy::add_proxy(
+				RuntimeOrigin::signed(alice()),
+				bob().into(),
+				ProxyType::Poe,
+				0
+			));
+
+			let batch =
+				|calls: Vec<RuntimeCall>| RuntimeCall::Utility(pallet_utility::Call::batch_all { calls });
+			assert_ok!(proxy(batch(vec![claim(1).into(), claim(2).into()])));
+			assert_eq!(claim_owner(1), Some(alice()));
+			assert_eq!(claim_owner(2), Some(alice()));
+
+			let smuggled = batch(vec![claim(3).into(), transfer(charlie(), EXISTENTIAL_DEPOSIT)]);
+			assert_eq!(proxy(smuggled), call_filtered());
+			assert_eq!(claim_owner(3), None);
+		});
+	}
+
+	#[test]
+	fn multisig_spends_from_a_shared_account() {
+		new_test_ext().execute_with(|| {
+			let shared = Multisig::multi_account_id(&[alice(), bob()], 2);
+			assert_ok!(Balances::transfer(
+				RuntimeOrigin::signed(alice()),
+				shared.clone().into(),
+				EXISTENTIAL_DEPOSIT * 100
+			));
+			let call = transfer(charlie(), EXISTENTIAL_DEPOSIT * 10);
+			let max_weight = call.get_dispatch_info().weight;
+
+			assert_ok!(Multisig::as_multi(
+				RuntimeOrigin::signed(alice()),
+				2,
+				vec![bob()],
+				None,
+				Box::new(call.clone()),
+				Weight::zero()
+			));
+			assert_eq!(Balances::free_balance(charlie()), ENDOWMENT);
+
+			assert_ok!(Multisig::as_multi(
+				RuntimeOrigin::signed(bob()),
+				2,
+				vec![alice()],
+				Some(Multisig::timepoint()),
+				Box::new(call),
+				max_weight
+			));
+			assert_eq!(Balances::free_balance(charlie()), ENDOWMENT + EXISTENTIAL_DEPOSIT * 10);
+			assert_eq!(Balances::free_balance(&shared), EXISTENTIAL_DEPOSIT * 90);
+		});
+	}
+}
+
+#[cfg(test)]
+mod pause_tests {
+	use super::*;
+	use crate::{BuildStorage, RuntimeEvent, RuntimeOrigin, System, TxPause};
+	use frame_support::{assert_noop, assert_ok};
+	use sp_runtime::traits::Dispatchable;
+
+	type BaseCallFilter = <Runtime as frame_system::Config>::BaseCallFilter;
+
+	fn new_test_ext() -> sp_io::TestExternalities {
+		let mut ext: sp_io::TestExternalities =
+			frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap().into();
+		ext.execute_with(|| System::set_block_number(1));
+		ext
+	}
+
+	fn name(name: &str) -> pallet_tx_pause::NameOf<Runtime> {
+		name.as_bytes().to_vec().try_into().unwrap()
+	}
+
+	#[test]
+	fn paused_kitties_are_filtered_in_batches_too() {
+		new_test_ext().execute_with(|| {
+			let create =
+				RuntimeCall::KittiesModule(pallet_kitties::Call::create { name: *b"kitty001" });
+			assert!(BaseCallFilter::contains(&create));
+
+			assert_ok!(TxPause::pause(RuntimeOrigin::root(), name("KittiesModule"), None));
+			assert!(!BaseCallFilter::contains(&create));
+			assert_eq!(TxPause::paused_calls(), vec![(b"KittiesModule".to_vec(), None)]);
+
+			let batch = RuntimeCall::Utility(pallet_utility::Call::batch { calls: vec![create] });
+			assert_ok!(batch.dispatch(RuntimeOrigin::signed(AccountId::from([1; 32]))));
+			System::assert_last_event(RuntimeEvent::Utility(
+				pallet_utility::Event::BatchInterrupted {
+					index: 0,
+					error: frame_system::Error::<Runtime>::CallFiltered.into(),
+				},
+			));
+		});
+	}
+
+	#[test]
+	fn governance_pallets_cannot_be_paused() {
+		new_test_ext().execute_with(|| {
+			for pallet in ["Council", "Democracy", "Scheduler", "Preimage"] {
+				assert_noop!(
+					TxPause::pause(RuntimeOrigin::root(), name(pallet), None),
+					pallet_tx_pause::Error::<Runtime>::Unpausable
+				);
+			}
+		});
+	}
+
+	#[test]
+	fn chain_critical_pallets_cannot_be_paused() {
+		new_test_ext().execute_with(|| {
+			let pallets = ["System", "Timestamp", "Babe", "Grandpa", "ImOnline", "Sudo", "TxPause"];
+			for pallet in pallets {
+				assert_noop!(
+					TxPause::pause(RuntimeOrigin::root(), name(pallet), None),
+					pallet_tx_pause::Error::<Runtime>::Unpausable
+				);
+			}
+		});
+	}
+}
@@ -6,19 +6,23 @@
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
+use frame_election_provider_support::{onchain, SequentialPhragmen};
 use frame_support::PalletId;
 use pallet_grandpa::AuthorityId as GrandpaId;
+use pallet_im_online::sr25519::AuthorityId as ImOnlineId;
+use pallet_session::historical as pallet_session_historical;
 use sp_api::impl_runtime_apis;
-use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
-	create_runtime_str, generic, impl_opaque_keys,
+	create_runtime_str, curve::PiecewiseLinear, generic, impl_opaque_keys,
 	traits::{
-		AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, One, Verify,
+		AccountIdLookup, BlakeTwo256, Block as BlockT, Bounded, IdentifyAccount, NumberFor,
+		OpaqueKeys, Verify,
 	},
-	transaction_validity::{TransactionSource, TransactionValidity},
-	ApplyExtrinsicResult, MultiSignature,
+	transaction_validity::{TransactionPriority, TransactionSource, TransactionValidity},
+	ApplyExtrinsicResult, FixedPointNumber, MultiSignature,
 };
+use sp_staking::{EraIndex, SessionIndex};
 use sp_std::prelude::*;
 #[cfg(feature = "std")]
 use sp_version::NativeVersion;
@@ -28,7 +32,8 @@ use sp_version::RuntimeVersion;
 pub use frame_support::{
 	construct_runtime, parameter_types,
 	traits::{
-		ConstU128, ConstU32, ConstU64, ConstU8, KeyOwnerProofSystem, Randomness, StorageInfo,
+		AsEnsureOriginWithArg, ConstBool, ConstU128, ConstU32, ConstU64, ConstU8, EitherOfDiverse,
+		EqualPrivilegeOnly, KeyOwnerProofSystem, Randomness, StorageInfo,
 	},
 	weights::{
 		constants::{
@@ -36,17 +41,23 @@ pub use frame_support::{
 		},
 		IdentityFee, Weight,
 	},
-	StorageValue,
+	StorageMap, StorageValue,
 };
 pub use frame_system::Call as SystemCall;
 pub use pallet_balances::Call as BalancesCall;
+pub use pallet_staking::StakerStatus;
 pub use pallet_timestamp::Call as TimestampCall;
-use pallet_transaction_payment::{ConstFeeMultiplier, CurrencyAdapter, Multiplier};
+use pallet_transaction_payment::{CurrencyAdapter, Multiplier, TargetedFeeAdjustment};
 #[cfg(any(feature = "std", test))]
 pub use sp_runtime::BuildStorage;
-pub use sp_runtime::{Perbill, Permill};
+pub use sp_runtime::{Perbill, Percent, Permill, Perquintill};
 use pallet_insecure_randomness_collective_flip;
 
+pub mod impls;
+pub mod migrations;
+
+use impls::{DealWithFees, ProxyType, UnpausableCalls, WeightToFee};
+
 /// Import the template pallet.
 pub use pallet_template;
 
@@ -55,6 +66,16 @@ pub use pallet_poe;
 
 /// Import the kitties pallet.
 pub use pallet_kitties;
+use pallet_kitties::pricing::{LinearParameters, LinearPrice, PriceBasis};
+
+/// Import the kitty arena pallet.
+pub use pallet_kitty_arena;
+
+/// Import the validator set pallet.
+pub use pallet_validator_set;
+
+/// Import the transaction pause pallet.
+pub use pallet_tx_pause;
 
 /// An index to a block.
 pub type BlockNumber = u32;
@@ -69,6 +90,9 @@ pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::Account
 /// Balance of an account.
 pub type Balance = u128;
 
+/// Identifier of an asset in `pallet_assets`.
+pub type AssetId = u32;
+
 /// Index of a transaction in the chain.
 pub type Index = u32;
 
@@ -93,8 +117,9 @@ pub mod opaque {
 
 	impl_opaque_keys! {
 		pub struct SessionKeys {
-			pub aura: Aura,
+			pub babe: Babe,
 			pub grandpa: Grandpa,
+			pub im_online: ImOnline,
 		}
 	}
 }
@@ -111,7 +136,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 300,
+	spec_version: 316,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
 	transaction_version: 1,
@@ -121,7 +146,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 /// This determines the average expected block time that we are targeting.
 /// Blocks will be produced at a minimum duration defined by `SLOT_DURATION`.
 /// `SLOT_DURATION` is picked up by `pallet_timestamp` which is in turn picked
-/// up by `pallet_aura` to implement `fn slot_duration()`.
+/// up by `pallet_babe` to implement `fn slot_duration()`.
 ///
 /// Change this to adjust the block time.
 pub const MILLISECS_PER_BLOCK: u64 = 6000;
@@ -135,6 +160,21 @@ pub const MINUTES: BlockNumber = 60_000 / (MILLISECS_PER_BLOCK as BlockNumber);
 pub const HOURS: BlockNumber = MINUTES * 60;
 pub const DAYS: BlockNumber = HOURS * 24;
 
+/// Length of a BABE epoch, which is also the length of a session.
+pub const EPOCH_DURATION_IN_BLOCKS: BlockNumber = 10 * MINUTES;
+// Secondary slots fill the slots without a primary author, so every slot has a block.
+pub const EPOCH_DURATION_IN_SLOTS: u64 = EPOCH_DURATION_IN_BLOCKS as u64;
+
+/// 1 in 4 slots (on average, not counting collisions) will have a primary block.
+pub const PRIMARY_PROBABILITY: (u64, u64) = (1, 4);
+
+/// The BABE epoch configuration at genesis.
+pub const BABE_GENESIS_EPOCH_CONFIG: sp_consensus_babe::BabeEpochConfiguration =
+	sp_consensus_babe::BabeEpochConfiguration {
+		c: PRIMARY_PROBABILITY,
+		allowed_slots: sp_consensus_babe::AllowedSlots::PrimaryAndSecondaryVRFSlots,
+	};
+
 /// The version information used to identify this runtime when compiled natively.
 #[cfg(feature = "std")]
 pub fn native_version() -> NativeVersion {
@@ -160,8 +200,8 @@ parameter_types! {
 // Configure FRAME pallets to include in runtime.
 
 impl frame_system::Config for Runtime {
-	/// The basic call filter to use in dispatchable.
-	type BaseCallFilter = frame_support::traits::Everything;
+	/// The basic call filter to use in dispatchable, rejecting calls paused by `TxPause`.
+	type BaseCallFilter = TxPause;
 	/// Block & extrinsics weights: base values and limits.
 	type BlockWeights = BlockWeights;
 	/// The maximum length of a block (in bytes).
@@ -211,27 +251,56 @@ impl frame_system::Config for Runtime {
 	type MaxConsumers = frame_support::traits::ConstU32<16>;
 }
 
-impl pallet_aura::Config for Runtime {
-	type AuthorityId = AuraId;
-	type DisabledValidators = ();
-	type MaxAuthorities = ConstU32<32>;
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime
+where
+	RuntimeCall: From<C>,
+{
+	type Extrinsic = UncheckedExtrinsic;
+	type OverarchingCall = RuntimeCall;
+}
+
+parameter_types! {
+	pub const EpochDuration: u64 = EPOCH_DURATION_IN_SLOTS;
+	pub const ExpectedBlockTime: u64 = MILLISECS_PER_BLOCK;
+	pub const MaxAuthorities: u32 = 100;
+	/// Equivocations can be reported, and their offenders slashed, until the offenders could
+	/// have unbonded.
+	pub const ReportLongevity: u64 =
+		BondingDuration::get() as u64 * SessionsPerEra::get() as u64 * EpochDuration::get();
+	pub const MaxSetIdSessionEntries: u64 =
+		BondingDuration::get() as u64 * SessionsPerEra::get() as u64;
+}
+
+impl pallet_babe::Config for Runtime {
+	type EpochDuration = EpochDuration;
+	type ExpectedBlockTime = ExpectedBlockTime;
+	// Epochs change when the session pallet rotates sessions.
+	type EpochChangeTrigger = pallet_babe::ExternalTrigger;
+	type DisabledValidators = Session;
+	type WeightInfo = ();
+	type MaxAuthorities = MaxAuthorities;
+	type KeyOwnerProof =
+		<Historical as KeyOwnerProofSystem<(KeyTypeId, pallet_babe::AuthorityId)>>::Proof;
+	type EquivocationReportSystem =
+		pallet_babe::EquivocationReportSystem<Self, Offences, Historical, ReportLongevity>;
 }
 
 impl pallet_grandpa::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 
 	type WeightInfo = ();
-	type MaxAuthorities = ConstU32<32>;
-	type MaxSetIdSessionEntries = ConstU64<0>;
+	type MaxAuthorities = MaxAuthorities;
+	type MaxSetIdSessionEntries = MaxSetIdSessionEntries;
 
-	type KeyOwnerProof = sp_core::Void;
-	type EquivocationReportSystem = ();
+	type KeyOwnerProof = <Historical as KeyOwnerProofSystem<(KeyTypeId, GrandpaId)>>::Proof;
+	type EquivocationReportSystem =
+		pallet_grandpa::EquivocationReportSystem<Self, Offences, Historical, ReportLongevity>;
 }
 
 impl pallet_timestamp::Config for Runtime {
 	/// A timestamp: milliseconds since the unix epoch.
 	type Moment = u64;
-	type OnTimestampSet = Aura;
+	type OnTimestampSet = Babe;
 	type MinimumPeriod = ConstU64<{ SLOT_DURATION / 2 }>;
 	type WeightInfo = ();
 }
@@ -239,6 +308,9 @@ impl pallet_timestamp::Config for Runtime {
 /// Existential deposit.
 pub const EXISTENTIAL_DEPOSIT: u128 = 500;
 
+/// Fee for the weight of a balance transfer while the fee multiplier is one.
+pub const TRANSFER_FEE: Balance = EXISTENTIAL_DEPOSIT * 10;
+
 impl pallet_balances::Config for Runtime {
 	type MaxLocks = ConstU32<50>;
 	type MaxReserves = ();
@@ -254,26 +326,399 @@ impl pallet_balances::Config for Runtime {
 }
 
 parameter_types! {
-	pub FeeMultiplier: Multiplier = Multiplier::one();
+	pub const AssetDeposit: Balance = EXISTENTIAL_DEPOSIT * 100;
+	pub const AssetAccountDeposit: Balance = EXISTENTIAL_DEPOSIT;
+	pub const MetadataDepositBase: Balance = EXISTENTIAL_DEPOSIT * 10;
+	pub const MetadataDepositPerByte: Balance = EXISTENTIAL_DEPOSIT / 10;
+	pub const ApprovalDeposit: Balance = EXISTENTIAL_DEPOSIT;
+}
+
+impl pallet_assets::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Balance = Balance;
+	type AssetId = AssetId;
+	type AssetIdParameter = codec::Compact<AssetId>;
+	type Currency = Balances;
+	type CreateOrigin = AsEnsureOriginWithArg<frame_system::EnsureSigned<AccountId>>;
+	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+	type AssetDeposit = AssetDeposit;
+	type AssetAccountDeposit = AssetAccountDeposit;
+	type MetadataDepositBase = MetadataDepositBase;
+	type MetadataDepositPerByte = MetadataDepositPerByte;
+	type ApprovalDeposit = ApprovalDeposit;
+	type StringLimit = ConstU32<50>;
+	type Freezer = ();
+	type Extra = ();
+	type CallbackHandle = ();
+	type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
+	type RemoveItemsLimit = ConstU32<1000>;
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = ();
+}
+
+parameter_types! {
+	/// Block fullness the fee multiplier steers towards.
+	pub const TargetBlockFullness: Perquintill = Perquintill::from_percent(25);
+	/// How fast the multiplier reacts: a day of full blocks raises fees about 2.25 times, a day
+	/// of empty blocks lowers them by about a quarter.
+	pub AdjustmentVariable: Multiplier = Multiplier::saturating_from_rational(75, 1_000_000);
+	/// Fees never drop below a tenth of their nominal value.
+	pub MinimumMultiplier: Multiplier = Multiplier::saturating_from_rational(1, 10u128);
+	pub MaximumMultiplier: Multiplier = Bounded::max_value();
+	/// Share of the transaction fees going to the treasury, the rest goes to the block author
+	/// along with the tips.
+	pub const TreasuryFeeShare: Percent = Percent::from_percent(80);
+}
+
+impl pallet_authorship::Config for Runtime {
+	type FindAuthor = pallet_session::FindAccountFromAuthorIndex<Self, Babe>;
+	type EventHandler = (Staking, ImOnline);
 }
 
 impl pallet_transaction_payment::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
-	type OnChargeTransaction = CurrencyAdapter<Balances, ()>;
+	type OnChargeTransaction = CurrencyAdapter<Balances, DealWithFees>;
 	type OperationalFeeMultiplier = ConstU8<5>;
-	type WeightToFee = IdentityFee<Balance>;
+	type WeightToFee = WeightToFee;
 	type LengthToFee = IdentityFee<Balance>;
-	type FeeMultiplierUpdate = ConstFeeMultiplier<FeeMultiplier>;
+	type FeeMultiplierUpdate = SlowAdjustingFeeUpdate<Self>;
 }
 
+/// Scale fees with congestion, raising them while blocks are fuller than
+/// [`TargetBlockFullness`] and lowering them otherwise.
+pub type SlowAdjustingFeeUpdate<R> = TargetedFeeAdjustment<
+	R,
+	TargetBlockFullness,
+	AdjustmentVariable,
+	MinimumMultiplier,
+	MaximumMultiplier,
+>;
+
 impl pallet_sudo::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type RuntimeCall = RuntimeCall;
 }
 
+parameter_types! {
+	pub MaximumSchedulerWeight: Weight =
+		Perbill::from_percent(80) * BlockWeights::get().max_block;
+}
+
+impl pallet_scheduler::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeOrigin = RuntimeOrigin;
+	type PalletsOrigin = OriginCaller;
+	type RuntimeCall = RuntimeCall;
+	type MaximumWeight = MaximumSchedulerWeight;
+	type ScheduleOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxScheduledPerBlock = ConstU32<512>;
+	type WeightInfo = pallet_scheduler::weights::SubstrateWeight<Runtime>;
+	type OriginPrivilegeCmp = EqualPrivilegeOnly;
+	type Preimages = Preimage;
+}
+
+parameter_types! {
+	pub const PreimageBaseDeposit: Balance = EXISTENTIAL_DEPOSIT * 100;
+	pub const PreimageByteDeposit: Balance = EXISTENTIAL_DEPOSIT / 10;
+}
+
+impl pallet_preimage::Config for Runtime {
+	type WeightInfo = pallet_preimage::weights::SubstrateWeight<Runtime>;
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type ManagerOrigin = frame_system::EnsureRoot<AccountId>;
+	type BaseDeposit = PreimageBaseDeposit;
+	type ByteDeposit = PreimageByteDeposit;
+}
+
+parameter_types! {
+	pub const CouncilMotionDuration: BlockNumber = 3 * DAYS;
+}
+
+/// The council: changes parameters of our pallets and brings runtime upgrades to referendum.
+pub type CouncilCollective = pallet_collective::Instance1;
+
+impl pallet_collective::Config<CouncilCollective> for Runtime {
+	type RuntimeOrigin = RuntimeOrigin;
+	type Proposal = RuntimeCall;
+	type RuntimeEvent = RuntimeEvent;
+	type MotionDuration = CouncilMotionDuration;
+	type MaxProposals = ConstU32<100>;
+	type MaxMembers = ConstU32<100>;
+	type DefaultVote = pallet_collective::PrimeDefaultVote;
+	type WeightInfo = pallet_collective::weights::SubstrateWeight<Runtime>;
+	type SetMembersOrigin = frame_system::EnsureRoot<AccountId>;
+}
+
+/// Root, or more than half of the council.
+pub type EnsureRootOrHalfCouncil = EitherOfDiverse<
+	frame_system::EnsureRoot<AccountId>,
+	pallet_collective::EnsureProportionMoreThan<AccountId, CouncilCollective, 1, 2>,
+>;
+
+/// At least `N / D` of the council.
+type CouncilAtLeast<const N: u32, const D: u32> =
+	pallet_collective::EnsureProportionAtLeast<AccountId, CouncilCollective, N, D>;
+
+parameter_types! {
+	pub const LaunchPeriod: BlockNumber = 7 * DAYS;
+	pub const VotingPeriod: BlockNumber = 7 * DAYS;
+	pub const FastTrackVotingPeriod: BlockNumber = 3 * HOURS;
+	pub const EnactmentPeriod: BlockNumber = DAYS;
+	pub const CooloffPeriod: BlockNumber = 7 * DAYS;
+	pub const MinimumDeposit: Balance = EXISTENTIAL_DEPOSIT * 1000;
+}
+
+// Referenda dispatch as root, so `set_code` and every other root call goes through them. The
+// council decides which external proposals are voted on and how fast.
+impl pallet_democracy::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type EnactmentPeriod = EnactmentPeriod;
+	type LaunchPeriod = LaunchPeriod;
+	type VotingPeriod = VotingPeriod;
+	type VoteLockingPeriod = EnactmentPeriod;
+	type MinimumDeposit = MinimumDeposit;
+	type ExternalOrigin = CouncilAtLeast<1, 2>;
+	type ExternalMajorityOrigin = CouncilAtLeast<3, 4>;
+	type ExternalDefaultOrigin = CouncilAtLeast<1, 1>;
+	type SubmitOrigin = frame_system::EnsureSigned<AccountId>;
+	type FastTrackOrigin = CouncilAtLeast<2, 3>;
+	type InstantOrigin = CouncilAtLeast<1, 1>;
+	type InstantAllowed = ConstBool<true>;
+	type FastTrackVotingPeriod = FastTrackVotingPeriod;
+	type CancellationOrigin = CouncilAtLeast<2, 3>;
+	type CancelProposalOrigin =
+		EitherOfDiverse<frame_system::EnsureRoot<AccountId>, CouncilAtLeast<1, 1>>;
+	type BlacklistOrigin = frame_system::EnsureRoot<AccountId>;
+	type VetoOrigin = pallet_collective::EnsureMember<AccountId, CouncilCollective>;
+	type CooloffPeriod = CooloffPeriod;
+	type Slash = Treasury;
+	type Scheduler = Scheduler;
+	type PalletsOrigin = OriginCaller;
+	type MaxVotes = ConstU32<100>;
+	type WeightInfo = pallet_democracy::weights::SubstrateWeight<Runtime>;
+	type MaxProposals = ConstU32<100>;
+	type Preimages = Preimage;
+	type MaxDeposits = ConstU32<100>;
+	type MaxBlacklisted = ConstU32<100>;
+}
+
+parameter_types! {
+	pub const TreasuryPalletId: PalletId = PalletId(*b"py/trsry");
+	pub const ProposalBond: Permill = Permill::from_percent(5);
+	pub const ProposalBondMinimum: Balance = EXISTENTIAL_DEPOSIT * 100;
+	pub const SpendPeriod: BlockNumber = DAYS;
+}
+
+// Spends are proposed with a bond and approved by the council; there is no spend origin, so
+// every payout goes through a proposal.
+impl pallet_treasury::Config for Runtime {
+	type PalletId = TreasuryPalletId;
+	type Currency = Balances;
+	type ApproveOrigin = EnsureRootOrHalfCouncil;
+	type RejectOrigin = EnsureRootOrHalfCouncil;
+	type RuntimeEvent = RuntimeEvent;
+	type OnSlash = Treasury;
+	type ProposalBond = ProposalBond;
+	type ProposalBondMinimum = ProposalBondMinimum;
+	type ProposalBondMaximum = ();
+	type SpendPeriod = SpendPeriod;
+	type Burn = ();
+	type BurnDestination = ();
+	type SpendFunds = ();
+	type WeightInfo = pallet_treasury::weights::SubstrateWeight<Runtime>;
+	type MaxApprovals = ConstU32<100>;
+	type SpendOrigin = frame_support::traits::NeverEnsureOrigin<Balance>;
+}
+
+impl pallet_utility::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeCall = RuntimeCall;
+	type PalletsOrigin = OriginCaller;
+	type WeightInfo = pallet_utility::weights::SubstrateWeight<Runtime>;
+}
+
+// Deposits are a base per storage item plus `EXISTENTIAL_DEPOSIT / 10` per byte, like the
+// template pallet's entries.
+parameter_types! {
+	pub const MultisigDepositBase: Balance = EXISTENTIAL_DEPOSIT * 10;
+	// An approving account id per signatory.
+	pub const MultisigDepositFactor: Balance = EXISTENTIAL_DEPOSIT * 32 / 10;
+	pub const ProxyDepositBase: Balance = EXISTENTIAL_DEPOSIT * 10;
+	// The delegate account id, the proxy type and the delay.
+	pub const ProxyDepositFactor: Balance = EXISTENTIAL_DEPOSIT * 37 / 10;
+	pub const AnnouncementDepositBase: Balance = EXISTENTIAL_DEPOSIT * 10;
+	// The delegate account id, the call hash and the block number.
+	pub const AnnouncementDepositFactor: Balance = EXISTENTIAL_DEPOSIT * 68 / 10;
+}
+
+impl pallet_multisig::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeCall = RuntimeCall;
+	type Currency = Balances;
+	type DepositBase = MultisigDepositBase;
+	type DepositFactor = MultisigDepositFactor;
+	type MaxSignatories = ConstU32<100>;
+	type WeightInfo = pallet_multisig::weights::SubstrateWeight<Runtime>;
+}
+
+impl pallet_proxy::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeCall = RuntimeCall;
+	type Currency = Balances;
+	type ProxyType = ProxyType;
+	type ProxyDepositBase = ProxyDepositBase;
+	type ProxyDepositFactor = ProxyDepositFactor;
+	type MaxProxies = ConstU32<32>;
+	type WeightInfo = pallet_proxy::weights::SubstrateWeight<Runtime>;
+	type MaxPending = ConstU32<32>;
+	type CallHasher = BlakeTwo256;
+	type AnnouncementDepositBase = AnnouncementDepositBase;
+	type AnnouncementDepositFactor = AnnouncementDepositFactor;
+}
+
+impl pallet_session::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type ValidatorId = <Self as frame_system::Config>::AccountId;
+	type ValidatorIdOf = pallet_validator_set::ValidatorOf<Self, pallet_staking::StashOf<Self>>;
+	type ShouldEndSession = Babe;
+	type NextSessionRotation = Babe;
+	type SessionManager = pallet_session::historical::NoteHistoricalRoot<Self, ValidatorSet>;
+	type SessionHandler = <opaque::SessionKeys as OpaqueKeys>::KeyTypeIdProviders;
+	type Keys = opaque::SessionKeys;
+	type WeightInfo = pallet_session::weights::SubstrateWeight<Runtime>;
+}
+
+impl pallet_session::historical::Config for Runtime {
+	type FullIdentification = pallet_staking::Exposure<AccountId, Balance>;
+	type FullIdentificationOf = pallet_staking::ExposureOf<Runtime>;
+}
+
+pallet_staking_reward_curve::build! {
+	const REWARD_CURVE: PiecewiseLinear<'static> = curve!(
+		min_inflation: 0_025_000,
+		max_inflation: 0_100_000,
+		ideal_stake: 0_500_000,
+		falloff: 0_050_000,
+		max_piece_count: 40,
+		test_precision: 0_005_000,
+	);
+}
+
+parameter_types! {
+	/// An era lasts an hour.
+	pub const SessionsPerEra: SessionIndex = 6;
+	/// Unbonded funds stay locked for a day.
+	pub const BondingDuration: EraIndex = 24;
+	/// Slashes can be cancelled by governance for a quarter of the bonding duration.
+	pub const SlashDeferDuration: EraIndex = 6;
+	pub const RewardCurve: &'static PiecewiseLinear<'static> = &REWARD_CURVE;
+	pub const MaxNominatorRewardedPerValidator: u32 = 256;
+	pub const OffendingValidatorsThreshold: Perbill = Perbill::from_percent(17);
+	pub const MaxActiveValidators: u32 = 100;
+}
+
+/// Elect validators within the block, good enough for the size of our validator set.
+pub struct OnChainSeqPhragmen;
+
+impl onchain::Config for OnChainSeqPhragmen {
+	type System = Runtime;
+	type Solver = SequentialPhragmen<AccountId, Perbill>;
+	type DataProvider = Staking;
+	type WeightInfo = frame_election_provider_support::weights::SubstrateWeight<Runtime>;
+	type MaxWinners = MaxActiveValidators;
+	type VotersBound = ConstU32<10_000>;
+	type TargetsBound = ConstU32<1_000>;
+}
+
+pub struct StakingBenchmarkingConfig;
+
+impl pallet_staking::BenchmarkingConfig for StakingBenchmarkingConfig {
+	type MaxNominators = ConstU32<1000>;
+	type MaxValidators = ConstU32<1000>;
+}
+
+impl pallet_staking::Config for Runtime {
+	type MaxNominations = ConstU32<16>;
+	type Currency = Balances;
+	type CurrencyBalance = Balance;
+	type UnixTime = Timestamp;
+	type CurrencyToVote = frame_support::traits::U128CurrencyToVote;
+	type RewardRemainder = Treasury;
+	type RuntimeEvent = RuntimeEvent;
+	type Slash = Treasury;
+	type Reward = ();
+	type SessionsPerEra = SessionsPerEra;
+	type BondingDuration = BondingDuration;
+	type SlashDeferDuration = SlashDeferDuration;
+	type AdminOrigin = EitherOfDiverse<frame_system::EnsureRoot<AccountId>, CouncilAtLeast<3, 4>>;
+	type SessionInterface = Self;
+	type EraPayout = pallet_staking::ConvertCurve<RewardCurve>;
+	type NextNewSession = Session;
+	type MaxNominatorRewardedPerValidator = MaxNominatorRewardedPerValidator;
+	type OffendingValidatorsThreshold = OffendingValidatorsThreshold;
+	type ElectionProvider = onchain::OnChainExecution<OnChainSeqPhragmen>;
+	type GenesisElectionProvider = onchain::OnChainExecution<OnChainSeqPhragmen>;
+	type VoterList = pallet_staking::UseNominatorsAndValidatorsMap<Self>;
+	type TargetList = pallet_staking::UseValidatorsMap<Self>;
+	type MaxUnlockingChunks = ConstU32<32>;
+	type HistoryDepth = ConstU32<84>;
+	type OnStakerSlash = ();
+	type WeightInfo = pallet_staking::weights::SubstrateWeight<Runtime>;
+	type BenchmarkingConfig = StakingBenchmarkingConfig;
+}
+
+parameter_types! {
+	pub const ImOnlineUnsignedPriority: TransactionPriority = TransactionPriority::max_value();
+}
+
+impl pallet_im_online::Config for Runtime {
+	type AuthorityId = ImOnlineId;
+	type RuntimeEvent = RuntimeEvent;
+	type NextSessionRotation = Babe;
+	type ValidatorSet = Historical;
+	type ReportUnresponsiveness = ValidatorSet;
+	type UnsignedPriority = ImOnlineUnsignedPriority;
+	type WeightInfo = pallet_im_online::weights::SubstrateWeight<Runtime>;
+	type MaxKeys = ConstU32<10_000>;
+	type MaxPeerInHeartbeats = ConstU32<10_000>;
+	type MaxPeerDataEncodingSize = ConstU32<1_000>;
+}
+
+impl pallet_offences::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type IdentificationTuple = pallet_session::historical::IdentificationTuple<Self>;
+	type OnOffenceHandler = Staking;
+}
+
+// Governance can add validators next to the ones elected by staking. Added validators are not
+// bonded, so they earn era points but no rewards, and are removed when reported offline.
+impl pallet_validator_set::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type AddRemoveOrigin = EnsureRootOrHalfCouncil;
+	type Elected = Staking;
+	type ReportUnresponsiveness = Offences;
+	type MaxValidators = ConstU32<16>;
+	type MaxElected = MaxActiveValidators;
+}
+
 /// Configure the pallet-template in pallets/template.
+parameter_types! {
+	pub const EntryDepositBase: Balance = EXISTENTIAL_DEPOSIT * 10;
+	pub const EntryDepositPerByte: Balance = EXISTENTIAL_DEPOSIT / 10;
+}
+
 impl pallet_template::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type EntryDepositBase = EntryDepositBase;
+	type EntryDepositPerByte = EntryDepositPerByte;
+	type MaxNamespaceLength = ConstU32<32>;
+	type MaxKeyLength = ConstU32<64>;
+	type MaxValueLength = ConstU32<1024>;
+	type MaxEntriesPerAccount = ConstU32<128>;
+	type WeightInfo = pallet_template::weights::SubstrateWeight<Runtime>;
 }
 
 /// Configure the pallet-poe in pallets/poe.
@@ -289,6 +734,15 @@ parameter_types! {
 	pub KittyPalletId: PalletId = PalletId(*b"py/kitty");
 	//palletid 数据结构
 	pub KittyPrice: Balance = EXISTENTIAL_DEPOSIT * 10;
+	// 铸造价格随供应量线性上涨，每只 kitty 涨 1/100
+	pub DefaultPriceParameters: LinearParameters<Balance> = LinearParameters {
+		base: KittyPrice::get(),
+		slope: KittyPrice::get() / 100,
+		basis: PriceBasis::Supply,
+	};
+	pub const VoucherPriority: TransactionPriority = TransactionPriority::max_value() / 2;
+	// 一半手续费分给质押者, 另一半进国库
+	pub const KittyStakerShare: Permill = Permill::from_percent(50);
 }
 
 //接下来，我们在 runtime 使用我们定义的类型，修改代码如下：
@@ -297,9 +751,48 @@ impl pallet_kitties::Config for Runtime {
 	type Randomness = RandomnessModule;
 	type Currency = Balances; 
 	//需要在runtime把type做一下绑定，currency 直接绑定 到balances这个pallet，既支持currency转账，还有reserve的currency，不管kitty怎么实现都支持
-	type KittyPrice = KittyPrice;
+	type Assets = Assets;
+	type PriceCurve = LinearPrice;
+	type DefaultPriceParameters = DefaultPriceParameters;
+	type GovernanceOrigin = EnsureRootOrHalfCouncil;
 	type PalletId = KittyPalletId;
 	//palletid有id定义方法，palletid数据结构，里面接受8byte的值，作为palletid，他再可以转为account，我们transfer用到的
+	type MaxLineageDepth = ConstU32<16>;
+	type MaxLineageSize = ConstU32<512>;
+	type EraLength = ConstU32<DAYS>;
+	type StakingLockPeriod = ConstU32<{ 7 * DAYS }>;
+	type RevenueDestination = Treasury;
+	type StakerShare = KittyStakerShare;
+	type VoucherPublic = <Signature as Verify>::Signer;
+	type VoucherSignature = Signature;
+	type VoucherPriority = VoucherPriority;
+	type MaxCollectionMetadata = ConstU32<256>;
+	type MaxProvenanceEntries = ConstU32<32>;
+}
+
+parameter_types! {
+	pub ArenaPalletId: PalletId = PalletId(*b"py/arena");
+}
+
+impl pallet_kitty_arena::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type Kitties = KittiesModule;
+	type Randomness = RandomnessModule;
+	type ScheduleOrigin = EnsureRootOrHalfCouncil;
+	type PalletId = ArenaPalletId;
+	type MaxEntrants = ConstU32<64>;
+	type MaxTournamentsPerBlock = ConstU32<4>;
+}
+
+// Root is never filtered, so sudo can still dispatch paused calls and unpause them.
+impl pallet_tx_pause::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeCall = RuntimeCall;
+	type PauseOrigin = frame_system::EnsureRoot<AccountId>;
+	type UnpauseOrigin = frame_system::EnsureRoot<AccountId>;
+	type WhitelistedCalls = UnpausableCalls;
+	type MaxNameLen = ConstU32<64>;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -310,19 +803,41 @@ construct_runtime!(
 		NodeBlock = opaque::Block,
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
-		System: frame_system,
-		Timestamp: pallet_timestamp,
-		Aura: pallet_aura,
-		Grandpa: pallet_grandpa,
-		Balances: pallet_balances,
-		TransactionPayment: pallet_transaction_payment,
-		Sudo: pallet_sudo,
+		// Indices are explicit so that existing calls keep their encoding: Aura's index 2 stays
+		// unused and pallets added later take the next free index. The declaration order only
+		// decides the order of the hooks.
+		System: frame_system = 0,
+		Babe: pallet_babe = 19,
+		Timestamp: pallet_timestamp = 1,
+		Grandpa: pallet_grandpa = 3,
+		Balances: pallet_balances = 4,
+		TransactionPayment: pallet_transaction_payment = 5,
+		Sudo: pallet_sudo = 6,
 		// Include the custom logic from the pallet-template in the runtime.
-		TemplateModule: pallet_template,
+		TemplateModule: pallet_template = 7,
 		// Include the custom logic from the pallet-poe in the runtime.
-		PoeModule: pallet_poe,
-		RandomnessModule: pallet_insecure_randomness_collective_flip,
-		KittiesModule: pallet_kitties,
+		PoeModule: pallet_poe = 8,
+		RandomnessModule: pallet_insecure_randomness_collective_flip = 9,
+		KittiesModule: pallet_kitties = 10,
+		KittyArena: pallet_kitty_arena = 11,
+		Assets: pallet_assets = 12,
+		Scheduler: pallet_scheduler = 13,
+		Preimage: pallet_preimage = 14,
+		Council: pallet_collective::<Instance1> = 15,
+		Democracy: pallet_democracy = 16,
+		Authorship: pallet_authorship = 17,
+		Treasury: pallet_treasury = 18,
+		// Staking and ValidatorSet must be before Session, and Babe before all of them.
+		Staking: pallet_staking = 20,
+		Offences: pallet_offences = 21,
+		Historical: pallet_session_historical::{Pallet} = 22,
+		ValidatorSet: pallet_validator_set = 25,
+		Session: pallet_session = 23,
+		ImOnline: pallet_im_online = 24,
+		Utility: pallet_utility = 26,
+		Multisig: pallet_multisig = 27,
+		Proxy: pallet_proxy = 28,
+		TxPause: pallet_tx_pause = 29,
 	}
 );
 
@@ -356,8 +871,12 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
+/// Runtime-level migrations run on the next runtime upgrade, see [`migrations`].
+pub type Migrations = (pallet_template::migrations::v1::MigrateToV1<Runtime, migrations::SudoKey>,);
+
 #[cfg(feature = "runtime-benchmarks")]
 #[macro_use]
 extern crate frame_benchmarking;
@@ -370,6 +889,14 @@ mod benches {
 		[pallet_balances, Balances]
 		[pallet_timestamp, Timestamp]
 		[pallet_template, TemplateModule]
+		[pallet_collective, Council]
+		[pallet_democracy, Democracy]
+		[pallet_preimage, Preimage]
+		[pallet_scheduler, Scheduler]
+		[pallet_treasury, Treasury]
+		[pallet_utility, Utility]
+		[pallet_multisig, Multisig]
+		[pallet_proxy, Proxy]
 	);
 }
 
@@ -431,13 +958,49 @@ impl_runtime_apis! {
 		}
 	}
 
-	impl sp_consensus_aura::AuraApi<Block, AuraId> for Runtime {
-		fn slot_duration() -> sp_consensus_aura::SlotDuration {
-			sp_consensus_aura::SlotDuration::from_millis(Aura::slot_duration())
+	impl sp_consensus_babe::BabeApi<Block> for Runtime {
+		fn configuration() -> sp_consensus_babe::BabeConfiguration {
+			let epoch_config = Babe::epoch_config().unwrap_or(BABE_GENESIS_EPOCH_CONFIG);
+			sp_consensus_babe::BabeConfiguration {
+				slot_duration: Babe::slot_duration(),
+				epoch_length: EpochDuration::get(),
+				c: epoch_config.c,
+				authorities: Babe::authorities().to_vec(),
+				randomness: Babe::randomness(),
+				allowed_slots: epoch_config.allowed_slots,
+			}
+		}
+
+		fn current_epoch_start() -> sp_consensus_babe::Slot {
+			Babe::current_epoch_start()
+		}
+
+		fn current_epoch() -> sp_consensus_babe::Epoch {
+			Babe::current_epoch()
 		}
 
-		fn authorities() -> Vec<AuraId> {
-			Aura::authorities().into_inner()
+		fn next_epoch() -> sp_consensus_babe::Epoch {
+			Babe::next_epoch()
+		}
+
+		fn generate_key_ownership_proof(
+			_slot: sp_consensus_babe::Slot,
+			authority_id: sp_consensus_babe::AuthorityId,
+		) -> Option<sp_consensus_babe::OpaqueKeyOwnershipProof> {
+			use codec::Encode;
+
+			Historical::prove((sp_consensus_babe::KEY_TYPE, authority_id))
+				.map(|p| p.encode())
+				.map(sp_consensus_babe::OpaqueKeyOwnershipProof::new)
+		}
+
+		fn submit_report_equivocation_unsigned_extrinsic(
+			equivocation_proof: sp_consensus_babe::EquivocationProof<<Block as BlockT>::Header>,
+			key_owner_proof: sp_consensus_babe::OpaqueKeyOwnershipProof,
+		) -> Option<()> {
+			let key_owner_proof = key_owner_proof.decode()?;
+
+			Babe::submit_unsigned_equivocation_report(equivocation_proof, key_owner_proof)
 		}
 	}
 
@@ -463,23 +1026,26 @@ impl_runtime_apis! {
 		}
 
 		fn submit_report_equivocation_unsigned_extrinsic(
-			_equivocation_proof: sp_consensus_grandpa::EquivocationProof<
+			equivocation_proof: sp_consensus_grandpa::EquivocationProof<
 				<Block as BlockT>::Hash,
 				NumberFor<Block>,
 			>,
-			_key_owner_proof: sp_consensus_grandpa::OpaqueKeyOwnershipProof,
+			key_owner_proof: sp_consensus_grandpa::OpaqueKeyOwnershipProof,
 		) -> Option<()> {
-			None
+			let key_owner_proof = key_owner_proof.decode()?;
+
+			Grandpa::submit_unsigned_equivocation_report(equivocation_proof, key_owner_proof)
 		}
 
 		fn generate_key_ownership_proof(
 			_set_id: sp_consensus_grandpa::SetId,
-			_authority_id: GrandpaId,
+			authority_id: GrandpaId,
 		) -> Option<sp_consensus_grandpa::OpaqueKeyOwnershipProof> {
-			// NOTE: this is the only implementation possible since we've
-			// defined our key owner proof type as a bottom type (i.e. a type
-			// with no values).
-			None
+			use codec::Encode;
+
+			Historical::prove((sp_consensus_grandpa::KEY_TYPE, authority_id))
+				.map(|p| p.encode())
+				.map(sp_consensus_grandpa::OpaqueKeyOwnershipProof::new)
 		}
 	}
 
@@ -533,6 +1099,65 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_template_runtime_api::TemplateApi<Block, AccountId> for Runtime {
+		fn entry(owner: AccountId, namespace: Vec<u8>, key: Vec<u8>) -> Option<Vec<u8>> {
+			TemplateModule::entry(owner, namespace, key)
+		}
+
+		fn entries(owner: AccountId, namespace: Vec<u8>) -> Vec<(Vec<u8>, Vec<u8>)> {
+			TemplateModule::entries(owner, namespace)
+		}
+
+		fn something(owner: AccountId) -> Option<u32> {
+			TemplateModule::something(owner)
+		}
+	}
+
+	impl pallet_kitties_runtime_api::KittiesApi<Block, AccountId, AssetId, Balance, BlockNumber>
+		for Runtime
+	{
+		fn ancestors(
+			kitty_id: pallet_kitties::KittyId,
+			max_depth: u32,
+		) -> Vec<pallet_kitties::KittyRelative> {
+			KittiesModule::ancestors(kitty_id, max_depth)
+		}
+
+		fn descendants(
+			kitty_id: pallet_kitties::KittyId,
+			max_depth: u32,
+		) -> Vec<pallet_kitties::KittyRelative> {
+			KittiesModule::descendants(kitty_id, max_depth)
+		}
+
+		fn quote_mint_price(collection_id: pallet_kitties::CollectionId) -> Option<Balance> {
+			KittiesModule::quote_mint_price(collection_id)
+		}
+
+		fn quote_breed_price(
+			kitty_id_1: pallet_kitties::KittyId,
+			kitty_id_2: pallet_kitties::KittyId,
+		) -> Option<Balance> {
+			KittiesModule::quote_breed_price(kitty_id_1, kitty_id_2)
+		}
+
+		fn provenance(
+			kitty_id: pallet_kitties::KittyId,
+		) -> Vec<pallet_kitties::ProvenanceRecord<AccountId, BlockNumber, AssetId, Balance>> {
+			KittiesModule::provenance(kitty_id)
+		}
+	}
+
+	impl pallet_tx_pause_runtime_api::TxPauseApi<Block> for Runtime {
+		fn paused_calls() -> Vec<(Vec<u8>, Option<Vec<u8>>)> {
+			TxPause::paused_calls()
+		}
+
+		fn is_paused(pallet: Vec<u8>, call: Vec<u8>) -> bool {
+			TxPause::is_paused(&pallet, &call)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn benchmark_metadata(extra: bool) -> (
@@ -0,0 +1,74 @@
+//! Runtime-level migrations, to be listed in [`crate::Migrations`] for the upgrade they belong to.
+
+use frame_support::{
+	storage::unhashed,
+	traits::{Get, OnRuntimeUpgrade},
+	weights::Weight,
+	StorageHasher, Twox128,
+};
+#[cfg(feature = "try-runtime")]
+use sp_std::vec::Vec;
+
+/// The current sudo key, receiving the global `do_something` value of `pallet_template`.
+pub struct SudoKey;
+
+impl Get<Option<crate::AccountId>> for SudoKey {
+	fn get() -> Option<crate::AccountId> {
+		crate::Sudo::key()
+	}
+}
+
+/// Remove the storage of `pallet_sudo`, i.e. the sudo key.
+///
+/// Sudo stays while the council and referenda are bootstrapped. To retire it, the council brings
+/// a runtime upgrade to referendum that drops `Sudo` from `construct_runtime!` and sets
+/// [`crate::Migrations`] to this migration, so the key is deleted as the upgrade is enacted.
+/// It must not run while `Sudo` is still part of the runtime.
+pub struct RemoveSudo;
+
+impl RemoveSudo {
+	fn prefix() -> [u8; 16] {
+		Twox128::hash(b"Sudo")
+	}
+}
+
+impl OnRuntimeUpgrade for RemoveSudo {
+	fn on_runtime_upgrade() -> Weight {
+		let removed = unhashed::clear_prefix(&Self::prefix(), None, None);
+		<crate::Runtime as frame_system::Config>::DbWeight::get()
+			.reads_writes(removed.loops.into(), removed.backend.into())
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade(_state: Vec<u8>) -> Result<(), &'static str> {
+		if unhashed::contains_prefixed_key(&Self::prefix()) {
+			return Err("sudo storage left after RemoveSudo")
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{AccountId, BuildStorage, Runtime, Sudo, System};
+
+	#[test]
+	fn remove_sudo_clears_the_sudo_prefix() {
+		let mut ext: sp_io::TestExternalities =
+			frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap().into();
+		ext.execute_with(|| {
+			System::set_block_number(1);
+			let key = [RemoveSudo::prefix(), Twox128::hash(b"Key")].concat();
+			unhashed::put(&key, &AccountId::from([1; 32]));
+			unhashed::put(&[&RemoveSudo::prefix()[..], b"stale"].concat(), &1u32);
+			assert_eq!(Sudo::key(), Some(AccountId::from([1; 32])));
+
+			assert_ne!(RemoveSudo::on_runtime_upgrade(), Weight::zero());
+
+			assert!(!unhashed::contains_prefixed_key(&RemoveSudo::prefix()));
+			assert_eq!(Sudo::key(), None);
+			assert_eq!(System::block_number(), 1);
+		});
+	}
+}